@@ -0,0 +1,192 @@
+//! Adam7 interlacing: seven passes over progressively finer pixel grids.
+
+use crate::{filter, ihdr::Ihdr, image::Image, Error};
+
+/// `(x start, y start, x step, y step)` of each pass.
+const PASSES: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// Width and height in pixels of each pass of a `width` x `height` image. Passes with a zero
+/// dimension are empty and contribute no scanlines.
+pub fn pass_sizes(width: u32, height: u32) -> [(u32, u32); 7] {
+    PASSES.map(|(x0, y0, dx, dy)| {
+        (
+            width.saturating_sub(x0).div_ceil(dx),
+            height.saturating_sub(y0).div_ceil(dy),
+        )
+    })
+}
+
+/// Length of the inflated image data of an interlaced image: every scanline of every non-empty
+/// pass, each with its filter type byte. `None` if it overflows `usize`.
+pub fn filtered_len(ihdr: &Ihdr) -> Option<usize> {
+    pass_sizes(ihdr.width(), ihdr.height())
+        .into_iter()
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .try_fold(0_usize, |len, (w, h)| {
+            (ihdr.row_bytes(w) + 1)
+                .checked_mul(h as usize)
+                .and_then(|pass| len.checked_add(pass))
+        })
+}
+
+/// Reconstructs the full image rows from the inflated data of an interlaced image, unfiltering
+/// each pass on its own.
+pub fn deinterlace(ihdr: &Ihdr, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let bits = ihdr.bits_per_pixel();
+    let bpp = bits.div_ceil(8);
+    let row_bytes = ihdr.row_bytes(ihdr.width());
+    let mut out = vec![0; row_bytes * ihdr.height() as usize];
+    let mut offset = 0;
+    for ((x0, y0, dx, dy), (w, h)) in PASSES
+        .into_iter()
+        .zip(pass_sizes(ihdr.width(), ihdr.height()))
+    {
+        if w == 0 || h == 0 {
+            continue;
+        }
+        let pass_row_bytes = ihdr.row_bytes(w);
+        let len = (pass_row_bytes + 1) * h as usize;
+        let pass = data
+            .get(offset..offset + len)
+            .ok_or_else(|| Error::InvalidImage("interlaced image data is too short".to_owned()))?;
+        offset += len;
+        let pass = filter::unfilter(pass, pass_row_bytes, h as usize, bpp)?;
+
+        for (py, src) in pass.chunks_exact(pass_row_bytes).enumerate() {
+            let y = (y0 + py as u32 * dy) as usize;
+            let dst = &mut out[y * row_bytes..(y + 1) * row_bytes];
+            for px in 0..w as usize {
+                let x = (x0 + px as u32 * dx) as usize;
+                copy_pixel(src, px, dst, x, bits);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Splits an image into the unfiltered rows of each pass, ready to be filtered and compressed
+/// in order. Empty passes yield an empty buffer.
+pub fn interlace(image: &Image) -> Vec<Vec<u8>> {
+    let bits = image.color_type().channels() as usize * image.bit_depth() as usize;
+    let row_bytes = |w: u32| (w as usize * bits).div_ceil(8);
+    PASSES
+        .into_iter()
+        .zip(pass_sizes(image.width(), image.height()))
+        .map(|((x0, y0, dx, dy), (w, h))| {
+            let pass_row_bytes = row_bytes(w);
+            let mut pass = vec![0; pass_row_bytes * h as usize];
+            for py in 0..h as usize {
+                let src = image
+                    .row(y0 + py as u32 * dy)
+                    .expect("pass rows are inside the image");
+                let dst = &mut pass[py * pass_row_bytes..(py + 1) * pass_row_bytes];
+                for px in 0..w as usize {
+                    copy_pixel(src, (x0 + px as u32 * dx) as usize, dst, px, bits);
+                }
+            }
+            pass
+        })
+        .collect()
+}
+
+/// Copies pixel `sx` of `src` to pixel `dx` of `dst`, both packed at `bits` bits per pixel.
+fn copy_pixel(src: &[u8], sx: usize, dst: &mut [u8], dx: usize, bits: usize) {
+    if bits >= 8 {
+        let bytes = bits / 8;
+        dst[dx * bytes..(dx + 1) * bytes].copy_from_slice(&src[sx * bytes..(sx + 1) * bytes]);
+        return;
+    }
+    let mask = ((1_u16 << bits) - 1) as u8;
+    let (s, d) = (sx * bits, dx * bits);
+    let value = src[s / 8] >> (8 - bits - s % 8) & mask;
+    let shift = 8 - bits - d % 8;
+    dst[d / 8] = dst[d / 8] & !(mask << shift) | value << shift;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::{ColorType, Interlace};
+
+    fn with_filter_bytes(ihdr: &Ihdr, passes: &[Vec<u8>]) -> Vec<u8> {
+        pass_sizes(ihdr.width(), ihdr.height())
+            .into_iter()
+            .zip(passes)
+            .filter(|((w, h), _)| *w > 0 && *h > 0)
+            .flat_map(|((w, _), pass)| {
+                pass.chunks_exact(ihdr.row_bytes(w))
+                    .flat_map(|row| [&[0][..], row].concat())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pass_sizes() {
+        assert_eq!(
+            pass_sizes(1, 1),
+            [(1, 1), (0, 1), (1, 0), (0, 1), (1, 0), (0, 1), (1, 0)]
+        );
+        assert_eq!(
+            pass_sizes(8, 8),
+            [(1, 1), (1, 1), (2, 1), (2, 2), (4, 2), (4, 4), (8, 4)]
+        );
+        assert_eq!(pass_sizes(3, 2)[6], (3, 1));
+    }
+
+    #[test]
+    fn test_filtered_len() {
+        let ihdr = Ihdr::new(8, 8, 8, ColorType::Grayscale, Interlace::Adam7).unwrap();
+        assert_eq!(filtered_len(&ihdr), Some(64 + 15));
+        let ihdr = Ihdr::new(1, 1, 1, ColorType::Grayscale, Interlace::Adam7).unwrap();
+        assert_eq!(filtered_len(&ihdr), Some(2));
+    }
+
+    #[test]
+    fn test_interlace_round_trip() {
+        for (width, height, bit_depth, color_type) in [
+            (1, 1, 8, ColorType::Grayscale),
+            (3, 2, 1, ColorType::Grayscale),
+            (5, 9, 2, ColorType::Indexed),
+            (13, 11, 4, ColorType::Grayscale),
+            (9, 17, 16, ColorType::Rgb),
+            (33, 7, 8, ColorType::Rgba),
+        ] {
+            let ihdr = Ihdr::new(width, height, bit_depth, color_type, Interlace::Adam7).unwrap();
+            let len = ihdr.row_bytes(width) * height as usize;
+            let data = (0..len).map(|i| (i * 37 % 251) as u8).collect::<Vec<_>>();
+            // Padding bits at the end of each row are not pixels and do not survive.
+            let image = Image::new(width, height, color_type, bit_depth, data).unwrap();
+            let image = Image::new(
+                width,
+                height,
+                color_type,
+                bit_depth,
+                deinterlace(&ihdr, &with_filter_bytes(&ihdr, &interlace(&image))).unwrap(),
+            )
+            .unwrap();
+
+            let passes = interlace(&image);
+            let filtered = with_filter_bytes(&ihdr, &passes);
+            assert_eq!(Some(filtered.len()), filtered_len(&ihdr));
+            assert_eq!(deinterlace(&ihdr, &filtered).unwrap(), image.data());
+        }
+    }
+
+    #[test]
+    fn test_deinterlace_places_pixels() {
+        let ihdr = Ihdr::new(3, 2, 8, ColorType::Grayscale, Interlace::Adam7).unwrap();
+        // Pass 1 holds (0, 0), pass 4 holds (2, 0), pass 6 holds (1, 0), pass 7 holds row 1.
+        let data = [0, 1, 0, 2, 0, 3, 0, 4, 5, 6];
+        assert_eq!(deinterlace(&ihdr, &data).unwrap(), [1, 3, 2, 4, 5, 6]);
+        assert!(deinterlace(&ihdr, &data[..9]).is_err());
+    }
+}
@@ -0,0 +1,574 @@
+//! Animated PNG chunks: `acTL`, `fcTL` and `fdAT`.
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    color::{check_type, fixed, malformed},
+    ihdr::{ColorType, Ihdr},
+    image::Image,
+    Error,
+};
+
+/// The frame and loop counts from an `acTL` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AnimationControl {
+    num_frames: u32,
+    num_plays: u32,
+}
+
+impl TryFrom<&[u8]> for AnimationControl {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 8] = fixed(data, ChunkType::ACTL)?;
+        let num_frames = u32::from_be_bytes(data[..4].try_into().unwrap());
+        if num_frames == 0 {
+            return Err(malformed(ChunkType::ACTL, "zero frames".to_owned()));
+        }
+        Ok(Self {
+            num_frames,
+            num_plays: u32::from_be_bytes(data[4..].try_into().unwrap()),
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for AnimationControl {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::ACTL)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for AnimationControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} frames, ", self.num_frames)?;
+        match self.num_plays {
+            0 => f.write_str("loops forever"),
+            1 => f.write_str("plays once"),
+            n => write!(f, "plays {n} times"),
+        }
+    }
+}
+
+impl AnimationControl {
+    /// Creates animation control for `num_frames` frames played `num_plays` times, or forever if
+    /// `num_plays` is 0.
+    pub const fn new(num_frames: u32, num_plays: u32) -> Self {
+        Self {
+            num_frames,
+            num_plays,
+        }
+    }
+    /// Number of frames, including the default image if it is part of the animation.
+    pub const fn num_frames(&self) -> u32 {
+        self.num_frames
+    }
+    /// Number of times to play the animation, 0 for forever.
+    pub const fn num_plays(&self) -> u32 {
+        self.num_plays
+    }
+    /// Serializes the control as an `acTL` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = self.num_frames.to_be_bytes().to_vec();
+        data.extend_from_slice(&self.num_plays.to_be_bytes());
+        Chunk::new(ChunkType::ACTL, data)
+    }
+}
+
+/// How a frame's region is treated before the next frame is rendered.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DisposeOp {
+    /// Leave the region as it is.
+    None = 0,
+    /// Clear the region to fully transparent black.
+    Background = 1,
+    /// Restore the region to what it was before the frame.
+    Previous = 2,
+}
+
+/// How a frame is combined with the region it covers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BlendOp {
+    /// Replace the region, alpha included.
+    Source = 0,
+    /// Alpha-composite the frame over the region.
+    Over = 1,
+}
+
+/// The region, timing and compositing of one frame, from an `fcTL` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FrameControl {
+    sequence_number: u32,
+    width: u32,
+    height: u32,
+    x_offset: u32,
+    y_offset: u32,
+    delay_num: u16,
+    delay_den: u16,
+    dispose_op: DisposeOp,
+    blend_op: BlendOp,
+}
+
+impl TryFrom<&[u8]> for FrameControl {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 26] = fixed(data, ChunkType::FCTL)?;
+        let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+        let dispose_op = match data[24] {
+            0 => DisposeOp::None,
+            1 => DisposeOp::Background,
+            2 => DisposeOp::Previous,
+            op => {
+                return Err(malformed(
+                    ChunkType::FCTL,
+                    format!("invalid dispose op {op}"),
+                ))
+            }
+        };
+        let blend_op = match data[25] {
+            0 => BlendOp::Source,
+            1 => BlendOp::Over,
+            op => return Err(malformed(ChunkType::FCTL, format!("invalid blend op {op}"))),
+        };
+        let (width, height) = (u32_at(4), u32_at(8));
+        if width == 0 || height == 0 {
+            return Err(malformed(
+                ChunkType::FCTL,
+                format!("invalid frame size {width}x{height}"),
+            ));
+        }
+
+        Ok(Self {
+            sequence_number: u32_at(0),
+            width,
+            height,
+            x_offset: u32_at(12),
+            y_offset: u32_at(16),
+            delay_num: u16_at(20),
+            delay_den: u16_at(22),
+            dispose_op,
+            blend_op,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for FrameControl {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::FCTL)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for FrameControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} at ({}, {}), {} s, dispose {:?}, blend {:?}",
+            self.width,
+            self.height,
+            self.x_offset,
+            self.y_offset,
+            self.delay(),
+            self.dispose_op,
+            self.blend_op
+        )
+    }
+}
+
+impl FrameControl {
+    /// Creates frame control for a `width` x `height` region at the given offset, shown for
+    /// `delay_num / delay_den` seconds.
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        sequence_number: u32,
+        width: u32,
+        height: u32,
+        x_offset: u32,
+        y_offset: u32,
+        delay_num: u16,
+        delay_den: u16,
+        dispose_op: DisposeOp,
+        blend_op: BlendOp,
+    ) -> Self {
+        Self {
+            sequence_number,
+            width,
+            height,
+            x_offset,
+            y_offset,
+            delay_num,
+            delay_den,
+            dispose_op,
+            blend_op,
+        }
+    }
+    /// Position of the chunk in the animation's shared `fcTL` / `fdAT` sequence.
+    pub const fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+    /// Frame width in pixels.
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+    /// Frame height in pixels.
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+    /// Distance from the left edge of the canvas.
+    pub const fn x_offset(&self) -> u32 {
+        self.x_offset
+    }
+    /// Distance from the top edge of the canvas.
+    pub const fn y_offset(&self) -> u32 {
+        self.y_offset
+    }
+    /// Delay numerator.
+    pub const fn delay_num(&self) -> u16 {
+        self.delay_num
+    }
+    /// Delay denominator. 0 means 100.
+    pub const fn delay_den(&self) -> u16 {
+        self.delay_den
+    }
+    /// How long the frame is shown, in seconds.
+    pub fn delay(&self) -> f64 {
+        let den = match self.delay_den {
+            0 => 100,
+            den => den,
+        };
+        f64::from(self.delay_num) / f64::from(den)
+    }
+    /// How the frame's region is treated before the next frame.
+    pub const fn dispose_op(&self) -> DisposeOp {
+        self.dispose_op
+    }
+    /// How the frame is combined with the canvas.
+    pub const fn blend_op(&self) -> BlendOp {
+        self.blend_op
+    }
+    /// Checks that the frame lies inside the canvas.
+    pub fn check(&self, ihdr: &Ihdr) -> Result<(), Error> {
+        let inside = |offset: u32, size: u32, canvas: u32| {
+            offset.checked_add(size).is_some_and(|end| end <= canvas)
+        };
+        if !inside(self.x_offset, self.width, ihdr.width())
+            || !inside(self.y_offset, self.height, ihdr.height())
+        {
+            return Err(malformed(
+                ChunkType::FCTL,
+                format!(
+                    "{}x{} frame at ({}, {}) exceeds the {}x{} canvas",
+                    self.width,
+                    self.height,
+                    self.x_offset,
+                    self.y_offset,
+                    ihdr.width(),
+                    ihdr.height()
+                ),
+            ));
+        }
+        Ok(())
+    }
+    /// Serializes the control as an `fcTL` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(26);
+        for value in [
+            self.sequence_number,
+            self.width,
+            self.height,
+            self.x_offset,
+            self.y_offset,
+        ] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&self.delay_num.to_be_bytes());
+        data.extend_from_slice(&self.delay_den.to_be_bytes());
+        data.extend_from_slice(&[self.dispose_op as u8, self.blend_op as u8]);
+        Chunk::new(ChunkType::FCTL, data)
+    }
+}
+
+/// One frame of an animation: its control and compressed image data.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Frame {
+    control: FrameControl,
+    data: Vec<u8>,
+    default_image: bool,
+}
+
+impl Frame {
+    /// The frame's region, timing and compositing.
+    pub const fn control(&self) -> &FrameControl {
+        &self.control
+    }
+    /// The zlib-compressed image data: `IDAT` contents for the default image, `fdAT` contents
+    /// without sequence numbers otherwise.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Whether the frame is the default image, shown by decoders that do not support APNG.
+    pub const fn is_default_image(&self) -> bool {
+        self.default_image
+    }
+}
+
+/// The animation of an APNG: its `acTL` chunk and frames in order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Animation {
+    control: AnimationControl,
+    frames: Vec<Frame>,
+}
+
+impl Animation {
+    /// Collects the animation from `chunks`, or `None` if there is no `acTL` chunk. Fails if the
+    /// animation chunks are malformed or an `fdAT` chunk comes before any `fcTL`.
+    pub fn from_chunks(chunks: &[Chunk]) -> Result<Option<Self>, Error> {
+        let Some(actl) = chunks.iter().find(|c| *c.r#type() == ChunkType::ACTL) else {
+            return Ok(None);
+        };
+        let control = AnimationControl::try_from(actl)?;
+        let mut frames: Vec<Frame> = vec![];
+        let mut seen_idat = false;
+        for chunk in chunks {
+            match *chunk.r#type() {
+                ChunkType::FCTL => frames.push(Frame {
+                    control: FrameControl::try_from(chunk)?,
+                    data: vec![],
+                    default_image: !seen_idat,
+                }),
+                ChunkType::IDAT => {
+                    seen_idat = true;
+                    if let Some(frame) = frames.last_mut().filter(|f| f.default_image) {
+                        frame.data.extend_from_slice(chunk.data());
+                    }
+                }
+                ChunkType::FDAT => {
+                    let (_, data) = frame_data(chunk)?;
+                    frames
+                        .last_mut()
+                        .filter(|f| !f.default_image)
+                        .ok_or_else(|| {
+                            malformed(
+                                ChunkType::FDAT,
+                                "no fcTL precedes the frame data".to_owned(),
+                            )
+                        })?
+                        .data
+                        .extend_from_slice(data);
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(Self { control, frames }))
+    }
+    /// The frame and loop counts.
+    pub const fn control(&self) -> &AnimationControl {
+        &self.control
+    }
+    /// Every frame in display order.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+    /// Renders every frame onto a canvas the size of `ihdr`, following each frame's blend and
+    /// dispose operations, and returns the canvas as shown for each frame. `decode` turns a
+    /// frame's header and compressed data into an RGBA image at the canvas bit depth.
+    pub(crate) fn composite(
+        &self,
+        ihdr: &Ihdr,
+        mut decode: impl FnMut(&Ihdr, &[u8]) -> Result<Image, Error>,
+    ) -> Result<Vec<Image>, Error> {
+        let depth = if ihdr.bit_depth() == 16 { 16 } else { 8 };
+        let pixel = 4 * depth / 8;
+        let stride = ihdr.width() as usize * pixel;
+        let mut canvas = vec![0; stride * ihdr.height() as usize];
+        let mut rendered = Vec::with_capacity(self.frames.len());
+        for frame in &self.frames {
+            let control = &frame.control;
+            control.check(ihdr)?;
+            let frame_ihdr = Ihdr::new(
+                control.width,
+                control.height,
+                ihdr.bit_depth(),
+                ihdr.color_type(),
+                ihdr.interlace(),
+            )?;
+            let image = decode(&frame_ihdr, &frame.data)?;
+            // Rows of the canvas covered by the frame, as byte ranges.
+            let region = (0..control.height as usize).map(|y| {
+                let start =
+                    (control.y_offset as usize + y) * stride + control.x_offset as usize * pixel;
+                start..start + control.width as usize * pixel
+            });
+            let previous = (control.dispose_op == DisposeOp::Previous).then(|| canvas.clone());
+            for (range, row) in region.clone().zip(image.rows()) {
+                let dst = &mut canvas[range];
+                match control.blend_op {
+                    BlendOp::Source => dst.copy_from_slice(row),
+                    BlendOp::Over => {
+                        for (dst, src) in dst.chunks_exact_mut(pixel).zip(row.chunks_exact(pixel)) {
+                            blend_over(dst, src, depth);
+                        }
+                    }
+                }
+            }
+            rendered.push(Image::new(
+                ihdr.width(),
+                ihdr.height(),
+                ColorType::Rgba,
+                depth as u8,
+                canvas.clone(),
+            )?);
+            match (control.dispose_op, previous) {
+                (DisposeOp::Previous, Some(previous)) => canvas = previous,
+                (DisposeOp::Background, _) => {
+                    for range in region {
+                        canvas[range].fill(0);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+/// The sequence number of an `fcTL` or `fdAT` chunk, or `None` for other chunk types or if the
+/// chunk is too short.
+pub fn sequence_number(chunk: &Chunk) -> Option<u32> {
+    match *chunk.r#type() {
+        ChunkType::FCTL | ChunkType::FDAT => chunk
+            .data()
+            .first_chunk()
+            .map(|bytes| u32::from_be_bytes(*bytes)),
+        _ => None,
+    }
+}
+
+/// Creates an `fdAT` chunk holding compressed frame data.
+pub fn frame_data_chunk(sequence_number: u32, data: &[u8]) -> Chunk {
+    let mut bytes = sequence_number.to_be_bytes().to_vec();
+    bytes.extend_from_slice(data);
+    Chunk::new(ChunkType::FDAT, bytes)
+}
+
+/// Composites the non-premultiplied RGBA pixel `src` over `dst` in place.
+fn blend_over(dst: &mut [u8], src: &[u8], depth: usize) {
+    let bytes = depth / 8;
+    let read = |pixel: &[u8], c: usize| {
+        pixel[c * bytes..(c + 1) * bytes]
+            .iter()
+            .fold(0, |sample, &byte| sample << 8 | u64::from(byte))
+    };
+    let max = (1 << depth) - 1;
+    let src_alpha = read(src, 3);
+    let src_weight = src_alpha * max;
+    let dst_weight = (max - src_alpha) * read(dst, 3);
+    let total = src_weight + dst_weight;
+    let mut out = [0; 4];
+    for (c, value) in out.iter_mut().take(3).enumerate() {
+        *value = (read(src, c) * src_weight + read(dst, c) * dst_weight)
+            .checked_div(total)
+            .unwrap_or(0);
+    }
+    out[3] = total / max;
+    for (c, value) in out.into_iter().enumerate() {
+        dst[c * bytes..(c + 1) * bytes].copy_from_slice(&value.to_be_bytes()[8 - bytes..]);
+    }
+}
+
+/// Splits an `fdAT` chunk into its sequence number and frame data.
+fn frame_data(chunk: &Chunk) -> Result<(u32, &[u8]), Error> {
+    check_type(chunk, ChunkType::FDAT)?;
+    match chunk.data().split_first_chunk() {
+        Some((number, data)) if !data.is_empty() => Ok((u32::from_be_bytes(*number), data)),
+        _ => Err(malformed(
+            ChunkType::FDAT,
+            format!("{} bytes is too short for frame data", chunk.length()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::{ColorType, Interlace};
+
+    fn frame(sequence_number: u32, x: u32, y: u32) -> Chunk {
+        FrameControl::new(
+            sequence_number,
+            2,
+            2,
+            x,
+            y,
+            1,
+            10,
+            DisposeOp::Background,
+            BlendOp::Over,
+        )
+        .to_chunk()
+    }
+
+    #[test]
+    fn test_frame_control() {
+        let ihdr = Ihdr::new(4, 4, 8, ColorType::Rgba, Interlace::None).unwrap();
+        let chunk = frame(3, 2, 2);
+        let fctl = FrameControl::try_from(&chunk).unwrap();
+        assert_eq!(fctl.to_chunk().data(), chunk.data());
+        assert_eq!(sequence_number(&chunk), Some(3));
+        assert_eq!(fctl.delay(), 0.1);
+        assert_eq!(
+            fctl.to_string(),
+            "2x2 at (2, 2), 0.1 s, dispose Background, blend Over"
+        );
+        assert!(fctl.check(&ihdr).is_ok());
+        assert!(FrameControl::try_from(&frame(0, 3, 0))
+            .unwrap()
+            .check(&ihdr)
+            .is_err());
+
+        let mut data = chunk.data().to_vec();
+        data[24] = 3;
+        assert!(FrameControl::try_from(&Chunk::new(ChunkType::FCTL, data)).is_err());
+        assert!(AnimationControl::try_from(&AnimationControl::new(0, 0).to_chunk()).is_err());
+        assert_eq!(
+            AnimationControl::new(2, 0).to_string(),
+            "2 frames, loops forever"
+        );
+    }
+
+    #[test]
+    fn test_animation() {
+        let chunks = [
+            AnimationControl::new(3, 1).to_chunk(),
+            frame(0, 0, 0),
+            Chunk::new(ChunkType::IDAT, [1, 2]),
+            Chunk::new(ChunkType::IDAT, [3]),
+            frame(1, 1, 1),
+            frame_data_chunk(2, &[4]),
+            frame_data_chunk(3, &[5]),
+            frame(4, 2, 2),
+            frame_data_chunk(5, &[6]),
+        ];
+        let animation = Animation::from_chunks(&chunks).unwrap().unwrap();
+        assert_eq!(animation.control().num_plays(), 1);
+        let frames = animation.frames();
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].is_default_image());
+        assert_eq!(frames[0].data(), [1, 2, 3]);
+        assert_eq!(frames[1].data(), [4, 5]);
+        assert_eq!(frames[2].control().x_offset(), 2);
+        assert!(!frames[2].is_default_image());
+
+        assert!(Animation::from_chunks(&chunks[1..]).unwrap().is_none());
+        // Frame data directly after the default image's IDAT has no fcTL of its own.
+        let mut orphan = chunks.to_vec();
+        orphan.remove(4);
+        assert!(Animation::from_chunks(&orphan).is_err());
+    }
+}
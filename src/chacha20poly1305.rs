@@ -0,0 +1,274 @@
+//! The ChaCha20-Poly1305 authenticated cipher (RFC 8439).
+
+use crate::Error;
+
+/// Size of a key in bytes.
+pub const KEY_LEN: usize = 32;
+/// Size of a nonce in bytes.
+pub const NONCE_LEN: usize = 12;
+/// Size of the authentication tag appended to the ciphertext.
+pub const TAG_LEN: usize = 16;
+
+/// Encrypts `plaintext` and returns the ciphertext followed by a tag authenticating it and
+/// `aad`. A nonce must never be reused with the same key.
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut out = plaintext.to_vec();
+    chacha20_xor(key, nonce, 1, &mut out);
+    let tag = tag(key, nonce, aad, &out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+/// Checks the tag on the output of [`seal`] and decrypts it. Fails with
+/// [`Error::DecryptionFailed`] if the key is wrong or the ciphertext or `aad` was modified.
+pub fn open(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let split = sealed
+        .len()
+        .checked_sub(TAG_LEN)
+        .ok_or(Error::DecryptionFailed)?;
+    let (ciphertext, stored) = sealed.split_at(split);
+    let computed = tag(key, nonce, aad, ciphertext);
+    // Compare without an early exit so timing does not reveal how much of the tag matched.
+    if stored
+        .iter()
+        .zip(computed)
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        != 0
+    {
+        return Err(Error::DecryptionFailed);
+    }
+    let mut out = ciphertext.to_vec();
+    chacha20_xor(key, nonce, 1, &mut out);
+    Ok(out)
+}
+
+fn tag(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let block = chacha20_block(key, nonce, 0);
+    let mut mac = Poly1305::new(block[..32].try_into().unwrap());
+    mac.update_padded(aad);
+    mac.update_padded(ciphertext);
+    let mut lengths = [0; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64).to_le_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    mac.update_padded(&lengths);
+    mac.finalize()
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn chacha20_block(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], counter: u32) -> [u8; 64] {
+    let word = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+    let mut input = [
+        0x61707865, 0x3320646e, 0x79622d32, 0x6b206574, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    for (i, bytes) in key.chunks_exact(4).enumerate() {
+        input[4 + i] = word(bytes);
+    }
+    input[12] = counter;
+    for (i, bytes) in nonce.chunks_exact(4).enumerate() {
+        input[13 + i] = word(bytes);
+    }
+
+    let mut state = input;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+    let mut out = [0; 64];
+    for ((bytes, word), input) in out.chunks_exact_mut(4).zip(state).zip(input) {
+        bytes.copy_from_slice(&word.wrapping_add(input).to_le_bytes());
+    }
+    out
+}
+
+/// XORs `data` with the key stream starting at block `counter`.
+fn chacha20_xor(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], counter: u32, data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let block = chacha20_block(key, nonce, counter.wrapping_add(i as u32));
+        for (byte, key) in chunk.iter_mut().zip(block) {
+            *byte ^= key;
+        }
+    }
+}
+
+/// The Poly1305 one-time authenticator, using five 26-bit limbs so products fit in 64 bits.
+struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    s: [u32; 4],
+}
+
+impl Poly1305 {
+    fn new(key: &[u8; 32]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(key[i..i + 4].try_into().unwrap());
+        Self {
+            r: [
+                word(0) & 0x3ff_ffff,
+                (word(3) >> 2) & 0x3ff_ff03,
+                (word(6) >> 4) & 0x3ff_c0ff,
+                (word(9) >> 6) & 0x3f0_3fff,
+                (word(12) >> 8) & 0x00f_ffff,
+            ],
+            h: [0; 5],
+            s: [word(16), word(20), word(24), word(28)],
+        }
+    }
+    /// Absorbs `data`, zero-padding its last block to 16 bytes.
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.block(&block, 1 << 24);
+        }
+    }
+    /// Absorbs one block; `high_bit` is the 2^128 term, zero only for a short final block that
+    /// was padded with a 1 byte.
+    fn block(&mut self, block: &[u8; 16], high_bit: u32) {
+        let word = |i: usize| u32::from_le_bytes(block[i..i + 4].try_into().unwrap());
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+        let h = &mut self.h;
+        h[0] += word(0) & 0x3ff_ffff;
+        h[1] += (word(3) >> 2) & 0x3ff_ffff;
+        h[2] += (word(6) >> 4) & 0x3ff_ffff;
+        h[3] += (word(9) >> 6) & 0x3ff_ffff;
+        h[4] += (word(12) >> 8) | high_bit;
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+
+        let d = [
+            h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1,
+            h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2,
+            h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3,
+            h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4,
+            h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0,
+        ];
+        let mut carry = 0;
+        for (h, d) in h.iter_mut().zip(d) {
+            let d = d + carry;
+            *h = (d & 0x3ff_ffff) as u32;
+            carry = d >> 26;
+        }
+        h[0] += carry as u32 * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= 0x3ff_ffff;
+    }
+    fn finalize(self) -> [u8; 16] {
+        let mut h = self.h;
+        // Fully carry h, then compute h - p and keep it if it did not underflow.
+        for i in 1..5 {
+            h[i] += h[i - 1] >> 26;
+            h[i - 1] &= 0x3ff_ffff;
+        }
+        h[0] += (h[4] >> 26) * 5;
+        h[4] &= 0x3ff_ffff;
+        h[1] += h[0] >> 26;
+        h[0] &= 0x3ff_ffff;
+
+        let mut g = [0; 5];
+        let mut carry = 5;
+        for (g, h) in g.iter_mut().zip(h) {
+            let sum = h + carry;
+            *g = sum & 0x3ff_ffff;
+            carry = sum >> 26;
+        }
+        g[4] = (g[4] | carry << 26).wrapping_sub(1 << 26);
+        let keep_g = (g[4] >> 31).wrapping_sub(1);
+        for (h, g) in h.iter_mut().zip(g) {
+            *h = (*h & !keep_g) | (g & keep_g);
+        }
+
+        let words = [
+            h[0] | h[1] << 26,
+            h[1] >> 6 | h[2] << 20,
+            h[2] >> 12 | h[3] << 14,
+            h[3] >> 18 | h[4] << 8,
+        ];
+        let mut out = [0; 16];
+        let mut carry = 0;
+        for ((bytes, word), s) in out.chunks_exact_mut(4).zip(words).zip(self.s) {
+            let sum = u64::from(word) + u64::from(s) + carry;
+            bytes.copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn test_poly1305() {
+        // RFC 8439 section 2.5.2.
+        let key = hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        let mut mac = Poly1305::new(&key.unwrap());
+        for chunk in b"Cryptographic Forum Research Group".chunks(16) {
+            if chunk.len() == 16 {
+                mac.block(chunk.try_into().unwrap(), 1 << 24);
+            } else {
+                let mut block = [0; 16];
+                block[..chunk.len()].copy_from_slice(chunk);
+                block[chunk.len()] = 1;
+                mac.block(&block, 0);
+            }
+        }
+        assert_eq!(
+            mac.finalize(),
+            hex::decode("a8061dc1305136c6c22b8baf0c0127a9").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        // RFC 8439 section 2.8.2.
+        let key: [u8; 32] = std::array::from_fn(|i| 0x80 + i as u8);
+        let nonce = hex::decode("070000004041424344454647").unwrap();
+        let aad = hex::decode::<12>("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only \
+            one tip for the future, sunscreen would be it.";
+        let sealed = seal(&key, &nonce, &aad, plaintext);
+        assert_eq!(
+            sealed,
+            hex::decode::<130>(
+                "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+                 3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+                 92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+                 3ff4def08e4b7a9de576d26586cec64b6116\
+                 1ae10b594f09e26a7e902ecbd0600691"
+            )
+            .unwrap()
+        );
+        assert_eq!(open(&key, &nonce, &aad, &sealed).unwrap(), plaintext);
+
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 1;
+        assert!(matches!(
+            open(&key, &nonce, &aad, &tampered),
+            Err(Error::DecryptionFailed)
+        ));
+        assert!(open(&key, &nonce, b"", &sealed).is_err());
+        assert!(open(&key, &nonce, &aad, &sealed[..15]).is_err());
+    }
+}
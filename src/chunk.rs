@@ -1,9 +1,12 @@
+//! Length-prefixed, CRC-checked PNG chunks.
+
 use std::sync::OnceLock;
 
 use crate::{chunk_type::ChunkType, Error};
 
 static CRC32_TABLE: OnceLock<[u32; 256]> = OnceLock::new();
 
+/// A single PNG chunk: length, type, data and CRC.
 #[derive(Debug, Clone)]
 pub struct Chunk {
     length: u32,
@@ -16,30 +19,41 @@ impl TryFrom<&[u8]> for Chunk {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let length = u32::from_be_bytes(value.get(..4).ok_or("Invalid chunk length")?.try_into()?);
-        if length > 2_u32.pow(31) || length != value.len() as u32 - 12 {
-            return Err(Error::from("Invalid chunk length"));
+        let Some(header) = value.get(..8) else {
+            return Err(Error::Truncated {
+                offset: 0,
+                needed: 12,
+            });
+        };
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        if length > Self::MAX_LENGTH {
+            return Err(Error::InvalidLength { offset: 0, length });
+        }
+        let needed = length as usize + 12;
+        if value.len() < needed {
+            return Err(Error::Truncated { offset: 0, needed });
+        } else if value.len() > needed {
+            return Err(Error::InvalidLength { offset: 0, length });
         }
-        let type_bytes: [u8; 4] = value
-            .get(4..8)
-            .ok_or("Invalid chunk length: chunk type")?
-            .try_into()?;
+        let type_bytes = [header[4], header[5], header[6], header[7]];
         let r#type = ChunkType::try_from(type_bytes)?;
         let crc_offset = value.len() - 4;
-        let data = value
-            .get(8..crc_offset)
-            .ok_or("Invalid chunk length: chunk data")?
-            .into();
-        let crc = u32::from_be_bytes(
-            value
-                .get(crc_offset..)
-                .ok_or("Invalid chunk length: chunk CRC")?
-                .try_into()?,
-        );
+        let data: Box<[u8]> = value[8..crc_offset].into();
+        let crc = u32::from_be_bytes([
+            value[crc_offset],
+            value[crc_offset + 1],
+            value[crc_offset + 2],
+            value[crc_offset + 3],
+        ]);
         let calculated_crc = crc32(type_bytes, &data);
 
         if crc != calculated_crc {
-            Err(format!("Invalid chunk CRC: read: {crc}, calculated: {calculated_crc}").into())
+            Err(Error::CrcMismatch {
+                chunk_type: r#type,
+                offset: crc_offset,
+                stored: crc,
+                computed: calculated_crc,
+            })
         } else {
             Ok(Self {
                 length,
@@ -58,6 +72,10 @@ impl std::fmt::Display for Chunk {
 }
 
 impl Chunk {
+    /// The largest data length allowed by the PNG specification.
+    pub const MAX_LENGTH: u32 = (1 << 31) - 1;
+
+    /// Creates a chunk from its type and data, computing the length and CRC.
     pub fn new(r#type: ChunkType, data: impl AsRef<[u8]>) -> Self {
         let data: Box<[u8]> = data.as_ref().into();
         let length = data.len() as u32;
@@ -70,50 +88,78 @@ impl Chunk {
             crc,
         }
     }
+    /// Builds a chunk whose CRC has already been computed and checked.
+    pub(crate) fn from_parts(r#type: ChunkType, data: Box<[u8]>, crc: u32) -> Self {
+        Self {
+            length: data.len() as u32,
+            r#type,
+            data,
+            crc,
+        }
+    }
+    /// Length of the chunk data in bytes.
     pub const fn length(&self) -> u32 {
         self.length
     }
+    /// The chunk type code.
     pub const fn r#type(&self) -> &ChunkType {
         &self.r#type
     }
+    /// The chunk data, excluding length, type and CRC.
     pub const fn data(&self) -> &[u8] {
         &self.data
     }
+    /// CRC-32 over the chunk type and data.
     pub const fn crc(&self) -> u32 {
         self.crc
     }
+    /// The chunk data decoded as UTF-8.
     pub fn data_as_string(&self) -> Result<String, Error> {
         Ok(std::str::from_utf8(&self.data)?.to_owned())
     }
+    /// Serializes the chunk as it appears in a PNG file.
     pub fn bytes(&self) -> Vec<u8> {
-        self.length
-            .to_be_bytes()
-            .into_iter()
-            .chain(*self.r#type.as_bytes())
-            .chain(self.data.iter().copied())
-            .chain(self.crc.to_be_bytes())
-            .collect()
+        let mut bytes = Vec::with_capacity(self.data.len() + 12);
+        bytes.extend_from_slice(&self.length.to_be_bytes());
+        bytes.extend_from_slice(self.r#type.as_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&self.crc.to_be_bytes());
+        bytes
     }
 }
 
-fn crc32(r#type: impl AsRef<[u8]>, data: impl AsRef<[u8]>) -> u32 {
-    let table = CRC32_TABLE.get_or_init(|| {
-        std::array::from_fn(|i| {
-            (0..8).fold(i as u32, |c, _| match c & 1 {
-                1 => c >> 1 ^ 0xedb88320,
-                _ => c >> 1,
+/// Incremental CRC-32 for chunks whose data arrives in pieces.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) const fn new() -> Self {
+        Self(u32::MAX)
+    }
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        let table = CRC32_TABLE.get_or_init(|| {
+            std::array::from_fn(|i| {
+                (0..8).fold(i as u32, |c, _| match c & 1 {
+                    1 => c >> 1 ^ 0xedb88320,
+                    _ => c >> 1,
+                })
             })
-        })
-    });
-
-    !r#type
-        .as_ref()
-        .iter()
-        .copied()
-        .chain(data.as_ref().iter().copied())
-        .fold(u32::MAX, |c, octet| {
+        });
+
+        self.0 = bytes.iter().fold(self.0, |c, &octet| {
             c >> 8 ^ table[((c ^ octet as u32) & 0xff) as usize]
-        })
+        });
+    }
+    pub(crate) const fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+pub(crate) fn crc32(r#type: impl AsRef<[u8]>, data: impl AsRef<[u8]>) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(r#type.as_ref());
+    crc.update(data.as_ref());
+    crc.finish()
 }
 
 #[cfg(test)]
@@ -230,6 +276,48 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    fn test_chunk_crc_mismatch_error() {
+        let mut chunk_data = testing_chunk().bytes();
+        let last = chunk_data.len() - 1;
+        chunk_data[last] ^= 1;
+
+        let err = Chunk::try_from(chunk_data.as_ref()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::CrcMismatch {
+                offset: 50,
+                stored: 2882656335,
+                computed: 2882656334,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_truncated_chunk_error() {
+        let chunk_data = testing_chunk().bytes();
+
+        let err = Chunk::try_from(&chunk_data[..20]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Truncated {
+                offset: 0,
+                needed: 54
+            }
+        ));
+
+        let err = Chunk::try_from(&chunk_data[..3]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Truncated {
+                offset: 0,
+                needed: 12
+            }
+        ));
+    }
+
     #[test]
     pub fn test_chunk_trait_impls() {
         let data_length: u32 = 42;
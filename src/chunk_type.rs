@@ -1,6 +1,10 @@
+//! Four-byte PNG chunk type codes.
+
 use crate::Error;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// A chunk type code such as `IHDR` or `ruSt`, made of four ASCII letters whose case encodes
+/// the chunk's properties.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct ChunkType([u8; 4]);
 
 impl TryFrom<[u8; 4]> for ChunkType {
@@ -8,7 +12,7 @@ impl TryFrom<[u8; 4]> for ChunkType {
 
     fn try_from(value: [u8; 4]) -> Result<Self, Self::Error> {
         if !value.iter().all(u8::is_ascii_alphabetic) {
-            Err("Invalid chunk type: contains non-ASCII chars".into())
+            Err(Error::InvalidChunkType(value.into()))
         } else {
             Ok(Self(value))
         }
@@ -19,12 +23,11 @@ impl std::str::FromStr for ChunkType {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 4 {
-            Err("Invalid chunk type length".into())
-        } else {
-            let s: [u8; 4] = s.as_bytes()[..4].try_into()?;
-            Ok(Self::try_from(s)?)
-        }
+        let bytes: [u8; 4] = s
+            .as_bytes()
+            .try_into()
+            .map_err(|_| Error::InvalidChunkType(s.as_bytes().into()))?;
+        Self::try_from(bytes)
     }
 }
 
@@ -35,21 +38,98 @@ impl std::fmt::Display for ChunkType {
 }
 
 impl ChunkType {
+    /// Image header.
+    pub const IHDR: Self = Self(*b"IHDR");
+    /// Palette.
+    pub const PLTE: Self = Self(*b"PLTE");
+    /// Image data.
+    pub const IDAT: Self = Self(*b"IDAT");
+    /// Image trailer.
+    pub const IEND: Self = Self(*b"IEND");
+    /// Transparency.
+    pub const TRNS: Self = Self(*b"tRNS");
+    /// Background color.
+    pub const BKGD: Self = Self(*b"bKGD");
+    /// Image histogram.
+    pub const HIST: Self = Self(*b"hIST");
+    /// Suggested palette.
+    pub const SPLT: Self = Self(*b"sPLT");
+    /// Embedded ICC profile.
+    pub const ICCP: Self = Self(*b"iCCP");
+    /// Standard RGB color space.
+    pub const SRGB: Self = Self(*b"sRGB");
+    /// Image gamma.
+    pub const GAMA: Self = Self(*b"gAMA");
+    /// Primary chromaticities and white point.
+    pub const CHRM: Self = Self(*b"cHRM");
+    /// Coding-independent code points.
+    pub const CICP: Self = Self(*b"cICP");
+    /// Mastering display color volume.
+    pub const MDCV: Self = Self(*b"mDCV");
+    /// Content light level information.
+    pub const CLLI: Self = Self(*b"cLLI");
+    /// Significant bits.
+    pub const SBIT: Self = Self(*b"sBIT");
+    /// Physical pixel dimensions.
+    pub const PHYS: Self = Self(*b"pHYs");
+    /// Image offset.
+    pub const OFFS: Self = Self(*b"oFFs");
+    /// Pixel calibration.
+    pub const PCAL: Self = Self(*b"pCAL");
+    /// Physical scale.
+    pub const SCAL: Self = Self(*b"sCAL");
+    /// Last-modification time.
+    pub const TIME: Self = Self(*b"tIME");
+    /// Animation control.
+    pub const ACTL: Self = Self(*b"acTL");
+    /// Frame control.
+    pub const FCTL: Self = Self(*b"fcTL");
+    /// Frame data.
+    pub const FDAT: Self = Self(*b"fdAT");
+    /// EXIF metadata.
+    pub const EXIF: Self = Self(*b"eXIf");
+    /// Latin-1 text.
+    pub const TEXT: Self = Self(*b"tEXt");
+    /// Compressed Latin-1 text.
+    pub const ZTXT: Self = Self(*b"zTXt");
+    /// International UTF-8 text.
+    pub const ITXT: Self = Self(*b"iTXt");
+
+    /// Creates a chunk type in a const context, such as a
+    /// [`ChunkCodec::CHUNK_TYPE`](crate::ChunkCodec::CHUNK_TYPE). Panics, at compile time when
+    /// used in a constant, if a byte is not an ASCII letter.
+    pub const fn new(bytes: [u8; 4]) -> Self {
+        let mut i = 0;
+        while i < 4 {
+            assert!(
+                bytes[i].is_ascii_alphabetic(),
+                "chunk type bytes must be ASCII letters"
+            );
+            i += 1;
+        }
+        Self(bytes)
+    }
+    /// The raw type code bytes.
     pub const fn as_bytes(&self) -> &[u8; 4] {
         &self.0
     }
+    /// Whether the type code is valid for the current PNG specification.
     pub const fn is_valid(&self) -> bool {
         self.is_reserved_bit_valid()
     }
+    /// Whether decoders must understand the chunk to display the image (uppercase first letter).
     pub const fn is_critical(&self) -> bool {
         self.0[0].is_ascii_uppercase()
     }
+    /// Whether the chunk type is registered by the PNG specification (uppercase second letter).
     pub const fn is_public(&self) -> bool {
         self.0[1].is_ascii_uppercase()
     }
+    /// Whether the reserved bit is unset (uppercase third letter).
     pub const fn is_reserved_bit_valid(&self) -> bool {
         self.0[2].is_ascii_uppercase()
     }
+    /// Whether editors may copy the chunk into a modified image (lowercase fourth letter).
     pub const fn is_safe_to_copy(&self) -> bool {
         self.0[3].is_ascii_lowercase()
     }
@@ -139,6 +219,15 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    pub fn test_invalid_chunk_type_error() {
+        let err = ChunkType::from_str("Ru1t").unwrap_err();
+        assert!(matches!(err, Error::InvalidChunkType(ref b) if &b[..] == b"Ru1t"));
+
+        let err = ChunkType::from_str("RuStt").unwrap_err();
+        assert!(matches!(err, Error::InvalidChunkType(ref b) if &b[..] == b"RuStt"));
+    }
+
     #[test]
     pub fn test_chunk_type_string() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -1,71 +1,1021 @@
-use std::{env, fs, str::FromStr};
+use std::{
+    env, fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+    process::ExitCode,
+    str::FromStr,
+};
 
-use crate::{chunk::Chunk, chunk_type::ChunkType, png::Png, Error};
+use pngme::{
+    ed25519::{SigningKey, VerifyingKey},
+    x25519::{PublicKey, SecretKey},
+    Chunk, ChunkCodec, ChunkSignature, ChunkType, CodecRegistry, ColorType, Envelope, Exif,
+    Placement, Png, PngEncoder, PngReader, PngWriter, TextChunk, Timestamp,
+};
+
+/// Failures reported by the command line, each mapped to its own exit code.
+#[derive(Debug)]
+pub enum Error {
+    Usage(String),
+    Png(pngme::Error),
+    ValidationFailed(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Usage(msg) => f.write_str(msg),
+            Self::Png(e) => e.fmt(f),
+            Self::ValidationFailed(n) => write!(f, "{n} file(s) failed validation"),
+        }
+    }
+}
+
+impl From<pngme::Error> for Error {
+    fn from(e: pngme::Error) -> Self {
+        Self::Png(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Png(e.into())
+    }
+}
+
+impl Error {
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Self::Usage(_) => 2,
+            Self::Png(pngme::Error::Io(_)) => 3,
+            Self::Png(pngme::Error::InvalidSignature) => 4,
+            Self::Png(pngme::Error::Truncated { .. } | pngme::Error::InvalidLength { .. }) => 5,
+            Self::Png(pngme::Error::CrcMismatch { .. }) => 6,
+            Self::Png(pngme::Error::InvalidChunkType(_)) => 7,
+            Self::Png(pngme::Error::ChunkNotFound(_) | pngme::Error::TextNotFound(_)) => 8,
+            Self::Png(pngme::Error::Utf8(_)) => 9,
+            Self::Png(pngme::Error::MalformedChunk { .. } | pngme::Error::InvalidPayload(_)) => 10,
+            Self::Png(pngme::Error::Invalid(_)) => 11,
+            Self::Png(pngme::Error::Decompress(_)) => 12,
+            Self::Png(pngme::Error::DecryptionFailed) => 13,
+            Self::Png(pngme::Error::VerificationFailed) => 14,
+            Self::Png(
+                pngme::Error::InvalidPlacement(_)
+                | pngme::Error::InvalidText(_)
+                | pngme::Error::InvalidKey(_),
+            ) => 2,
+            Self::Png(_) => 1,
+            Self::ValidationFailed(_) => 11,
+        })
+    }
+}
 
 pub fn run() -> Result<(), Error> {
-    let args = env::args().collect::<Box<_>>();
+    let mut args = env::args().collect::<Vec<String>>();
     match args.get(1).map(String::as_str) {
         Some("encode") => {
+            let placement = take_option(&mut args, "--placement")?
+                .map(|s| Placement::from_str(&s))
+                .transpose()?
+                .unwrap_or_default();
+            refuse_password_option(&args)?;
+            let seal = match (
+                take_option(&mut args, "--password-file")?,
+                take_options(&mut args, "--recipient")?,
+            ) {
+                (None, recipients) if recipients.is_empty() => Seal::Plain,
+                (Some(path), recipients) if recipients.is_empty() => {
+                    Seal::Password(read_password(&path)?)
+                }
+                (None, recipients) => Seal::Recipients(
+                    recipients
+                        .iter()
+                        .map(|r| PublicKey::from_str(r))
+                        .collect::<Result<_, _>>()?,
+                ),
+                (Some(_), _) => {
+                    return Err(usage(
+                        "Options '--password-file' and '--recipient' cannot be combined",
+                    ))
+                }
+            };
             if args.len() != 5 {
-                Err("Invalid number of arguments: subcommand 'encode'".into())
+                Err(usage("Invalid number of arguments: subcommand 'encode'"))
             } else {
-                encode(&args)
+                encode(&args, placement, seal)
             }
         }
         Some("decode") => {
+            refuse_password_option(&args)?;
+            let open = match (
+                take_option(&mut args, "--password-file")?,
+                take_option(&mut args, "--identity")?,
+            ) {
+                (None, None) => Open::Plain,
+                (Some(path), None) => Open::Password(read_password(&path)?),
+                (None, Some(path)) => Open::Identity(read_key_file(&path)?.parse()?),
+                (Some(_), Some(_)) => {
+                    return Err(usage(
+                        "Options '--password-file' and '--identity' cannot be combined",
+                    ))
+                }
+            };
+            if args.len() != 4 {
+                Err(usage("Invalid number of arguments: subcommand 'decode'"))
+            } else {
+                decode(&args, open)
+            }
+        }
+        Some("keygen") => {
+            let signing = take_flag(&mut args, "--sign");
+            match args.len() {
+                2 => keygen(None, signing),
+                3 => keygen(Some(&args[2]), signing),
+                _ => Err(usage("Invalid number of arguments: subcommand 'keygen'")),
+            }
+        }
+        Some("sign") => {
+            let extra = take_options(&mut args, "--chunk")?;
             if args.len() != 4 {
-                Err("Invalid number of arguments: subcommand 'decode'".into())
+                Err(usage("Invalid number of arguments: subcommand 'sign'"))
+            } else {
+                sign(&args[2], &args[3], &extra)
+            }
+        }
+        Some("verify") => {
+            let signer = take_option(&mut args, "--signer")?
+                .map(|s| VerifyingKey::from_str(&s))
+                .transpose()?;
+            if args.len() != 3 {
+                Err(usage("Invalid number of arguments: subcommand 'verify'"))
             } else {
-                decode(&args)
+                verify(&args[2], signer)
             }
         }
         Some("remove") => {
             if args.len() != 4 {
-                Err("Invalid number of arguments: subcommand 'remove'".into())
+                Err(usage("Invalid number of arguments: subcommand 'remove'"))
             } else {
                 remove(&args)
             }
         }
         Some("print") => {
             if args.len() != 3 {
-                Err("Invalid number of arguments: subcommand 'print'".into())
+                Err(usage("Invalid number of arguments: subcommand 'print'"))
             } else {
                 print(&args)
             }
         }
-        Some(s) => Err(format!("Invalid subcommand: {s}").into()),
-        None => Err("Missing subcommand".into()),
+        Some("validate") => {
+            let json = take_flag(&mut args, "--json");
+            if args.len() < 3 {
+                Err(usage("Invalid number of arguments: subcommand 'validate'"))
+            } else {
+                validate(&args[2..], json)
+            }
+        }
+        Some("exif") => {
+            let scrub_gps = take_flag(&mut args, "--scrub-gps");
+            if args.len() != 3 {
+                Err(usage("Invalid number of arguments: subcommand 'exif'"))
+            } else {
+                exif(&args[2], scrub_gps)
+            }
+        }
+        Some("text") => {
+            let compress = take_flag(&mut args, "--compress");
+            let language = take_option(&mut args, "--language")?;
+            match (args.get(2).map(String::as_str), args.len()) {
+                (Some("list"), 4) => text_list(&args[3]),
+                (Some("get"), 5) => text_get(&args[3], &args[4]),
+                (Some("set"), 6) => text_set(&args[3], &args[4], &args[5], language, compress),
+                (Some("remove"), 5) => text_remove(&args[3], &args[4]),
+                (Some(s @ ("list" | "get" | "set" | "remove")), _) => Err(usage(format!(
+                    "Invalid number of arguments: subcommand 'text {s}'"
+                ))),
+                (Some(s), _) => Err(usage(format!("Invalid text subcommand: {s}"))),
+                (None, _) => Err(usage("Missing text subcommand")),
+            }
+        }
+        Some("frames") => match (args.get(2).map(String::as_str), args.len()) {
+            (Some("extract"), 5) => frames_extract(&args[3], &args[4]),
+            (Some("extract"), _) => Err(usage(
+                "Invalid number of arguments: subcommand 'frames extract'",
+            )),
+            (Some(s), _) => Err(usage(format!("Invalid frames subcommand: {s}"))),
+            (None, _) => Err(usage("Missing frames subcommand")),
+        },
+        Some(s) => Err(usage(format!("Invalid subcommand: {s}"))),
+        None => Err(usage("Missing subcommand")),
+    }
+}
+
+fn usage(msg: impl Into<String>) -> Error {
+    Error::Usage(msg.into())
+}
+
+/// Removes `name` and the value following it from `args`, returning the value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, Error> {
+    let Some(i) = args.iter().position(|a| a == name) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(usage(format!("Missing value for option '{name}'")));
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+/// Fails if `args` has `--password`, which would show the password in the process list, pointing
+/// to `--password-file` instead.
+fn refuse_password_option(args: &[String]) -> Result<(), Error> {
+    if args.iter().any(|a| a == "--password") {
+        return Err(usage(
+            "Option '--password' is not supported because the process list would show the \
+             password: use '--password-file <path>', or '--password-file -' to read it from stdin",
+        ));
     }
+    Ok(())
+}
+
+/// Removes every occurrence of option `name` and its value from `args`.
+fn take_options(args: &mut Vec<String>, name: &str) -> Result<Vec<String>, Error> {
+    let mut values = vec![];
+    while let Some(value) = take_option(args, name)? {
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Removes `name` from `args`, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let len = args.len();
+    args.retain(|a| a != name);
+    args.len() != len
 }
 
 fn png_from_path(path: &str) -> Result<Png, Error> {
-    let bytes = fs::read(path)?;
-    Png::try_from(bytes.as_slice())
+    Ok(Png::read_from(BufReader::new(File::open(path)?))?)
 }
 
-fn encode(args: &[String]) -> Result<(), Error> {
-    let mut png = png_from_path(&args[2])?;
-    png.append_chunk(Chunk::new(ChunkType::from_str(&args[3])?, &args[4]));
-    fs::write(&args[2], png.bytes()).map_err(Error::from)
+/// Opens a streaming reader, skipping the data of `IDAT` chunks if `skip_idat` is set.
+fn reader_from_path(path: &str, skip_idat: bool) -> Result<PngReader<BufReader<File>>, Error> {
+    let reader = PngReader::new(BufReader::new(File::open(path)?))?;
+    if skip_idat {
+        Ok(reader.skip_data(ChunkType::IDAT))
+    } else {
+        Ok(reader)
+    }
 }
 
-fn decode(args: &[String]) -> Result<(), Error> {
-    let png = png_from_path(&args[2])?;
-    match png.chunk_by_type(&args[3]) {
-        Some(chunk) => Ok(println!("{}", chunk.data_as_string()?)),
-        None => Err("Invalid chunk type".into()),
+/// Streams the chunks of the PNG at `path` through `edit` into a temporary file, which replaces
+/// the original once every chunk has been written. `edit` sees each chunk in order, then `None`
+/// at the end of the file, and returns the chunks to write in its place.
+///
+/// If `stamp_time` is set, the `tIME` chunk is set to the current time, replacing the first
+/// existing one or going before `IEND`. Edits that target `tIME` themselves pass `false` so
+/// their result is kept.
+fn rewrite(
+    path: &str,
+    stamp_time: bool,
+    mut edit: impl FnMut(Option<Chunk>) -> Result<Vec<Chunk>, Error>,
+) -> Result<(), Error> {
+    let mut reader = PngReader::new(BufReader::new(File::open(path)?))?;
+    let mut stamp = stamp_time
+        .then(Timestamp::now)
+        .transpose()?
+        .map(|time| time.to_chunk());
+    let (tmp_path, tmp_file) = create_temp(path)?;
+    let write = || -> Result<(), Error> {
+        let mut writer = PngWriter::new(BufWriter::new(tmp_file))?;
+        loop {
+            let chunk = reader.next_chunk()?;
+            let done = chunk.is_none();
+            for chunk in edit(chunk)? {
+                match *chunk.r#type() {
+                    ChunkType::TIME if stamp_time => {
+                        if let Some(stamp) = stamp.take() {
+                            writer.write_chunk(&stamp)?;
+                        }
+                        continue;
+                    }
+                    ChunkType::IEND => {
+                        if let Some(stamp) = stamp.take() {
+                            writer.write_chunk(&stamp)?;
+                        }
+                    }
+                    _ => {}
+                }
+                writer.write_chunk(&chunk)?;
+            }
+            if done {
+                break;
+            }
+        }
+        writer.finish()?;
+        Ok(())
+    };
+    match write() {
+        Ok(()) => Ok(fs::rename(&tmp_path, path)?),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+/// Creates a file next to `path` to hold its replacement, picking a name no other file or run
+/// is using, with the permissions of `path` rather than the umask default.
+fn create_temp(path: &str) -> Result<(String, File), Error> {
+    let mut attempt = 0;
+    loop {
+        let tmp_path = format!("{path}.{}-{attempt}.tmp", std::process::id());
+        match File::options().write(true).create_new(true).open(&tmp_path) {
+            Ok(file) => {
+                let permissions = fs::metadata(path).map(|m| m.permissions());
+                if let Err(e) = permissions.and_then(|p| file.set_permissions(p)) {
+                    let _ = fs::remove_file(&tmp_path);
+                    return Err(e.into());
+                }
+                return Ok((tmp_path, file));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// APNG chunks whose sequence numbers would be broken by adding or removing one of them.
+const ANIMATION_CHUNKS: [ChunkType; 3] = [ChunkType::ACTL, ChunkType::FCTL, ChunkType::FDAT];
+
+fn check_not_animation(chunk_type: &str) -> Result<(), Error> {
+    let chunk_type = ChunkType::from_str(chunk_type)?;
+    if ANIMATION_CHUNKS.contains(&chunk_type) {
+        return Err(usage(format!(
+            "{chunk_type} chunks control APNG frame sequencing and cannot be edited"
+        )));
     }
+    Ok(())
+}
+
+/// How `encode` protects the message.
+enum Seal {
+    Plain,
+    Password(String),
+    Recipients(Vec<PublicKey>),
+}
+
+/// How `decode` reads the message.
+enum Open {
+    Plain,
+    Password(String),
+    Identity(SecretKey),
+}
+
+fn encode(args: &[String], placement: Placement, seal: Seal) -> Result<(), Error> {
+    check_not_animation(&args[3])?;
+    let chunk_type = ChunkType::from_str(&args[3])?;
+    let message = args[4].as_bytes();
+    let mut message = Some(match seal {
+        Seal::Plain => Chunk::new(chunk_type, message),
+        Seal::Password(password) => {
+            Envelope::seal_with_password(chunk_type, message, &password)?.to_chunk(chunk_type)
+        }
+        Seal::Recipients(recipients) => {
+            Envelope::seal_to_recipients(chunk_type, message, &recipients)?.to_chunk(chunk_type)
+        }
+    });
+    let mut previous = None;
+    rewrite(
+        &args[2],
+        chunk_type != ChunkType::TIME,
+        |chunk| match chunk {
+            Some(chunk) => {
+                let mut chunks = vec![];
+                if placement.is_between(previous.as_ref(), chunk.r#type()) {
+                    chunks.extend(message.take());
+                }
+                previous = Some(*chunk.r#type());
+                chunks.push(chunk);
+                Ok(chunks)
+            }
+            None => match (message.take(), placement) {
+                (None, _) => Ok(vec![]),
+                (Some(chunk), Placement::BeforeIend) => Ok(vec![chunk]),
+                (Some(_), Placement::AfterIhdr) => {
+                    Err(pngme::Error::ChunkNotFound("IHDR".into()).into())
+                }
+                (Some(_), Placement::BeforeIdat) => {
+                    Err(pngme::Error::ChunkNotFound("IDAT".into()).into())
+                }
+            },
+        },
+    )
+}
+
+fn decode(args: &[String], open: Open) -> Result<(), Error> {
+    let mut reader = reader_from_path(&args[2], args[3] != "IDAT")?;
+    while let Some(chunk) = reader.next_chunk()? {
+        if chunk.r#type().to_string() == args[3] {
+            let chunk_type = *chunk.r#type();
+            let message =
+                match open {
+                    Open::Plain if Envelope::is_envelope(chunk.data()) => {
+                        return Err(usage(format!(
+                        "Chunk {chunk_type} is encrypted: pass --password-file or --identity to \
+                         decode it"
+                    )));
+                    }
+                    Open::Plain => chunk.data().to_vec(),
+                    Open::Password(password) => Envelope::try_from(chunk.data())?
+                        .open_with_password(chunk_type, &password)?,
+                    Open::Identity(identity) => Envelope::try_from(chunk.data())?
+                        .open_with_identity(chunk_type, &identity)?,
+                };
+            println!(
+                "{}",
+                std::str::from_utf8(&message).map_err(pngme::Error::from)?
+            );
+            return Ok(());
+        }
+    }
+    Err(pngme::Error::ChunkNotFound(args[3].clone()).into())
+}
+
+/// Reads a password from the first line of the file at `path`, or of stdin if `path` is `-`, so
+/// it never appears in the process's arguments.
+fn read_password(path: &str) -> Result<String, Error> {
+    let contents = if path == "-" {
+        io::read_to_string(io::stdin())?
+    } else {
+        fs::read_to_string(path)?
+    };
+    match contents.lines().next() {
+        Some(password) if !password.is_empty() => Ok(password.to_owned()),
+        _ if path == "-" => Err(usage("No password on stdin")),
+        _ => Err(usage(format!("No password in {path}"))),
+    }
+}
+
+/// Reads the secret key from a key file written by `keygen`, skipping blank and `#` lines.
+fn read_key_file(path: &str) -> Result<String, Error> {
+    let contents = fs::read_to_string(path)?;
+    let key = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or_else(|| pngme::Error::InvalidKey(format!("no secret key in {path}")))?;
+    Ok(key.to_owned())
+}
+
+/// Generates an X25519 key pair for encryption, or an Ed25519 one for signing if `signing` is
+/// set. The key file goes to `path`, which must not exist yet, or to stdout; either way its
+/// comments include the public key.
+fn keygen(path: Option<&str>, signing: bool) -> Result<(), Error> {
+    let (secret, public) = if signing {
+        let secret = SigningKey::generate()?;
+        (secret.to_hex(), secret.verifying_key().to_string())
+    } else {
+        let secret = SecretKey::generate()?;
+        (secret.to_hex(), secret.public_key().to_string())
+    };
+    let contents = format!(
+        "# created: {}\n# public key: {public}\n{secret}\n",
+        Timestamp::now()?
+    );
+    let Some(path) = path else {
+        print!("{contents}");
+        return Ok(());
+    };
+
+    let mut options = File::options();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents.as_bytes())?;
+    println!("Public key: {public}");
+    Ok(())
 }
 
 fn remove(args: &[String]) -> Result<(), Error> {
-    let mut png = png_from_path(&args[2])?;
-    png.remove_chunk(&args[3])?;
-    fs::write(&args[2], png.bytes()).map_err(Error::from)?;
+    check_not_animation(&args[3])?;
+    let mut removed = false;
+    rewrite(&args[2], args[3] != "tIME", |chunk| match chunk {
+        Some(chunk) if !removed && chunk.r#type().to_string() == args[3] => {
+            removed = true;
+            Ok(vec![])
+        }
+        Some(chunk) => Ok(vec![chunk]),
+        None if removed => Ok(vec![]),
+        None => Err(pngme::Error::ChunkNotFound(args[3].clone()).into()),
+    })
+}
+
+/// Signs the chunks that affect the image and every chunk of the `extra` types, replacing an
+/// earlier signature by the same key.
+fn sign(path: &str, key_path: &str, extra: &[String]) -> Result<(), Error> {
+    let key = SigningKey::from_str(&read_key_file(key_path)?)?;
+    let extra = extra
+        .iter()
+        .map(|s| ChunkType::from_str(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    if extra.contains(&ChunkType::TIME) {
+        return Err(usage(
+            "Chunk tIME is updated on every edit and cannot be signed",
+        ));
+    }
+    let png = png_from_path(path)?;
+    let signature = ChunkSignature::sign(&key, png.chunks(), &extra)?;
+    let mut signature_chunk = Some(pngme::codec::to_chunk(&signature));
+    rewrite(path, true, |chunk| match chunk {
+        Some(chunk)
+            if *chunk.r#type() == ChunkSignature::CHUNK_TYPE
+                && ChunkSignature::decode(chunk.data())
+                    .is_ok_and(|s| s.public_key() == signature.public_key()) =>
+        {
+            Ok(vec![])
+        }
+        Some(chunk) if *chunk.r#type() == ChunkType::IEND => {
+            Ok(signature_chunk.take().into_iter().chain([chunk]).collect())
+        }
+        Some(chunk) => Ok(vec![chunk]),
+        None => Ok(signature_chunk.take().into_iter().collect()),
+    })?;
+    println!("{signature}");
     Ok(())
 }
 
+/// Checks every signature, listing the chunks each one covers and those none of them cover.
+/// With `signer`, a valid signature by that key is also required.
+fn verify(path: &str, signer: Option<VerifyingKey>) -> Result<(), Error> {
+    let png = png_from_path(path)?;
+    let signatures = png.signatures()?;
+    if signatures.is_empty() {
+        return Err(pngme::Error::ChunkNotFound(ChunkSignature::CHUNK_TYPE.to_string()).into());
+    }
+    let mut all_good = true;
+    let mut signed_by_signer = signer.is_none();
+    let mut covered = vec![ChunkSignature::CHUNK_TYPE];
+    for signature in &signatures {
+        let public_key = signature.public_key();
+        match signature.verify(png.chunks()) {
+            Ok(chunks) => {
+                println!("Good signature by {public_key}");
+                println!("  covers: {}", chunk_list(chunks.iter().copied()));
+                covered.extend_from_slice(signature.covered());
+                signed_by_signer |= signer.as_ref() == Some(public_key);
+            }
+            Err(e) => {
+                println!("Bad signature by {public_key}: {e}");
+                all_good = false;
+            }
+        }
+    }
+    let uncovered = chunk_list(
+        png.chunks()
+            .iter()
+            .filter(|chunk| !covered.contains(chunk.r#type())),
+    );
+    if !uncovered.is_empty() {
+        println!("Not covered: {uncovered}");
+    }
+    if let Some(signer) = signer.filter(|_| !signed_by_signer) {
+        println!("No good signature by {signer}");
+    }
+    if all_good && signed_by_signer {
+        Ok(())
+    } else {
+        Err(pngme::Error::VerificationFailed.into())
+    }
+}
+
+/// Lists chunk types in order, counting runs of the same type, as in `IHDR, IDAT (3), IEND`.
+fn chunk_list<'a>(chunks: impl IntoIterator<Item = &'a Chunk>) -> String {
+    let mut runs: Vec<(ChunkType, usize)> = vec![];
+    for chunk in chunks {
+        match runs.last_mut() {
+            Some((chunk_type, count)) if chunk_type == chunk.r#type() => *count += 1,
+            _ => runs.push((*chunk.r#type(), 1)),
+        }
+    }
+    runs.iter()
+        .map(|(chunk_type, count)| match count {
+            1 => chunk_type.to_string(),
+            _ => format!("{chunk_type} ({count})"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prints the header, every chunk with a registered codec in file order, the chunks whose layout
+/// depends on the header, and finally the raw chunk list.
 fn print(args: &[String]) -> Result<(), Error> {
     let png = png_from_path(&args[2])?;
+    print_entry(ChunkType::IHDR, png.ihdr().map(Some));
+    let mut registry = CodecRegistry::with_builtins();
+    registry.register::<ChunkSignature>();
+    for chunk in png.chunks() {
+        match *chunk.r#type() {
+            // Frame controls are listed under the animation.
+            ChunkType::IHDR | ChunkType::FCTL => {}
+            ChunkType::ACTL => match png.animation() {
+                Ok(Some(animation)) => {
+                    println!("acTL: {}", animation.control());
+                    for (i, frame) in animation.frames().iter().enumerate() {
+                        println!("  frame {i}: {}", frame.control());
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => println!("acTL: error: {e}"),
+            },
+            chunk_type => print_entry(chunk_type, registry.render(chunk).transpose()),
+        }
+    }
+    print_entry(ChunkType::SBIT, png.significant_bits());
+    print_entry(ChunkType::TRNS, png.transparency());
+    print_entry(ChunkType::BKGD, png.background());
+    match png.exif() {
+        Ok(Some(exif)) => {
+            println!("eXIf:");
+            for entry in exif.entries() {
+                println!("  {entry}");
+            }
+        }
+        Ok(None) => {}
+        Err(e) => println!("eXIf: error: {e}"),
+    }
     println!("{png}");
     Ok(())
 }
+
+/// Prints a parsed chunk, or why it could not be parsed, so one bad chunk does not hide the rest.
+fn print_entry(chunk_type: ChunkType, parsed: Result<Option<impl fmt::Display>, pngme::Error>) {
+    match parsed {
+        Ok(Some(value)) => println!("{chunk_type}: {value}"),
+        Ok(None) => {}
+        Err(e) => println!("{chunk_type}: error: {e}"),
+    }
+}
+
+/// Prints the EXIF tags, first removing the GPS tags from the file if `scrub_gps` is set.
+fn exif(path: &str, scrub_gps: bool) -> Result<(), Error> {
+    let mut exif = None;
+    let mut has_gps = false;
+    let mut reader = reader_from_path(path, true)?;
+    while let Some(chunk) = reader.next_chunk()? {
+        if *chunk.r#type() == ChunkType::EXIF {
+            let parsed = Exif::try_from(&chunk)?;
+            if !scrub_gps {
+                exif = Some(parsed);
+                break;
+            }
+            has_gps |= parsed.without_gps()? != parsed;
+            exif.get_or_insert(parsed);
+        }
+    }
+    let mut exif = exif.ok_or_else(|| pngme::Error::ChunkNotFound(ChunkType::EXIF.to_string()))?;
+
+    // Rewriting stamps a new tIME, so a file with nothing to scrub is left untouched.
+    if has_gps {
+        rewrite(path, true, |chunk| match chunk {
+            Some(chunk) if *chunk.r#type() == ChunkType::EXIF => {
+                Ok(vec![Exif::try_from(&chunk)?.without_gps()?.to_chunk()])
+            }
+            Some(chunk) => Ok(vec![chunk]),
+            None => Ok(vec![]),
+        })?;
+        exif = exif.without_gps()?;
+    }
+    for entry in exif.entries() {
+        println!("{entry}");
+    }
+    Ok(())
+}
+
+/// The entry stored in `chunk`, or `None` if it is not a text chunk.
+fn text_entry(chunk: &Chunk) -> Result<Option<TextChunk>, Error> {
+    if TextChunk::CHUNK_TYPES.contains(chunk.r#type()) {
+        Ok(Some(TextChunk::try_from(chunk)?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn text_list(path: &str) -> Result<(), Error> {
+    let mut reader = reader_from_path(path, true)?;
+    while let Some(chunk) = reader.next_chunk()? {
+        // A malformed entry is reported in its place so it does not hide the others.
+        match text_entry(&chunk) {
+            Ok(Some(entry)) => println!("{} {entry}", entry.chunk_type()),
+            Ok(None) => {}
+            Err(e) => println!("{} error: {e}", chunk.r#type()),
+        }
+    }
+    Ok(())
+}
+
+fn text_get(path: &str, keyword: &str) -> Result<(), Error> {
+    let mut reader = reader_from_path(path, true)?;
+    while let Some(chunk) = reader.next_chunk()? {
+        if let Some(entry) = text_entry(&chunk)?.filter(|e| e.keyword() == keyword) {
+            println!("{}", entry.text());
+            return Ok(());
+        }
+    }
+    Err(pngme::Error::TextNotFound(keyword.to_owned()).into())
+}
+
+/// Replaces the first entry with `keyword` in place and drops any others, or adds the entry
+/// before `IEND` if there is none.
+fn text_set(
+    path: &str,
+    keyword: &str,
+    text: &str,
+    language: Option<String>,
+    compress: bool,
+) -> Result<(), Error> {
+    let entry = match language {
+        Some(language) => TextChunk::international(keyword, text, language, "")?,
+        None => TextChunk::new(keyword, text)?,
+    };
+    let entry = if compress { entry.compressed() } else { entry };
+    let mut pending = Some(entry.to_chunk());
+    rewrite(path, true, |chunk| match chunk {
+        Some(chunk) if text_entry(&chunk)?.is_some_and(|e| e.keyword() == keyword) => {
+            Ok(pending.take().into_iter().collect())
+        }
+        Some(chunk) if *chunk.r#type() == ChunkType::IEND => {
+            Ok(pending.take().into_iter().chain([chunk]).collect())
+        }
+        Some(chunk) => Ok(vec![chunk]),
+        None => Ok(pending.take().into_iter().collect()),
+    })
+}
+
+fn text_remove(path: &str, keyword: &str) -> Result<(), Error> {
+    let mut removed = false;
+    rewrite(path, true, |chunk| match chunk {
+        Some(chunk) if text_entry(&chunk)?.is_some_and(|e| e.keyword() == keyword) => {
+            removed = true;
+            Ok(vec![])
+        }
+        Some(chunk) => Ok(vec![chunk]),
+        None if removed => Ok(vec![]),
+        None => Err(pngme::Error::TextNotFound(keyword.to_owned()).into()),
+    })
+}
+
+/// Writes each composited frame of the PNG at `path` to `dir` as an RGBA PNG named by its index.
+fn frames_extract(path: &str, dir: &str) -> Result<(), Error> {
+    let frames = png_from_path(path)?.frames()?;
+    fs::create_dir_all(dir)?;
+    for (i, frame) in frames.iter().enumerate() {
+        let png = PngEncoder::new(
+            frame.width(),
+            frame.height(),
+            ColorType::Rgba,
+            frame.bit_depth(),
+        )?
+        .encode(frame.data())?;
+        let frame_path = Path::new(dir).join(format!("frame_{i:03}.png"));
+        png.write_to(BufWriter::new(File::create(&frame_path)?))?;
+        println!("{}", frame_path.display());
+    }
+    Ok(())
+}
+
+fn validate(paths: &[String], json: bool) -> Result<(), Error> {
+    let reports = paths
+        .iter()
+        .map(|path| {
+            let issues = match png_from_path(path) {
+                Ok(png) => png
+                    .validate()
+                    .iter()
+                    .map(|v| (v.index(), v.kind().to_string()))
+                    .collect(),
+                Err(e) => vec![(None, e.to_string())],
+            };
+            (path, issues)
+        })
+        .collect::<Vec<_>>();
+
+    if json {
+        let files = reports
+            .iter()
+            .map(|(path, issues)| {
+                let errors = issues
+                    .iter()
+                    .map(|(index, message)| {
+                        let index = index.map_or("null".to_owned(), |i| i.to_string());
+                        format!(r#"{{"chunk":{index},"message":{}}}"#, json_string(message))
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    r#"{{"file":{},"valid":{},"errors":[{}]}}"#,
+                    json_string(path),
+                    issues.is_empty(),
+                    errors.join(",")
+                )
+            })
+            .collect::<Vec<_>>();
+        println!("[{}]", files.join(","));
+    } else {
+        for (path, issues) in &reports {
+            if issues.is_empty() {
+                println!("{path}: ok");
+                continue;
+            }
+            println!("{path}: {} error(s)", issues.len());
+            for (index, message) in issues {
+                match index {
+                    Some(i) => println!("  chunk {i}: {message}"),
+                    None => println!("  {message}"),
+                }
+            }
+        }
+    }
+
+    match reports
+        .iter()
+        .filter(|(_, issues)| !issues.is_empty())
+        .count()
+    {
+        0 => Ok(()),
+        n => Err(Error::ValidationFailed(n)),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a 1x1 grayscale PNG to a fresh file in the temporary directory.
+    fn temp_png(name: &str) -> String {
+        let path = env::temp_dir().join(format!("pngme-cli-{}-{name}.png", std::process::id()));
+        let png = PngEncoder::new(1, 1, ColorType::Grayscale, 8)
+            .unwrap()
+            .encode(&[0])
+            .unwrap();
+        fs::write(&path, png.bytes()).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        ["pngme"]
+            .iter()
+            .chain(args)
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn chunks_of_type(path: &str, chunk_type: ChunkType) -> Vec<Chunk> {
+        let png = png_from_path(path).unwrap();
+        let chunks = png.chunks().iter().filter(|c| *c.r#type() == chunk_type);
+        chunks.cloned().collect()
+    }
+
+    #[test]
+    fn test_remove_time_is_not_restamped() {
+        let path = temp_png("remove-time");
+        text_set(&path, "Comment", "hi", None, false).unwrap();
+        assert_eq!(chunks_of_type(&path, ChunkType::TIME).len(), 1);
+        remove(&args(&["remove", &path, "tIME"])).unwrap();
+        assert!(chunks_of_type(&path, ChunkType::TIME).is_empty());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_time_is_kept() {
+        let path = temp_png("encode-time");
+        encode(
+            &args(&["encode", &path, "tIME", "explicit"]),
+            Placement::default(),
+            Seal::Plain,
+        )
+        .unwrap();
+        let times = chunks_of_type(&path, ChunkType::TIME);
+        assert_eq!(times.len(), 1);
+        assert_eq!(times[0].data(), b"explicit");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_decode_idat() {
+        let path = env::temp_dir().join(format!("pngme-cli-{}-idat.png", std::process::id()));
+        let png = Png::from_chunks([
+            Chunk::new(ChunkType::IDAT, "data"),
+            Chunk::new(ChunkType::IEND, []),
+        ]);
+        fs::write(&path, png.bytes()).unwrap();
+        let path = path.to_str().unwrap();
+        decode(&args(&["decode", path, "IDAT"]), Open::Plain).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_text_list_continues_past_bad_entries() {
+        let path = env::temp_dir().join(format!("pngme-cli-{}-text.png", std::process::id()));
+        let png = Png::from_chunks([
+            Chunk::new(ChunkType::TEXT, "\0no keyword"),
+            Chunk::new(ChunkType::TEXT, "Comment\0hi"),
+            Chunk::new(ChunkType::IEND, []),
+        ]);
+        fs::write(&path, png.bytes()).unwrap();
+        let path = path.to_str().unwrap();
+        text_list(path).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_decode_plaintext_with_magic_prefix() {
+        let path = temp_png("magic");
+        let message = "PMEmorandum: meet at noon";
+        encode(
+            &args(&["encode", &path, "ruSt", message]),
+            Placement::default(),
+            Seal::Plain,
+        )
+        .unwrap();
+        decode(&args(&["decode", &path, "ruSt"]), Open::Plain).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_password_option_refused() {
+        let err = refuse_password_option(&args(&["decode", "a.png", "ruSt", "--password", "x"]));
+        assert!(matches!(err, Err(Error::Usage(msg)) if msg.contains("--password-file")));
+        assert!(refuse_password_option(&args(&["decode", "--password-file", "-"])).is_ok());
+    }
+
+    #[test]
+    fn test_scrub_gps_without_exif_leaves_file() {
+        let path = temp_png("scrub");
+        let before = fs::read(&path).unwrap();
+        assert!(exif(&path, true).is_err());
+        assert_eq!(fs::read(&path).unwrap(), before);
+
+        // An eXIf chunk without GPS tags has nothing to scrub, so tIME is not stamped either.
+        let mut png = png_from_path(&path).unwrap();
+        png.insert_before_iend(Chunk::new(ChunkType::EXIF, b"II*\0\x08\0\0\0\0\0\0\0\0\0"));
+        fs::write(&path, png.bytes()).unwrap();
+        exif(&path, true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), png.bytes());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rewrite_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_png("mode");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        text_set(&path, "Comment", "hi", None, false).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rewrite_keeps_other_files() {
+        let path = temp_png("rewrite");
+        let tmp_path = format!("{path}.tmp");
+        fs::write(&tmp_path, "unrelated").unwrap();
+        text_set(&path, "Comment", "hi", None, false).unwrap();
+        assert_eq!(fs::read_to_string(&tmp_path).unwrap(), "unrelated");
+
+        // A failed edit leaves neither a temporary file nor a changed original.
+        let before = fs::read(&path).unwrap();
+        assert!(remove(&args(&["remove", &path, "zzZz"])).is_err());
+        assert_eq!(fs::read(&path).unwrap(), before);
+        let dir = Path::new(&path).parent().unwrap();
+        let prefix = format!("{path}.");
+        let leftovers = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|p| p.to_str().unwrap().starts_with(&prefix) && *p != Path::new(&tmp_path))
+            .count();
+        assert_eq!(leftovers, 0);
+        fs::remove_file(tmp_path).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+}
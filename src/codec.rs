@@ -0,0 +1,234 @@
+//! Typed chunk formats and a registry that renders them by chunk type.
+//!
+//! Implement [`ChunkCodec`] for a private chunk format to read it with
+//! [`Png::decode_chunk`](crate::Png::decode_chunk) and write it with
+//! [`Png::encode_chunk`](crate::Png::encode_chunk). Registering it in a [`CodecRegistry`] lets
+//! code that only holds a [`Chunk`] show it in human-readable form. The registry only renders:
+//! decoding to and encoding from a typed value always goes through the trait, which needs the
+//! type to be named anyway.
+//!
+//! ```
+//! use std::fmt;
+//!
+//! use pngme::{ChunkCodec, ChunkType, CodecRegistry, Error};
+//!
+//! struct Score(u32);
+//!
+//! impl ChunkCodec for Score {
+//!     const CHUNK_TYPE: ChunkType = ChunkType::new(*b"scOr");
+//!
+//!     fn decode(data: &[u8]) -> Result<Self, Error> {
+//!         let bytes = data.try_into().map_err(|_| Error::MalformedChunk {
+//!             chunk_type: Self::CHUNK_TYPE,
+//!             reason: "expected 4 bytes".to_owned(),
+//!         })?;
+//!         Ok(Self(u32::from_be_bytes(bytes)))
+//!     }
+//!     fn encode(&self) -> Vec<u8> {
+//!         self.0.to_be_bytes().to_vec()
+//!     }
+//! }
+//!
+//! impl fmt::Display for Score {
+//!     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//!         write!(f, "{} points", self.0)
+//!     }
+//! }
+//!
+//! let mut registry = CodecRegistry::with_builtins();
+//! registry.register::<Score>();
+//! let chunk = pngme::codec::to_chunk(&Score(42));
+//! assert_eq!(registry.render(&chunk).unwrap()?, "42 points");
+//! # Ok::<(), Error>(())
+//! ```
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    apng::{AnimationControl, FrameControl},
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    color::{
+        Chromaticities, Cicp, ContentLightLevel, Gamma, IccProfile, MasteringDisplay,
+        RenderingIntent,
+    },
+    ihdr::Ihdr,
+    palette::{Histogram, Palette, SuggestedPalette},
+    physical::{Offset, PhysicalDimensions, PhysicalScale, PixelCalibration},
+    time::Timestamp,
+    Error,
+};
+
+/// A value stored as the data of one chunk type.
+pub trait ChunkCodec: Sized {
+    /// The chunk type holding the value.
+    const CHUNK_TYPE: ChunkType;
+
+    /// Parses the data of a chunk of type [`Self::CHUNK_TYPE`].
+    fn decode(data: &[u8]) -> Result<Self, Error>;
+    /// Serializes the value as chunk data.
+    fn encode(&self) -> Vec<u8>;
+}
+
+/// Creates a chunk holding `value`.
+pub fn to_chunk<T: ChunkCodec>(value: &T) -> Chunk {
+    Chunk::new(T::CHUNK_TYPE, value.encode())
+}
+
+/// Decodes a chunk and formats the value.
+type Render = fn(&Chunk) -> Result<String, Error>;
+
+/// Renders chunks whose type has a registered codec. It does not decode or encode typed values;
+/// use [`ChunkCodec`] for that.
+#[derive(Debug, Clone, Default)]
+pub struct CodecRegistry {
+    renderers: HashMap<ChunkType, Render>,
+}
+
+impl CodecRegistry {
+    /// Creates a registry with no codecs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Creates a registry with a codec for every standard chunk whose layout does not depend on
+    /// other chunks.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register::<Ihdr>()
+            .register::<Palette>()
+            .register::<Histogram>()
+            .register::<SuggestedPalette>()
+            .register::<IccProfile>()
+            .register::<RenderingIntent>()
+            .register::<Gamma>()
+            .register::<Chromaticities>()
+            .register::<Cicp>()
+            .register::<MasteringDisplay>()
+            .register::<ContentLightLevel>()
+            .register::<PhysicalDimensions>()
+            .register::<Offset>()
+            .register::<PixelCalibration>()
+            .register::<PhysicalScale>()
+            .register::<Timestamp>()
+            .register::<AnimationControl>()
+            .register::<FrameControl>();
+        registry
+    }
+    /// Registers `T`'s decoder and [`Display`] to render its chunk type, replacing any codec
+    /// registered before.
+    pub fn register<T: ChunkCodec + Display>(&mut self) -> &mut Self {
+        self.renderers.insert(T::CHUNK_TYPE, render::<T>);
+        self
+    }
+    /// Whether a codec is registered for `chunk_type`.
+    pub fn contains(&self, chunk_type: ChunkType) -> bool {
+        self.renderers.contains_key(&chunk_type)
+    }
+    /// Decodes `chunk` with the codec registered for its type and formats it, or `None` if no
+    /// codec is registered.
+    pub fn render(&self, chunk: &Chunk) -> Option<Result<String, Error>> {
+        self.renderers
+            .get(chunk.r#type())
+            .map(|render| render(chunk))
+    }
+}
+
+fn render<T: ChunkCodec + Display>(chunk: &Chunk) -> Result<String, Error> {
+    T::decode(chunk.data()).map(|value| value.to_string())
+}
+
+/// Implements [`ChunkCodec`] for standard chunks through their `TryFrom<&[u8]>` and `to_chunk`.
+macro_rules! builtin_codec {
+    ($($t:ty => $chunk_type:ident),* $(,)?) => {
+        $(
+            impl ChunkCodec for $t {
+                const CHUNK_TYPE: ChunkType = ChunkType::$chunk_type;
+
+                fn decode(data: &[u8]) -> Result<Self, Error> {
+                    Self::try_from(data)
+                }
+                fn encode(&self) -> Vec<u8> {
+                    self.to_chunk().data().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+builtin_codec! {
+    Ihdr => IHDR,
+    Palette => PLTE,
+    Histogram => HIST,
+    SuggestedPalette => SPLT,
+    IccProfile => ICCP,
+    RenderingIntent => SRGB,
+    Gamma => GAMA,
+    Chromaticities => CHRM,
+    Cicp => CICP,
+    MasteringDisplay => MDCV,
+    ContentLightLevel => CLLI,
+    PhysicalDimensions => PHYS,
+    Offset => OFFS,
+    PixelCalibration => PCAL,
+    PhysicalScale => SCAL,
+    Timestamp => TIME,
+    AnimationControl => ACTL,
+    FrameControl => FCTL,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::PhysicalUnit;
+
+    #[derive(Debug, PartialEq)]
+    struct Note(String);
+
+    impl ChunkCodec for Note {
+        const CHUNK_TYPE: ChunkType = ChunkType::new(*b"noTe");
+
+        fn decode(data: &[u8]) -> Result<Self, Error> {
+            Ok(Self(std::str::from_utf8(data)?.to_owned()))
+        }
+        fn encode(&self) -> Vec<u8> {
+            self.0.as_bytes().to_vec()
+        }
+    }
+
+    impl Display for Note {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "note {:?}", self.0)
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = CodecRegistry::with_builtins();
+        let note = to_chunk(&Note("hi".to_owned()));
+        assert!(registry.render(&note).is_none());
+        registry.register::<Note>();
+        assert!(registry.contains(Note::CHUNK_TYPE));
+        assert_eq!(registry.render(&note).unwrap().unwrap(), "note \"hi\"");
+        assert!(registry
+            .render(&Chunk::new(Note::CHUNK_TYPE, [0xff]))
+            .unwrap()
+            .is_err());
+
+        let time = Timestamp::new(2024, 1, 2, 3, 4, 5).unwrap().to_chunk();
+        assert_eq!(
+            registry.render(&time).unwrap().unwrap(),
+            "2024-01-02 03:04:05 UTC"
+        );
+        assert!(CodecRegistry::new().render(&time).is_none());
+    }
+
+    #[test]
+    fn test_builtin_codec() {
+        let phys = PhysicalDimensions::new(2835, 2835, PhysicalUnit::Meter);
+        let chunk = to_chunk(&phys);
+        assert_eq!(chunk.data(), phys.to_chunk().data());
+        assert_eq!(PhysicalDimensions::decode(chunk.data()).unwrap(), phys);
+        assert!(Gamma::decode(&[0; 3]).is_err());
+    }
+}
@@ -0,0 +1,751 @@
+//! Color space chunks: `iCCP`, `sRGB`, `gAMA`, `cHRM`, `cICP`, `mDCV` and `cLLI`.
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    deflate::Compression,
+    text::{self, check_keyword},
+    zlib, Error,
+};
+
+/// Largest decompressed ICC profile accepted from an `iCCP` chunk.
+const MAX_PROFILE_LEN: usize = 16 * 1024 * 1024;
+
+/// An embedded ICC profile, from an `iCCP` chunk.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IccProfile {
+    name: String,
+    profile: Vec<u8>,
+}
+
+impl TryFrom<&[u8]> for IccProfile {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let malformed = |reason: String| malformed(ChunkType::ICCP, reason);
+        let (name, rest) = text::split_nul(data)
+            .ok_or_else(|| malformed("missing profile name separator".to_owned()))?;
+        let name = text::latin1(name);
+        text::check_stored_keyword(ChunkType::ICCP, &name)?;
+        let Some((0, profile)) = rest.split_first() else {
+            return Err(malformed(
+                "missing or invalid compression method".to_owned(),
+            ));
+        };
+
+        Ok(Self {
+            name,
+            profile: zlib::decompress_limited(profile, MAX_PROFILE_LEN)?,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for IccProfile {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::ICCP)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for IccProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}, {} bytes", self.name, self.profile.len())?;
+        match self.header() {
+            Ok(header) => write!(f, ", {header}"),
+            Err(_) => f.write_str(", invalid header"),
+        }
+    }
+}
+
+impl IccProfile {
+    /// Creates a profile, checking that `name` is a valid keyword.
+    pub fn new(name: impl Into<String>, profile: impl Into<Vec<u8>>) -> Result<Self, Error> {
+        let name = name.into();
+        check_keyword(&name)?;
+        Ok(Self {
+            name,
+            profile: profile.into(),
+        })
+    }
+    /// The profile name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The decompressed ICC profile.
+    pub fn profile(&self) -> &[u8] {
+        &self.profile
+    }
+    /// The fixed 128-byte header at the start of the profile.
+    pub fn header(&self) -> Result<IccHeader, Error> {
+        IccHeader::try_from(self.profile.as_slice())
+    }
+    /// Serializes the profile as an `iCCP` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = text::to_latin1(&self.name);
+        data.extend_from_slice(&[0, 0]);
+        data.extend(zlib::compress(&self.profile, Compression::Best));
+        Chunk::new(ChunkType::ICCP, data)
+    }
+}
+
+/// A summary of an ICC profile header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IccHeader {
+    size: u32,
+    version: (u8, u8, u8),
+    class: String,
+    color_space: String,
+    connection_space: String,
+    rendering_intent: u32,
+}
+
+impl TryFrom<&[u8]> for IccHeader {
+    type Error = Error;
+
+    fn try_from(profile: &[u8]) -> Result<Self, Self::Error> {
+        let header = profile
+            .get(..128)
+            .ok_or_else(|| malformed(ChunkType::ICCP, "profile header is truncated".to_owned()))?;
+        if &header[36..40] != b"acsp" {
+            return Err(malformed(
+                ChunkType::ICCP,
+                "missing profile signature".to_owned(),
+            ));
+        }
+        let u32_at = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap());
+        let signature = |i: usize| String::from_utf8_lossy(&header[i..i + 4]).trim().to_owned();
+
+        Ok(Self {
+            size: u32_at(0),
+            version: (header[8], header[9] >> 4, header[9] & 0xf),
+            class: signature(12),
+            color_space: signature(16),
+            connection_space: signature(20),
+            rendering_intent: u32_at(64),
+        })
+    }
+}
+
+impl std::fmt::Display for IccHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (major, minor, bugfix) = self.version;
+        write!(
+            f,
+            "ICC v{major}.{minor}.{bugfix} {} profile, {} to {}",
+            self.class, self.color_space, self.connection_space
+        )
+    }
+}
+
+impl IccHeader {
+    /// Profile size declared in the header.
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+    /// Major, minor and bug-fix version.
+    pub const fn version(&self) -> (u8, u8, u8) {
+        self.version
+    }
+    /// Device class signature, such as `mntr` for displays.
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+    /// Color space of the data, such as `RGB` or `GRAY`.
+    pub fn color_space(&self) -> &str {
+        &self.color_space
+    }
+    /// Profile connection space, `XYZ` or `Lab`.
+    pub fn connection_space(&self) -> &str {
+        &self.connection_space
+    }
+    /// Rendering intent the profile was built for.
+    pub const fn rendering_intent(&self) -> u32 {
+        self.rendering_intent
+    }
+}
+
+/// How colors outside the output gamut are mapped, from an `sRGB` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RenderingIntent {
+    Perceptual = 0,
+    RelativeColorimetric = 1,
+    Saturation = 2,
+    AbsoluteColorimetric = 3,
+}
+
+impl TryFrom<&[u8]> for RenderingIntent {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let [intent] = fixed(data, ChunkType::SRGB)?;
+        match intent {
+            0 => Ok(Self::Perceptual),
+            1 => Ok(Self::RelativeColorimetric),
+            2 => Ok(Self::Saturation),
+            3 => Ok(Self::AbsoluteColorimetric),
+            _ => Err(malformed(
+                ChunkType::SRGB,
+                format!("invalid rendering intent {intent}"),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&Chunk> for RenderingIntent {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::SRGB)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for RenderingIntent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Perceptual => "perceptual",
+            Self::RelativeColorimetric => "relative colorimetric",
+            Self::Saturation => "saturation",
+            Self::AbsoluteColorimetric => "absolute colorimetric",
+        })
+    }
+}
+
+impl RenderingIntent {
+    /// Serializes the intent as an `sRGB` chunk.
+    pub fn to_chunk(self) -> Chunk {
+        Chunk::new(ChunkType::SRGB, [self as u8])
+    }
+}
+
+/// Image gamma from a `gAMA` chunk, stored times 100000.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Gamma(u32);
+
+impl TryFrom<&[u8]> for Gamma {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        match u32::from_be_bytes(fixed(data, ChunkType::GAMA)?) {
+            0 => Err(malformed(ChunkType::GAMA, "gamma is 0".to_owned())),
+            gamma => Ok(Self(gamma)),
+        }
+    }
+}
+
+impl TryFrom<&Chunk> for Gamma {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::GAMA)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Gamma {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.5}", self.value())
+    }
+}
+
+impl Gamma {
+    /// The gamma of sRGB-like content, 1 / 2.2.
+    pub const SRGB: Self = Self(45455);
+
+    /// Creates a gamma from its value times 100000.
+    pub const fn new(scaled: u32) -> Self {
+        Self(scaled)
+    }
+    /// The value times 100000, as stored.
+    pub const fn scaled(self) -> u32 {
+        self.0
+    }
+    /// The value.
+    pub fn value(self) -> f64 {
+        self.0 as f64 / 100_000.0
+    }
+    /// Serializes the gamma as a `gAMA` chunk.
+    pub fn to_chunk(self) -> Chunk {
+        Chunk::new(ChunkType::GAMA, self.0.to_be_bytes())
+    }
+}
+
+/// CIE 1931 x, y chromaticities of the white point and primaries from a `cHRM` chunk, each
+/// stored times 100000.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Chromaticities {
+    white: (u32, u32),
+    red: (u32, u32),
+    green: (u32, u32),
+    blue: (u32, u32),
+}
+
+impl TryFrom<&[u8]> for Chromaticities {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 32] = fixed(data, ChunkType::CHRM)?;
+        let at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        Ok(Self {
+            white: (at(0), at(4)),
+            red: (at(8), at(12)),
+            green: (at(16), at(20)),
+            blue: (at(24), at(28)),
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for Chromaticities {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::CHRM)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Chromaticities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let xy = |(x, y): (u32, u32)| format!("({:.5}, {:.5})", x as f64 / 1e5, y as f64 / 1e5);
+        write!(
+            f,
+            "white {}, red {}, green {}, blue {}",
+            xy(self.white),
+            xy(self.red),
+            xy(self.green),
+            xy(self.blue)
+        )
+    }
+}
+
+impl Chromaticities {
+    /// The sRGB / BT.709 primaries with a D65 white point.
+    pub const SRGB: Self = Self {
+        white: (31270, 32900),
+        red: (64000, 33000),
+        green: (30000, 60000),
+        blue: (15000, 6000),
+    };
+
+    /// Creates chromaticities from x, y pairs times 100000.
+    pub const fn new(
+        white: (u32, u32),
+        red: (u32, u32),
+        green: (u32, u32),
+        blue: (u32, u32),
+    ) -> Self {
+        Self {
+            white,
+            red,
+            green,
+            blue,
+        }
+    }
+    /// White point x, y times 100000.
+    pub const fn white(&self) -> (u32, u32) {
+        self.white
+    }
+    /// Red primary x, y times 100000.
+    pub const fn red(&self) -> (u32, u32) {
+        self.red
+    }
+    /// Green primary x, y times 100000.
+    pub const fn green(&self) -> (u32, u32) {
+        self.green
+    }
+    /// Blue primary x, y times 100000.
+    pub const fn blue(&self) -> (u32, u32) {
+        self.blue
+    }
+    /// Serializes the chromaticities as a `cHRM` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let data = [self.white, self.red, self.green, self.blue]
+            .iter()
+            .flat_map(|&(x, y)| [x.to_be_bytes(), y.to_be_bytes()])
+            .flatten()
+            .collect::<Vec<_>>();
+        Chunk::new(ChunkType::CHRM, data)
+    }
+}
+
+/// Coding-independent code points (ITU-T H.273) from a `cICP` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cicp {
+    color_primaries: u8,
+    transfer_function: u8,
+    full_range: bool,
+}
+
+impl TryFrom<&[u8]> for Cicp {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let [color_primaries, transfer_function, matrix, full_range] =
+            fixed(data, ChunkType::CICP)?;
+        if matrix != 0 {
+            return Err(malformed(
+                ChunkType::CICP,
+                format!("matrix coefficients must be 0 for RGB, found {matrix}"),
+            ));
+        }
+        if full_range > 1 {
+            return Err(malformed(
+                ChunkType::CICP,
+                format!("invalid video full range flag {full_range}"),
+            ));
+        }
+
+        Ok(Self {
+            color_primaries,
+            transfer_function,
+            full_range: full_range == 1,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for Cicp {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::CICP)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Cicp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let primaries = match self.color_primaries {
+            1 => "BT.709",
+            9 => "BT.2020",
+            11 => "DCI-P3",
+            12 => "Display P3",
+            _ => "",
+        };
+        let transfer = match self.transfer_function {
+            1 => "BT.709",
+            8 => "linear",
+            13 => "sRGB",
+            16 => "PQ",
+            18 => "HLG",
+            _ => "",
+        };
+        let name = |code: u8, name: &str| match name {
+            "" => code.to_string(),
+            name => format!("{code} ({name})"),
+        };
+        write!(
+            f,
+            "primaries {}, transfer {}, {} range",
+            name(self.color_primaries, primaries),
+            name(self.transfer_function, transfer),
+            if self.full_range { "full" } else { "narrow" }
+        )
+    }
+}
+
+impl Cicp {
+    /// Creates code points for RGB data.
+    pub const fn new(color_primaries: u8, transfer_function: u8, full_range: bool) -> Self {
+        Self {
+            color_primaries,
+            transfer_function,
+            full_range,
+        }
+    }
+    /// Color primaries code point.
+    pub const fn color_primaries(&self) -> u8 {
+        self.color_primaries
+    }
+    /// Transfer characteristics code point.
+    pub const fn transfer_function(&self) -> u8 {
+        self.transfer_function
+    }
+    /// Always 0: PNG stores RGB.
+    pub const fn matrix_coefficients(&self) -> u8 {
+        0
+    }
+    /// Whether samples use the full range rather than the narrow video range.
+    pub const fn full_range(&self) -> bool {
+        self.full_range
+    }
+    /// Serializes the code points as a `cICP` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(
+            ChunkType::CICP,
+            [
+                self.color_primaries,
+                self.transfer_function,
+                self.matrix_coefficients(),
+                self.full_range as u8,
+            ],
+        )
+    }
+}
+
+/// The color volume of the display content was mastered on, from an `mDCV` chunk.
+///
+/// Chromaticities are stored in units of 0.00002 and luminances in units of 0.0001 cd/m².
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MasteringDisplay {
+    primaries: [(u16, u16); 3],
+    white: (u16, u16),
+    max_luminance: u32,
+    min_luminance: u32,
+}
+
+impl TryFrom<&[u8]> for MasteringDisplay {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 24] = fixed(data, ChunkType::MDCV)?;
+        let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+        let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        Ok(Self {
+            primaries: [
+                (u16_at(0), u16_at(2)),
+                (u16_at(4), u16_at(6)),
+                (u16_at(8), u16_at(10)),
+            ],
+            white: (u16_at(12), u16_at(14)),
+            max_luminance: u32_at(16),
+            min_luminance: u32_at(20),
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for MasteringDisplay {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::MDCV)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for MasteringDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let xy = |(x, y): (u16, u16)| format!("({:.4}, {:.4})", x as f64 / 5e4, y as f64 / 5e4);
+        write!(
+            f,
+            "primaries {} {} {}, white {}, luminance {}-{} cd/m²",
+            xy(self.primaries[0]),
+            xy(self.primaries[1]),
+            xy(self.primaries[2]),
+            xy(self.white),
+            self.min_luminance as f64 / 1e4,
+            self.max_luminance as f64 / 1e4
+        )
+    }
+}
+
+impl MasteringDisplay {
+    /// Creates mastering display metadata from stored units.
+    pub const fn new(
+        primaries: [(u16, u16); 3],
+        white: (u16, u16),
+        max_luminance: u32,
+        min_luminance: u32,
+    ) -> Self {
+        Self {
+            primaries,
+            white,
+            max_luminance,
+            min_luminance,
+        }
+    }
+    /// Red, green and blue primary x, y in units of 0.00002.
+    pub const fn primaries(&self) -> [(u16, u16); 3] {
+        self.primaries
+    }
+    /// White point x, y in units of 0.00002.
+    pub const fn white(&self) -> (u16, u16) {
+        self.white
+    }
+    /// Maximum luminance in units of 0.0001 cd/m².
+    pub const fn max_luminance(&self) -> u32 {
+        self.max_luminance
+    }
+    /// Minimum luminance in units of 0.0001 cd/m².
+    pub const fn min_luminance(&self) -> u32 {
+        self.min_luminance
+    }
+    /// Serializes the metadata as an `mDCV` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(24);
+        for (x, y) in self.primaries.into_iter().chain([self.white]) {
+            data.extend_from_slice(&x.to_be_bytes());
+            data.extend_from_slice(&y.to_be_bytes());
+        }
+        data.extend_from_slice(&self.max_luminance.to_be_bytes());
+        data.extend_from_slice(&self.min_luminance.to_be_bytes());
+        Chunk::new(ChunkType::MDCV, data)
+    }
+}
+
+/// Content light levels from a `cLLI` chunk, in units of 0.0001 cd/m².
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ContentLightLevel {
+    max_cll: u32,
+    max_fall: u32,
+}
+
+impl TryFrom<&[u8]> for ContentLightLevel {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 8] = fixed(data, ChunkType::CLLI)?;
+        Ok(Self {
+            max_cll: u32::from_be_bytes(data[..4].try_into().unwrap()),
+            max_fall: u32::from_be_bytes(data[4..].try_into().unwrap()),
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for ContentLightLevel {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::CLLI)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for ContentLightLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MaxCLL {} cd/m², MaxFALL {} cd/m²",
+            self.max_cll as f64 / 1e4,
+            self.max_fall as f64 / 1e4
+        )
+    }
+}
+
+impl ContentLightLevel {
+    /// Creates light levels from stored units.
+    pub const fn new(max_cll: u32, max_fall: u32) -> Self {
+        Self { max_cll, max_fall }
+    }
+    /// Maximum content light level in units of 0.0001 cd/m².
+    pub const fn max_cll(&self) -> u32 {
+        self.max_cll
+    }
+    /// Maximum frame-average light level in units of 0.0001 cd/m².
+    pub const fn max_fall(&self) -> u32 {
+        self.max_fall
+    }
+    /// Serializes the light levels as a `cLLI` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = self.max_cll.to_be_bytes().to_vec();
+        data.extend_from_slice(&self.max_fall.to_be_bytes());
+        Chunk::new(ChunkType::CLLI, data)
+    }
+}
+
+pub(crate) fn check_type(chunk: &Chunk, expected: ChunkType) -> Result<(), Error> {
+    if *chunk.r#type() != expected {
+        return Err(malformed(
+            expected,
+            format!("expected {expected}, found {}", chunk.r#type()),
+        ));
+    }
+    Ok(())
+}
+
+/// The data of a fixed-length chunk of type `expected`.
+pub(crate) fn fixed<const N: usize>(data: &[u8], expected: ChunkType) -> Result<[u8; N], Error> {
+    data.try_into().map_err(|_| {
+        malformed(
+            expected,
+            format!("expected {N} bytes, found {}", data.len()),
+        )
+    })
+}
+
+pub(crate) fn malformed(chunk_type: ChunkType, reason: String) -> Error {
+    Error::MalformedChunk { chunk_type, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn icc_header() -> Vec<u8> {
+        let mut profile = vec![0; 132];
+        profile[..4].copy_from_slice(&132_u32.to_be_bytes());
+        profile[8..10].copy_from_slice(&[4, 0x30]);
+        profile[12..24].copy_from_slice(b"mntrRGB XYZ ");
+        profile[36..40].copy_from_slice(b"acsp");
+        profile
+    }
+
+    #[test]
+    fn test_icc_profile() {
+        let icc = IccProfile::new("Display P3", icc_header()).unwrap();
+        let parsed = IccProfile::try_from(&icc.to_chunk()).unwrap();
+        assert_eq!(parsed, icc);
+        let header = parsed.header().unwrap();
+        assert_eq!(header.version(), (4, 3, 0));
+        assert_eq!(header.class(), "mntr");
+        assert_eq!(header.color_space(), "RGB");
+        assert_eq!(
+            parsed.to_string(),
+            r#""Display P3", 132 bytes, ICC v4.3.0 mntr profile, RGB to XYZ"#
+        );
+
+        assert!(IccProfile::new("name", [0; 10]).unwrap().header().is_err());
+        assert!(IccProfile::new(" name", []).is_err());
+        let mut chunk = icc.to_chunk().data().to_vec();
+        chunk[11] = 1;
+        assert!(IccProfile::try_from(&Chunk::new(ChunkType::ICCP, chunk)).is_err());
+    }
+
+    #[test]
+    fn test_fixed_length_chunks() {
+        let intent = RenderingIntent::Saturation;
+        assert_eq!(
+            RenderingIntent::try_from(&intent.to_chunk()).unwrap(),
+            intent
+        );
+        assert!(RenderingIntent::try_from(&Chunk::new(ChunkType::SRGB, [4])).is_err());
+
+        assert_eq!(
+            Gamma::try_from(&Gamma::SRGB.to_chunk()).unwrap(),
+            Gamma::SRGB
+        );
+        assert_eq!(Gamma::SRGB.to_string(), "0.45455");
+        assert!(Gamma::try_from(&Chunk::new(ChunkType::GAMA, [0; 4])).is_err());
+        assert!(Gamma::try_from(&Chunk::new(ChunkType::GAMA, [0; 3])).is_err());
+
+        let chrm = Chromaticities::SRGB;
+        assert_eq!(Chromaticities::try_from(&chrm.to_chunk()).unwrap(), chrm);
+        assert!(chrm
+            .to_string()
+            .starts_with("white (0.31270, 0.32900), red"));
+
+        let cicp = Cicp::new(9, 16, true);
+        assert_eq!(Cicp::try_from(&cicp.to_chunk()).unwrap(), cicp);
+        assert_eq!(
+            cicp.to_string(),
+            "primaries 9 (BT.2020), transfer 16 (PQ), full range"
+        );
+        assert!(Cicp::try_from(&Chunk::new(ChunkType::CICP, [9, 16, 1, 1])).is_err());
+        assert!(Cicp::try_from(&Chunk::new(ChunkType::CICP, [9, 16, 0, 2])).is_err());
+
+        let mdcv = MasteringDisplay::new(
+            [(35400, 14600), (8500, 39850), (6550, 2300)],
+            (15635, 16450),
+            10_000_000,
+            1,
+        );
+        assert_eq!(MasteringDisplay::try_from(&mdcv.to_chunk()).unwrap(), mdcv);
+        assert!(mdcv.to_string().ends_with("luminance 0.0001-1000 cd/m²"));
+
+        let clli = ContentLightLevel::new(10_000_000, 4_000_000);
+        assert_eq!(ContentLightLevel::try_from(&clli.to_chunk()).unwrap(), clli);
+        assert_eq!(clli.to_string(), "MaxCLL 1000 cd/m², MaxFALL 400 cd/m²");
+        assert!(ContentLightLevel::try_from(&Chunk::new(ChunkType::GAMA, [0; 8])).is_err());
+    }
+}
@@ -0,0 +1,621 @@
+//! Raw DEFLATE compression (RFC 1951).
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::inflate::{CODE_LENGTH_ORDER, DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+const WINDOW_SIZE: usize = 1 << 15;
+const HASH_BITS: u32 = 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_STORED: usize = u16::MAX as usize;
+/// Tokens per compressed block; each block gets its own Huffman codes.
+const BLOCK_TOKENS: usize = 1 << 14;
+const END_OF_BLOCK: usize = 256;
+
+/// Trade-off between compression speed and output size.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Compression {
+    /// Stored blocks only: no compression, fastest.
+    None,
+    /// Short LZ77 match search with the fixed Huffman code.
+    Fast,
+    /// LZ77 with lazy matching and per-block dynamic Huffman codes.
+    #[default]
+    Default,
+    /// Like [`Compression::Default`], with a much longer match search.
+    Best,
+}
+
+impl Compression {
+    /// The zlib `FLEVEL` header field advertising this level.
+    pub(crate) const fn zlib_level(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Fast => 1,
+            Self::Default => 2,
+            Self::Best => 3,
+        }
+    }
+    /// Match search parameters: (hash chain length, lazy matching, good enough length).
+    const fn search(self) -> (usize, bool, usize) {
+        match self {
+            Self::None => (0, false, 0),
+            Self::Fast => (4, false, 16),
+            Self::Default => (32, true, 128),
+            Self::Best => (1024, true, MAX_MATCH),
+        }
+    }
+}
+
+/// Compresses `data` into a raw DEFLATE stream.
+pub fn deflate(data: &[u8], level: Compression) -> Vec<u8> {
+    let mut out = BitWriter::default();
+    if level == Compression::None || data.is_empty() {
+        write_stored(&mut out, data, true);
+        return out.finish();
+    }
+
+    let tokens = tokenize(data, level);
+    let mut start = 0;
+    let blocks = tokens.chunks(BLOCK_TOKENS).collect::<Vec<_>>();
+    for (i, block) in blocks.iter().enumerate() {
+        let last = i + 1 == blocks.len();
+        let len = block.iter().map(|t| t.len()).sum::<usize>();
+        write_block(&mut out, block, &data[start..start + len], level, last);
+        start += len;
+    }
+    out.finish()
+}
+
+/// An LZ77 token: a literal byte, or a back-reference of `len` bytes at `dist`.
+#[derive(Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match { len: u16, dist: u16 },
+}
+
+impl Token {
+    const fn len(self) -> usize {
+        match self {
+            Self::Literal(_) => 1,
+            Self::Match { len, .. } => len as usize,
+        }
+    }
+}
+
+struct Matcher<'a> {
+    data: &'a [u8],
+    /// Positions below this one are in the hash chains.
+    inserted: usize,
+    head: Vec<usize>,
+    prev: Vec<usize>,
+    max_chain: usize,
+    good_len: usize,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(data: &'a [u8], max_chain: usize, good_len: usize) -> Self {
+        Self {
+            data,
+            inserted: 0,
+            head: vec![usize::MAX; 1 << HASH_BITS],
+            prev: vec![usize::MAX; WINDOW_SIZE],
+            max_chain,
+            good_len,
+        }
+    }
+    fn hash(&self, i: usize) -> usize {
+        let d = self.data;
+        let key = (d[i] as u32) << 16 | (d[i + 1] as u32) << 8 | d[i + 2] as u32;
+        (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+    }
+    /// Adds every position before `end` to the hash chains.
+    fn insert_until(&mut self, end: usize) {
+        let end = end.min(self.data.len().saturating_sub(MIN_MATCH - 1));
+        for i in self.inserted..end {
+            let h = self.hash(i);
+            self.prev[i % WINDOW_SIZE] = self.head[h];
+            self.head[h] = i;
+        }
+        self.inserted = self.inserted.max(end);
+    }
+    /// The longest earlier match for the bytes at `i`, as (length, distance).
+    fn find(&self, i: usize) -> (usize, usize) {
+        let data = self.data;
+        if i + MIN_MATCH > data.len() {
+            return (0, 0);
+        }
+        let max_len = (data.len() - i).min(MAX_MATCH);
+        let (mut best_len, mut best_dist) = (0, 0);
+        let mut candidate = self.head[self.hash(i)];
+        for _ in 0..self.max_chain {
+            if candidate == usize::MAX || candidate >= i || i - candidate > WINDOW_SIZE {
+                break;
+            }
+            if data[candidate + best_len] == data[i + best_len] {
+                let len = data[candidate..candidate + max_len]
+                    .iter()
+                    .zip(&data[i..i + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    (best_len, best_dist) = (len, i - candidate);
+                    if len >= self.good_len.min(max_len) {
+                        break;
+                    }
+                }
+            }
+            let next = self.prev[candidate % WINDOW_SIZE];
+            if next >= candidate {
+                break;
+            }
+            candidate = next;
+        }
+        if best_len >= MIN_MATCH {
+            (best_len, best_dist)
+        } else {
+            (0, 0)
+        }
+    }
+}
+
+fn tokenize(data: &[u8], level: Compression) -> Vec<Token> {
+    let (max_chain, lazy, good_len) = level.search();
+    let mut matcher = Matcher::new(data, max_chain, good_len);
+    let mut tokens = Vec::with_capacity(data.len() / 2);
+    let mut i = 0;
+    while i < data.len() {
+        let (mut len, mut dist) = matcher.find(i);
+        if lazy && len >= MIN_MATCH && len < good_len {
+            matcher.insert_until(i + 1);
+            let (next_len, next_dist) = matcher.find(i + 1);
+            if next_len > len {
+                tokens.push(Token::Literal(data[i]));
+                i += 1;
+                (len, dist) = (next_len, next_dist);
+            }
+        }
+        if len >= MIN_MATCH {
+            tokens.push(Token::Match {
+                len: len as u16,
+                dist: dist as u16,
+            });
+            i += len;
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            i += 1;
+        }
+        matcher.insert_until(i);
+    }
+    tokens
+}
+
+/// Symbol and extra bits `(symbol, extra value, extra bit count)` for a match length.
+fn length_symbol(len: usize) -> (usize, u32, u32) {
+    let i = LENGTH_BASE.partition_point(|&base| base as usize <= len) - 1;
+    (
+        257 + i,
+        (len - LENGTH_BASE[i] as usize) as u32,
+        LENGTH_EXTRA[i] as u32,
+    )
+}
+
+/// Symbol and extra bits `(symbol, extra value, extra bit count)` for a match distance.
+fn dist_symbol(dist: usize) -> (usize, u32, u32) {
+    let i = DIST_BASE.partition_point(|&base| base as usize <= dist) - 1;
+    (
+        i,
+        (dist - DIST_BASE[i] as usize) as u32,
+        DIST_EXTRA[i] as u32,
+    )
+}
+
+fn write_block(out: &mut BitWriter, tokens: &[Token], raw: &[u8], level: Compression, last: bool) {
+    let mut lit_freqs = [0_u32; 286];
+    let mut dist_freqs = [0_u32; 30];
+    let mut extra_bits = 0;
+    lit_freqs[END_OF_BLOCK] = 1;
+    for &token in tokens {
+        match token {
+            Token::Literal(byte) => lit_freqs[byte as usize] += 1,
+            Token::Match { len, dist } => {
+                let (symbol, _, len_extra) = length_symbol(len as usize);
+                let (dist_symbol, _, dist_extra) = dist_symbol(dist as usize);
+                lit_freqs[symbol] += 1;
+                dist_freqs[dist_symbol] += 1;
+                extra_bits += (len_extra + dist_extra) as usize;
+            }
+        }
+    }
+
+    let (fixed_lit, fixed_dist) = fixed_lengths();
+    let fixed_cost = 3 + extra_bits + cost(&lit_freqs, &fixed_lit) + cost(&dist_freqs, &fixed_dist);
+    let stored_cost = raw.len().div_ceil(MAX_STORED).max(1) * (3 + 7 + 32) + raw.len() * 8;
+    let dynamic = (level != Compression::Fast).then(|| {
+        let lit = limited_lengths(&lit_freqs, 15);
+        let dist = limited_lengths(&dist_freqs, 15);
+        let header = DynamicHeader::new(&lit, &dist);
+        let cost =
+            3 + header.cost() + extra_bits + cost(&lit_freqs, &lit) + cost(&dist_freqs, &dist);
+        (cost, lit, dist, header)
+    });
+
+    match dynamic {
+        _ if stored_cost < fixed_cost
+            && dynamic
+                .as_ref()
+                .is_none_or(|(cost, ..)| stored_cost < *cost) =>
+        {
+            write_stored(out, raw, last);
+        }
+        Some((cost, lit, dist, header)) if cost < fixed_cost => {
+            out.write(last as u32, 1);
+            out.write(2, 2);
+            header.write(out);
+            write_tokens(out, tokens, &Codes::new(&lit), &Codes::new(&dist));
+        }
+        _ => {
+            out.write(last as u32, 1);
+            out.write(1, 2);
+            write_tokens(
+                out,
+                tokens,
+                &Codes::new(&fixed_lit),
+                &Codes::new(&fixed_dist),
+            );
+        }
+    }
+}
+
+fn write_stored(out: &mut BitWriter, data: &[u8], last: bool) {
+    let mut blocks = data.chunks(MAX_STORED).peekable();
+    if blocks.peek().is_none() {
+        out.write(last as u32, 1);
+        out.write(0, 2);
+        out.align();
+        out.bytes(&[0, 0, 0xff, 0xff]);
+        return;
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        out.write((last && blocks.peek().is_none()) as u32, 1);
+        out.write(0, 2);
+        out.align();
+        out.bytes(&len.to_le_bytes());
+        out.bytes(&(!len).to_le_bytes());
+        out.bytes(block);
+    }
+}
+
+fn write_tokens(out: &mut BitWriter, tokens: &[Token], lit: &Codes, dist: &Codes) {
+    for &token in tokens {
+        match token {
+            Token::Literal(byte) => lit.write(out, byte as usize),
+            Token::Match { len, dist: d } => {
+                let (symbol, extra, extra_len) = length_symbol(len as usize);
+                lit.write(out, symbol);
+                out.write(extra, extra_len);
+                let (symbol, extra, extra_len) = dist_symbol(d as usize);
+                dist.write(out, symbol);
+                out.write(extra, extra_len);
+            }
+        }
+    }
+    lit.write(out, END_OF_BLOCK);
+}
+
+/// The fixed code, including the two unused literal/length symbols that shape it.
+fn fixed_lengths() -> ([u8; 288], [u8; 30]) {
+    let mut lit = [8; 288];
+    lit[144..256].fill(9);
+    lit[256..280].fill(7);
+    (lit, [5; 30])
+}
+
+fn cost(freqs: &[u32], lengths: &[u8]) -> usize {
+    freqs
+        .iter()
+        .zip(lengths)
+        .map(|(&f, &l)| f as usize * l as usize)
+        .sum()
+}
+
+/// Huffman code lengths for `freqs`, no longer than `limit` bits.
+///
+/// Lengths over the limit are avoided by halving the frequencies and rebuilding, which flattens
+/// the tree. At least two symbols always get a code so that the code is complete.
+fn limited_lengths(freqs: &[u32], limit: u8) -> Vec<u8> {
+    let mut freqs = freqs.to_vec();
+    for i in 0..freqs.len() {
+        if freqs.iter().filter(|&&f| f > 0).count() >= 2 {
+            break;
+        }
+        if freqs[i] == 0 {
+            freqs[i] = 1;
+        }
+    }
+    loop {
+        let lengths = huffman_lengths(&freqs);
+        if lengths.iter().all(|&l| l <= limit) {
+            return lengths;
+        }
+        for f in freqs.iter_mut().filter(|f| **f > 0) {
+            *f = f.div_ceil(2);
+        }
+    }
+}
+
+fn huffman_lengths(freqs: &[u32]) -> Vec<u8> {
+    let leaves = (0..freqs.len())
+        .filter(|&s| freqs[s] > 0)
+        .collect::<Vec<_>>();
+    let mut parents = vec![0; 2 * leaves.len() - 1];
+    let mut heap = leaves
+        .iter()
+        .enumerate()
+        .map(|(node, &s)| Reverse((freqs[s] as u64, node)))
+        .collect::<BinaryHeap<_>>();
+    let mut next = leaves.len();
+    while let (Some(Reverse((fa, a))), Some(Reverse((fb, b)))) = (heap.pop(), heap.pop()) {
+        parents[a] = next;
+        parents[b] = next;
+        heap.push(Reverse((fa + fb, next)));
+        next += 1;
+    }
+
+    // Parents are created after their children, so one backwards pass sets every depth.
+    let mut depths = vec![0_u8; parents.len()];
+    for node in (0..parents.len() - 1).rev() {
+        depths[node] = depths[parents[node]] + 1;
+    }
+    let mut lengths = vec![0; freqs.len()];
+    for (node, &s) in leaves.iter().enumerate() {
+        lengths[s] = depths[node];
+    }
+    lengths
+}
+
+/// Canonical Huffman codes, bit-reversed for LSB-first output.
+struct Codes {
+    codes: Vec<u16>,
+    lengths: Vec<u8>,
+}
+
+impl Codes {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0_u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut next = [0_u16; 16];
+        for len in 1..16 {
+            next[len] = (next[len - 1] + counts[len - 1]) << 1;
+        }
+        let codes = lengths
+            .iter()
+            .map(|&len| match len {
+                0 => 0,
+                _ => {
+                    let code = next[len as usize];
+                    next[len as usize] += 1;
+                    code.reverse_bits() >> (16 - len)
+                }
+            })
+            .collect();
+        Self {
+            codes,
+            lengths: lengths.to_vec(),
+        }
+    }
+    fn write(&self, out: &mut BitWriter, symbol: usize) {
+        out.write(self.codes[symbol] as u32, self.lengths[symbol] as u32);
+    }
+}
+
+/// The code length section of a dynamic block header.
+struct DynamicHeader {
+    lit_count: usize,
+    dist_count: usize,
+    /// Run-length encoded code lengths as (symbol, extra value, extra bit count).
+    runs: Vec<(usize, u32, u32)>,
+    code_lengths: Vec<u8>,
+    code_length_count: usize,
+}
+
+impl DynamicHeader {
+    fn new(lit: &[u8], dist: &[u8]) -> Self {
+        let lit_count = lit
+            .iter()
+            .rposition(|&l| l != 0)
+            .map_or(0, |i| i + 1)
+            .max(257);
+        let dist_count = dist
+            .iter()
+            .rposition(|&l| l != 0)
+            .map_or(0, |i| i + 1)
+            .max(1);
+        let lengths = [&lit[..lit_count], &dist[..dist_count]].concat();
+
+        let mut runs = vec![];
+        let mut i = 0;
+        while i < lengths.len() {
+            let value = lengths[i];
+            let mut run = lengths[i..].iter().take_while(|&&l| l == value).count();
+            i += run;
+            if value == 0 {
+                while run >= 11 {
+                    let n = run.min(138);
+                    runs.push((18, (n - 11) as u32, 7));
+                    run -= n;
+                }
+                if run >= 3 {
+                    runs.push((17, (run - 3) as u32, 3));
+                    run = 0;
+                }
+            } else {
+                runs.push((value as usize, 0, 0));
+                run -= 1;
+                while run >= 3 {
+                    let n = run.min(6);
+                    runs.push((16, (n - 3) as u32, 2));
+                    run -= n;
+                }
+            }
+            runs.extend(std::iter::repeat_n((value as usize, 0, 0), run));
+        }
+
+        let mut freqs = [0_u32; 19];
+        for &(symbol, ..) in &runs {
+            freqs[symbol] += 1;
+        }
+        let code_lengths = limited_lengths(&freqs, 7);
+        let code_length_count = CODE_LENGTH_ORDER
+            .iter()
+            .rposition(|&s| code_lengths[s] != 0)
+            .map_or(0, |i| i + 1)
+            .max(4);
+
+        Self {
+            lit_count,
+            dist_count,
+            runs,
+            code_lengths,
+            code_length_count,
+        }
+    }
+    fn cost(&self) -> usize {
+        14 + 3 * self.code_length_count
+            + self
+                .runs
+                .iter()
+                .map(|&(s, _, extra)| self.code_lengths[s] as usize + extra as usize)
+                .sum::<usize>()
+    }
+    fn write(&self, out: &mut BitWriter) {
+        out.write(self.lit_count as u32 - 257, 5);
+        out.write(self.dist_count as u32 - 1, 5);
+        out.write(self.code_length_count as u32 - 4, 4);
+        for &s in &CODE_LENGTH_ORDER[..self.code_length_count] {
+            out.write(self.code_lengths[s] as u32, 3);
+        }
+        let codes = Codes::new(&self.code_lengths);
+        for &(symbol, extra, extra_len) in &self.runs {
+            codes.write(out, symbol);
+            out.write(extra, extra_len);
+        }
+    }
+}
+
+/// Writes bits least-significant first, as DEFLATE packs them.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buf: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, bits: u32, n: u32) {
+        self.buf |= (bits as u64) << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.buf as u8);
+            self.buf >>= 8;
+            self.count -= 8;
+        }
+    }
+    /// Pads with zero bits to the next byte boundary.
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.write(0, 8 - self.count);
+        }
+    }
+    fn bytes(&mut self, bytes: &[u8]) {
+        debug_assert_eq!(self.count, 0);
+        self.out.extend_from_slice(bytes);
+    }
+    fn finish(mut self) -> Vec<u8> {
+        self.align();
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inflate::inflate;
+
+    fn round_trip(data: &[u8], level: Compression) -> Vec<u8> {
+        let compressed = deflate(data, level);
+        let (out, used) = inflate(&compressed, usize::MAX).unwrap();
+        assert_eq!(used, compressed.len());
+        assert_eq!(out, data);
+        compressed
+    }
+
+    /// Deterministic bytes mixing repetitive text with noise.
+    fn sample(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                match i % 1000 < 700 {
+                    true => b"the quick brown fox jumps over the lazy dog "[i % 44],
+                    false => state as u8,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_all_levels() {
+        let levels = [
+            Compression::None,
+            Compression::Fast,
+            Compression::Default,
+            Compression::Best,
+        ];
+        for data in [vec![], vec![7], vec![0; 100_000], sample(200_000)] {
+            for level in levels {
+                round_trip(&data, level);
+            }
+        }
+    }
+
+    #[test]
+    fn test_levels_compress() {
+        let data = sample(100_000);
+        let stored = round_trip(&data, Compression::None).len();
+        let fast = round_trip(&data, Compression::Fast).len();
+        let default = round_trip(&data, Compression::Default).len();
+        let best = round_trip(&data, Compression::Best).len();
+        assert!(stored > data.len());
+        assert!(fast < stored / 2);
+        assert!(default < fast);
+        assert!(best <= default);
+    }
+
+    #[test]
+    fn test_incompressible_falls_back_to_stored() {
+        let data = (0..5000_u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect::<Vec<_>>();
+        let compressed = round_trip(&data, Compression::Default);
+        assert!(compressed.len() <= data.len() + 5 * data.len().div_ceil(MAX_STORED));
+    }
+
+    #[test]
+    fn test_limited_lengths() {
+        let freqs = (0..40).map(|i| 1 << (i % 31)).collect::<Vec<u32>>();
+        let lengths = limited_lengths(&freqs, 7);
+        assert!(lengths.iter().all(|&l| (1..=7).contains(&l)));
+        let kraft = lengths.iter().map(|&l| 1.0 / (1 << l) as f64).sum::<f64>();
+        assert!(kraft <= 1.0);
+
+        assert_eq!(limited_lengths(&[0, 5, 0], 15), [1, 1, 0]);
+    }
+}
@@ -0,0 +1,460 @@
+//! Ed25519 signatures (RFC 8032).
+
+use std::str::FromStr;
+
+use crate::{envelope::random_bytes, field25519::Fe, hex, sha2::Sha512, Error};
+
+/// The curve constant d = -121665 / 121666.
+const D: Fe = Fe::from_limbs([
+    0x34dca135978a3,
+    0x1a8283b156ebd,
+    0x5e7a26001c029,
+    0x739c663a03cbb,
+    0x52036cee2b6ff,
+]);
+/// 2 * d, as used by point addition.
+const D2: Fe = Fe::from_limbs([
+    0x69b9426b2f159,
+    0x35050762add7a,
+    0x3cf44c0038052,
+    0x6738cc7407977,
+    0x2406d9dc56dff,
+]);
+/// A square root of -1.
+const SQRT_M1: Fe = Fe::from_limbs([
+    0x61b274a0ea0b0,
+    0xd5a5fc8f189d,
+    0x7ef5e9cbd0c60,
+    0x78595a6804c9e,
+    0x2b8324804fc1d,
+]);
+/// The base point, with y = 4/5 and positive x.
+const BASE: Point = Point {
+    x: Fe::from_limbs([
+        0x62d608f25d51a,
+        0x412a4b4f6592a,
+        0x75b7171a4b31d,
+        0x1ff60527118fe,
+        0x216936d3cd6e5,
+    ]),
+    y: Fe::from_limbs([
+        0x6666666666658,
+        0x4cccccccccccc,
+        0x1999999999999,
+        0x3333333333333,
+        0x6666666666666,
+    ]),
+    z: Fe::ONE,
+    t: Fe::from_limbs([
+        0x68ab3a5b7dda3,
+        0xeea2a5eadbb,
+        0x2af8df483c27e,
+        0x332b375274732,
+        0x67875f0fd78b7,
+    ]),
+};
+/// The order of the base point, 2^252 + 27742317777372353535851937790883648493, as 64-bit
+/// limbs, least significant first.
+const L: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0,
+    0x1000000000000000,
+];
+
+/// An Ed25519 public key, written as 64 hex digits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct VerifyingKey([u8; 32]);
+
+impl FromStr for VerifyingKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s.trim())
+            .map(Self)
+            .ok_or_else(|| Error::InvalidKey("expected 64 hex digits".to_owned()))
+    }
+}
+
+impl std::fmt::Display for VerifyingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl VerifyingKey {
+    /// Wraps an encoded curve point. Whether it is valid is checked by [`Self::verify`].
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+    /// The encoded curve point.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+    /// Checks that `signature` was made over `message` by the matching signing key.
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> Result<(), Error> {
+        let a = Point::decode(&self.0)
+            .ok_or_else(|| Error::InvalidKey("not a point on Ed25519".to_owned()))?;
+        let (r, s) = signature.0.split_at(32);
+        let s: [u8; 32] = s.try_into().unwrap();
+        // Accepting s + L as well as s would make signatures malleable.
+        if reduce(&s) != s {
+            return Err(Error::VerificationFailed);
+        }
+        let k = challenge(r, &self.0, message);
+        // [s]B - [k]A must encode to R exactly.
+        if BASE.mul(&s).add(&a.neg().mul(&k)).encode() != r {
+            return Err(Error::VerificationFailed);
+        }
+        Ok(())
+    }
+}
+
+/// An Ed25519 signing key, stored as its 32-byte seed. Its `Debug` output hides the key.
+#[derive(PartialEq, Eq, Clone)]
+pub struct SigningKey([u8; 32]);
+
+impl FromStr for SigningKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s.trim())
+            .map(Self)
+            .ok_or_else(|| Error::InvalidKey("expected 64 hex digits".to_owned()))
+    }
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SigningKey(..)")
+    }
+}
+
+impl SigningKey {
+    /// Generates a key from the operating system's random number generator.
+    pub fn generate() -> Result<Self, Error> {
+        random_bytes().map(Self)
+    }
+    /// Wraps a 32-byte seed.
+    pub const fn from_bytes(seed: [u8; 32]) -> Self {
+        Self(seed)
+    }
+    /// The seed as 64 hex digits, for storing in a key file.
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+    /// The matching public key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey(BASE.mul(&self.expand().0).encode())
+    }
+    /// Signs `message`. Signing is deterministic: the same key and message always give the
+    /// same signature.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let (scalar, prefix) = self.expand();
+        let public = BASE.mul(&scalar).encode();
+        let mut hasher = Sha512::new();
+        hasher.update(&prefix);
+        hasher.update(message);
+        let r = reduce(&hasher.finalize());
+        let big_r = BASE.mul(&r).encode();
+        let k = challenge(&big_r, &public, message);
+
+        let mut signature = [0; 64];
+        signature[..32].copy_from_slice(&big_r);
+        signature[32..].copy_from_slice(&mul_add(&k, &scalar, &r));
+        Signature(signature)
+    }
+
+    /// Hashes the seed into the clamped secret scalar and the nonce prefix.
+    fn expand(&self) -> ([u8; 32], [u8; 32]) {
+        let hash = Sha512::digest(&self.0);
+        let (scalar, prefix) = hash.split_at(32);
+        let mut scalar: [u8; 32] = scalar.try_into().unwrap();
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+        (scalar, prefix.try_into().unwrap())
+    }
+}
+
+/// An Ed25519 signature: the encoded point R followed by the scalar s.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Signature([u8; 64]);
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl Signature {
+    /// Wraps the 64 signature bytes.
+    pub const fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+    /// The 64 signature bytes.
+    pub const fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+/// A point in extended coordinates: x = X/Z, y = Y/Z and xy = T/Z.
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl Point {
+    const IDENTITY: Self = Self {
+        x: Fe::ZERO,
+        y: Fe::ONE,
+        z: Fe::ONE,
+        t: Fe::ZERO,
+    };
+
+    /// Decodes a point per RFC 8032 section 5.1.3, rejecting non-canonical y coordinates.
+    fn decode(bytes: &[u8; 32]) -> Option<Self> {
+        let negative = bytes[31] >> 7 == 1;
+        let y = Fe::from_bytes(bytes);
+        let mut canonical = *bytes;
+        canonical[31] &= 0x7f;
+        if y.to_bytes() != canonical {
+            return None;
+        }
+
+        // x^2 = u / v, so x = u v^3 (u v^7)^((p - 5) / 8), up to a factor of sqrt(-1).
+        let y2 = y.square();
+        let u = y2 - Fe::ONE;
+        let v = D * y2 + Fe::ONE;
+        let v3 = v.square() * v;
+        let mut exponent = [0xff; 32];
+        exponent[0] = 0xfd;
+        exponent[31] = 0x0f;
+        let mut x = u * v3 * (u * v3.square() * v).pow(&exponent);
+        let vx2 = v * x.square();
+        if vx2 == -u {
+            x = x * SQRT_M1;
+        } else if vx2 != u {
+            return None;
+        }
+        if x == Fe::ZERO && negative {
+            return None;
+        }
+        if x.is_negative() != negative {
+            x = -x;
+        }
+        Some(Self {
+            x,
+            y,
+            z: Fe::ONE,
+            t: x * y,
+        })
+    }
+    fn encode(&self) -> [u8; 32] {
+        let z = self.z.invert();
+        let mut bytes = (self.y * z).to_bytes();
+        bytes[31] |= u8::from((self.x * z).is_negative()) << 7;
+        bytes
+    }
+    fn neg(&self) -> Self {
+        Self {
+            x: -self.x,
+            t: -self.t,
+            ..*self
+        }
+    }
+    /// Adds two points with the unified formula from RFC 8032 section 5.1.4, which also
+    /// doubles.
+    fn add(&self, other: &Self) -> Self {
+        let a = (self.y - self.x) * (other.y - other.x);
+        let b = (self.y + self.x) * (other.y + other.x);
+        let c = self.t * D2 * other.t;
+        let d = self.z * other.z;
+        let d = d + d;
+        let (e, f, g, h) = (b - a, d - c, d + c, b + a);
+        Self {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+    /// Multiplies by a little-endian scalar in time independent of its value.
+    fn mul(&self, scalar: &[u8; 32]) -> Self {
+        let mut result = Self::IDENTITY;
+        for bit in (0..256).rev() {
+            result = result.add(&result);
+            let mut sum = result.add(self);
+            let swap = u64::from(scalar[bit / 8] >> (bit % 8) & 1);
+            Fe::swap(&mut result.x, &mut sum.x, swap);
+            Fe::swap(&mut result.y, &mut sum.y, swap);
+            Fe::swap(&mut result.z, &mut sum.z, swap);
+            Fe::swap(&mut result.t, &mut sum.t, swap);
+        }
+        result
+    }
+}
+
+/// The challenge scalar SHA-512(R || A || message) mod L.
+fn challenge(r: &[u8], public: &[u8; 32], message: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha512::new();
+    hasher.update(r);
+    hasher.update(public);
+    hasher.update(message);
+    reduce(&hasher.finalize())
+}
+
+/// Reduces a little-endian integer of at most 512 bits modulo L, one bit at a time so the
+/// running time does not depend on the value.
+fn reduce(bytes: &[u8]) -> [u8; 32] {
+    let mut r = [0_u64; 4];
+    for bit in (0..bytes.len() * 8).rev() {
+        // r < L < 2^253, so doubling it cannot overflow.
+        let mut carry = u64::from(bytes[bit / 8] >> (bit % 8) & 1);
+        for limb in &mut r {
+            let next = *limb >> 63;
+            *limb = *limb << 1 | carry;
+            carry = next;
+        }
+
+        let mut diff = [0; 4];
+        let mut borrow = false;
+        for ((diff, r), l) in diff.iter_mut().zip(r).zip(L) {
+            let (d, b1) = r.overflowing_sub(l);
+            let (d, b2) = d.overflowing_sub(u64::from(borrow));
+            *diff = d;
+            borrow = b1 | b2;
+        }
+        // Keep r if subtracting L borrowed, otherwise take the difference.
+        let keep = u64::from(borrow).wrapping_neg();
+        for (r, diff) in r.iter_mut().zip(diff) {
+            *r = *r & keep | diff & !keep;
+        }
+    }
+    let mut out = [0; 32];
+    for (bytes, limb) in out.chunks_exact_mut(8).zip(r) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+/// Computes (a * b + c) mod L for little-endian scalars.
+fn mul_add(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let limbs = |bytes: &[u8; 32]| -> [u64; 4] {
+        std::array::from_fn(|i| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap()))
+    };
+    let (a, b, c) = (limbs(a), limbs(b), limbs(c));
+    let mut wide = [0_u64; 8];
+    wide[..4].copy_from_slice(&c);
+    for (i, a) in a.into_iter().enumerate() {
+        let mut carry = 0;
+        for (j, b) in b.into_iter().enumerate() {
+            let t = u128::from(a) * u128::from(b) + u128::from(wide[i + j]) + carry;
+            wide[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        // Propagate the carry into the higher limbs; a * b + c < 2^512, so it never overflows.
+        for limb in &mut wide[i + 4..] {
+            let t = u128::from(*limb) + carry;
+            *limb = t as u64;
+            carry = t >> 64;
+        }
+    }
+    let mut bytes = [0; 64];
+    for (bytes, limb) in bytes.chunks_exact_mut(8).zip(wide) {
+        bytes.copy_from_slice(&limb.to_le_bytes());
+    }
+    reduce(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(s: &str) -> Signature {
+        Signature(hex::decode(s).unwrap())
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(D * Fe::from_u64(121_666), -Fe::from_u64(121_665));
+        assert_eq!(D2, D + D);
+        assert_eq!(SQRT_M1.square(), -Fe::ONE);
+        assert_eq!(BASE.t, BASE.x * BASE.y);
+        assert_eq!(
+            Point::decode(&BASE.encode()).unwrap().encode(),
+            BASE.encode()
+        );
+        let order: [u8; 32] = std::array::from_fn(|i| (L[i / 8] >> (i % 8 * 8)) as u8);
+        assert_eq!(BASE.mul(&order).encode(), Point::IDENTITY.encode());
+        assert_eq!(reduce(&order), [0; 32]);
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        // RFC 8032 section 7.1, tests 1 and 2.
+        let key: SigningKey = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+            .parse()
+            .unwrap();
+        let public = key.verifying_key();
+        assert_eq!(
+            public.to_string(),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+        let expected = signature(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+             5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        );
+        assert_eq!(key.sign(b""), expected);
+        public.verify(b"", &expected).unwrap();
+
+        let key: SigningKey = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            key.verifying_key().to_string(),
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"
+        );
+        assert_eq!(
+            key.sign(&[0x72]),
+            signature(
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                 085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            )
+        );
+        assert_eq!(format!("{key:?}"), "SigningKey(..)");
+    }
+
+    #[test]
+    fn test_verify_rejects() {
+        let key = SigningKey::from_bytes([7; 32]);
+        let public = key.verifying_key();
+        let signature = key.sign(b"message");
+        public.verify(b"message", &signature).unwrap();
+        assert!(matches!(
+            public.verify(b"massage", &signature),
+            Err(Error::VerificationFailed)
+        ));
+        assert!(SigningKey::from_bytes([8; 32])
+            .verifying_key()
+            .verify(b"message", &signature)
+            .is_err());
+
+        let mut tampered = signature.0;
+        tampered[40] ^= 1;
+        assert!(public.verify(b"message", &Signature(tampered)).is_err());
+
+        // Adding L to s gives the same point but must be rejected.
+        let mut malleated = signature.0;
+        let mut carry = 0;
+        for (i, byte) in malleated[32..].iter_mut().enumerate() {
+            let sum = u16::from(*byte) + u16::from((L[i / 8] >> (i % 8 * 8)) as u8) + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert!(public.verify(b"message", &Signature(malleated)).is_err());
+    }
+}
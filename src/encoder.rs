@@ -0,0 +1,326 @@
+//! Building complete PNG files from pixel rows.
+
+use crate::{
+    adam7,
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    deflate::Compression,
+    filter::{self, FilterStrategy},
+    ihdr::{ColorType, Ihdr, Interlace},
+    image::{self, Image},
+    palette::{Palette, Transparency},
+    png::Png,
+    zlib, Error,
+};
+
+/// Encodes pixel rows into a [`Png`] with `IHDR`, optional `PLTE` and `tRNS`, `IDAT` and `IEND`
+/// chunks.
+///
+/// Rows use the [`Image`] layout for the configured format and are passed as one buffer.
+#[derive(Debug, Clone)]
+pub struct PngEncoder {
+    ihdr: Ihdr,
+    palette: Option<Vec<u8>>,
+    transparency: Option<Vec<u8>>,
+    compression: Compression,
+    filter_strategy: Option<FilterStrategy>,
+    chunk_size: usize,
+}
+
+impl PngEncoder {
+    /// Default maximum length of each `IDAT` chunk.
+    pub const DEFAULT_CHUNK_SIZE: usize = 8192;
+
+    /// Creates a non-interlaced encoder, checking dimensions and the bit depth / color type
+    /// combination.
+    pub fn new(
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+        bit_depth: u8,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            ihdr: Ihdr::new(width, height, bit_depth, color_type, Interlace::None)?,
+            palette: None,
+            transparency: None,
+            compression: Compression::default(),
+            filter_strategy: None,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        })
+    }
+    /// Sets the pixel order of the image data.
+    pub fn interlace(mut self, interlace: Interlace) -> Self {
+        let ihdr = &self.ihdr;
+        self.ihdr = Ihdr::new(
+            ihdr.width(),
+            ihdr.height(),
+            ihdr.bit_depth(),
+            ihdr.color_type(),
+            interlace,
+        )
+        .expect("only the interlace method changed");
+        self
+    }
+    /// Sets the `PLTE` data as RGB triples. Required for indexed images, optional for RGB.
+    pub fn palette(mut self, palette: impl Into<Vec<u8>>) -> Self {
+        self.palette = Some(palette.into());
+        self
+    }
+    /// Sets the `tRNS` data: alpha per palette entry, or the transparent sample values.
+    pub fn transparency(mut self, transparency: impl Into<Vec<u8>>) -> Self {
+        self.transparency = Some(transparency.into());
+        self
+    }
+    /// Sets the compression level of the image data.
+    pub const fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+    /// Sets how each scanline's filter is picked. Defaults to [`FilterStrategy::for_ihdr`].
+    pub const fn filter_strategy(mut self, strategy: FilterStrategy) -> Self {
+        self.filter_strategy = Some(strategy);
+        self
+    }
+    /// Sets the maximum length of each `IDAT` chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0 or larger than [`Chunk::MAX_LENGTH`].
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(
+            (1..=Chunk::MAX_LENGTH as usize).contains(&chunk_size),
+            "invalid IDAT chunk size {chunk_size}"
+        );
+        self.chunk_size = chunk_size;
+        self
+    }
+    /// The image header the encoder writes.
+    pub const fn ihdr(&self) -> &Ihdr {
+        &self.ihdr
+    }
+    /// Encodes `data`, the rows of the image from top to bottom.
+    pub fn encode(&self, data: &[u8]) -> Result<Png, Error> {
+        let palette = self.check_palette()?;
+        let ihdr = &self.ihdr;
+        let image = Image::new(
+            ihdr.width(),
+            ihdr.height(),
+            ihdr.color_type(),
+            ihdr.bit_depth(),
+            data.to_vec(),
+        )?;
+        if let (ColorType::Indexed, Some(palette)) = (ihdr.color_type(), &palette) {
+            check_indices(&image, palette.len())?;
+        }
+
+        let mut chunks = vec![ihdr.to_chunk()];
+        if let Some(palette) = &self.palette {
+            chunks.push(Chunk::new(ChunkType::PLTE, palette.as_slice()));
+        }
+        if let Some(transparency) = &self.transparency {
+            chunks.push(Chunk::new(ChunkType::TRNS, transparency.as_slice()));
+        }
+        let compressed = zlib::compress(&self.filter(&image), self.compression);
+        chunks.extend(
+            compressed
+                .chunks(self.chunk_size)
+                .map(|data| Chunk::new(ChunkType::IDAT, data)),
+        );
+        chunks.push(Chunk::new(ChunkType::IEND, []));
+        Ok(Png::from_chunks(chunks))
+    }
+
+    /// Filters every scanline, pass by pass for interlaced images.
+    fn filter(&self, image: &Image) -> Vec<u8> {
+        let strategy = self
+            .filter_strategy
+            .unwrap_or_else(|| FilterStrategy::for_ihdr(&self.ihdr));
+        let bpp = self.ihdr.bits_per_pixel().div_ceil(8);
+        let mut out = vec![];
+        let mut filter_rows = |data: &[u8], row_bytes: usize| {
+            filter::filter(strategy, data, row_bytes, bpp, self.compression, &mut out);
+        };
+        match self.ihdr.interlace() {
+            Interlace::None => filter_rows(image.data(), image.row_bytes()),
+            Interlace::Adam7 => {
+                let sizes = adam7::pass_sizes(image.width(), image.height());
+                for (pass, (w, _)) in adam7::interlace(image).iter().zip(sizes) {
+                    if !pass.is_empty() {
+                        filter_rows(pass, self.ihdr.row_bytes(w));
+                    }
+                }
+            }
+        }
+        out
+    }
+    /// Checks the palette and transparency against the header, returning the parsed palette.
+    fn check_palette(&self) -> Result<Option<Palette>, Error> {
+        let palette = self
+            .palette
+            .as_ref()
+            .map(|p| Palette::try_from(&Chunk::new(ChunkType::PLTE, p)))
+            .transpose()?;
+        match &palette {
+            Some(palette) => palette.check(&self.ihdr)?,
+            None if self.ihdr.color_type() == ColorType::Indexed => {
+                return Err(invalid("indexed images need a palette".to_owned()))
+            }
+            None => {}
+        }
+        if let Some(transparency) = &self.transparency {
+            let chunk = Chunk::new(ChunkType::TRNS, transparency);
+            Transparency::from_chunk(&chunk, self.ihdr.color_type())?
+                .check(&self.ihdr, palette.as_ref())?;
+        }
+        Ok(palette)
+    }
+}
+
+/// Checks that every pixel of an indexed image refers to one of the palette's `entries`.
+fn check_indices(image: &Image, entries: usize) -> Result<(), Error> {
+    let depth = usize::from(image.bit_depth());
+    for (y, row) in image.rows().enumerate() {
+        for x in 0..image.width() as usize {
+            let index = usize::from(image::sample(row, x, depth));
+            if index >= entries {
+                return Err(invalid(format!(
+                    "pixel ({x}, {y}) has palette index {index}, but the palette has {entries} \
+                     entries"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidImage(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::FilterType;
+
+    fn pixels(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        for (bit_depth, color_type) in [
+            (1, ColorType::Grayscale),
+            (4, ColorType::Grayscale),
+            (16, ColorType::Grayscale),
+            (8, ColorType::Rgb),
+            (16, ColorType::Rgb),
+            (8, ColorType::GrayscaleAlpha),
+            (16, ColorType::Rgba),
+        ] {
+            for interlace in [Interlace::None, Interlace::Adam7] {
+                let encoder = PngEncoder::new(8, 5, color_type, bit_depth)
+                    .unwrap()
+                    .interlace(interlace);
+                let data = pixels(encoder.ihdr().row_bytes(8) * 5);
+                let png = encoder.encode(&data).unwrap();
+                let png = Png::try_from(png.bytes().as_slice()).unwrap();
+                assert!(png.validate().is_empty());
+                assert_eq!(png.ihdr().unwrap(), *encoder.ihdr());
+                assert_eq!(png.decode_image().unwrap().data(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_filter_strategies() {
+        let data = (0..32 * 32)
+            .flat_map(|i: u32| [(i % 32 * 8) as u8, (i / 32 * 8) as u8, (i % 7 * 9) as u8])
+            .collect::<Vec<_>>();
+        let encoder = PngEncoder::new(32, 32, ColorType::Rgb, 8).unwrap();
+        let size = |strategy| {
+            let png = encoder
+                .clone()
+                .filter_strategy(strategy)
+                .encode(&data)
+                .unwrap();
+            assert_eq!(png.decode_image().unwrap().data(), data);
+            png.bytes().len()
+        };
+        let unfiltered = size(FilterStrategy::Fixed(FilterType::None));
+        assert!(size(FilterStrategy::MinSum) < unfiltered);
+        assert!(size(FilterStrategy::BruteForce) < unfiltered);
+        for filter in FilterType::ALL {
+            size(FilterStrategy::Fixed(filter));
+        }
+        // Without a strategy, 8-bit truecolor images keep the Paeth filter.
+        assert_eq!(
+            encoder.encode(&data).unwrap().bytes(),
+            encoder
+                .clone()
+                .filter_strategy(FilterStrategy::Fixed(FilterType::Paeth))
+                .encode(&data)
+                .unwrap()
+                .bytes()
+        );
+    }
+
+    #[test]
+    fn test_encode_indexed() {
+        let png = PngEncoder::new(4, 1, ColorType::Indexed, 2)
+            .unwrap()
+            .palette([1, 2, 3, 4, 5, 6])
+            .transparency([0])
+            .encode(&[0b00_01_01_00])
+            .unwrap();
+        assert!(png.validate().is_empty());
+        assert_eq!(
+            png.decode_image().unwrap().data(),
+            [1, 2, 3, 0, 4, 5, 6, 255, 4, 5, 6, 255, 1, 2, 3, 0]
+        );
+    }
+
+    #[test]
+    fn test_encode_splits_idat() {
+        let data = pixels(64 * 64 * 3);
+        let png = PngEncoder::new(64, 64, ColorType::Rgb, 8)
+            .unwrap()
+            .compression(Compression::None)
+            .chunk_size(1000)
+            .encode(&data)
+            .unwrap();
+        let idat = png
+            .chunks()
+            .iter()
+            .filter(|c| *c.r#type() == ChunkType::IDAT)
+            .collect::<Vec<_>>();
+        assert!(idat.len() > 1);
+        assert!(idat.iter().all(|c| c.length() <= 1000));
+        assert_eq!(png.chunks().last().unwrap().r#type(), &ChunkType::IEND);
+        assert_eq!(png.decode_image().unwrap().data(), data);
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let gray = PngEncoder::new(2, 2, ColorType::Grayscale, 8).unwrap();
+        assert!(matches!(gray.encode(&[0; 3]), Err(Error::InvalidImage(_))));
+        assert!(gray.clone().palette([0; 3]).encode(&[0; 4]).is_err());
+        assert!(gray.clone().transparency([0]).encode(&[0; 4]).is_err());
+        assert!(gray.transparency([0, 0]).encode(&[0; 4]).is_ok());
+
+        let indexed = PngEncoder::new(2, 2, ColorType::Indexed, 1).unwrap();
+        assert!(indexed.encode(&[0; 2]).is_err());
+        assert!(indexed.clone().palette([0; 9]).encode(&[0; 2]).is_err());
+        // Index 1 is set in the second row but the palette has one entry.
+        assert!(matches!(
+            indexed.clone().palette([0; 3]).encode(&[0, 0x40]),
+            Err(Error::InvalidImage(_))
+        ));
+        assert!(indexed.clone().palette([0; 3]).encode(&[0, 0]).is_ok());
+        assert!(indexed
+            .palette([0; 6])
+            .transparency([0; 3])
+            .encode(&[0; 2])
+            .is_err());
+        assert!(PngEncoder::new(0, 1, ColorType::Rgb, 8).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+//! Encrypted chunk payloads: a versioned header followed by ChaCha20-Poly1305 ciphertext.
+//!
+//! A payload starts with the magic bytes `PME`, a format version and a key scheme, followed by
+//! the scheme's fields and the nonce. Version 1 defines two schemes:
+//!
+//! - Password (1): the PBKDF2 iteration count and salt.
+//! - Recipients (2): an ephemeral X25519 public key, a recipient count and, per recipient, the
+//!   message key encrypted under a key derived with HKDF from the ephemeral-static shared secret.
+//!
+//! The chunk type and the whole header are authenticated along with the message, so moving the
+//! payload to another chunk type or editing the header fails decryption.
+
+#[cfg(unix)]
+use std::{fs::File, io::Read};
+
+use crate::{
+    chacha20poly1305::{self, KEY_LEN, NONCE_LEN, TAG_LEN},
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    kdf::{hkdf_sha256, pbkdf2_sha256},
+    x25519::{PublicKey, SecretKey},
+    Error,
+};
+
+/// The bytes every encrypted payload starts with.
+pub const MAGIC: [u8; 3] = *b"PME";
+/// The payload format version written by this crate.
+pub const VERSION: u8 = 1;
+/// PBKDF2 iterations used for new password-encrypted payloads, per current guidance for
+/// PBKDF2-HMAC-SHA-256. Payloads store their own count, so older ones still decrypt.
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+/// The largest iteration count accepted when decrypting, so a crafted payload cannot stall
+/// decoding.
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+const SALT_LEN: usize = 16;
+/// Size of the message key once encrypted for one recipient.
+const WRAPPED_KEY_LEN: usize = KEY_LEN + TAG_LEN;
+/// HKDF info string binding recipient keys to this format.
+const RECIPIENT_INFO: &[u8] = b"pngme recipient v1";
+
+/// How the payload key is derived.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Scheme {
+    /// PBKDF2-HMAC-SHA-256 over a password.
+    Password {
+        iterations: u32,
+        salt: [u8; SALT_LEN],
+    },
+    /// A random message key, encrypted for each of up to 255 X25519 public keys.
+    Recipients {
+        ephemeral: PublicKey,
+        wrapped_keys: Vec<[u8; WRAPPED_KEY_LEN]>,
+    },
+}
+
+impl Scheme {
+    const fn id(&self) -> u8 {
+        match self {
+            Self::Password { .. } => 1,
+            Self::Recipients { .. } => 2,
+        }
+    }
+}
+
+/// An encrypted message as stored in a chunk's data.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Envelope {
+    scheme: Scheme,
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl TryFrom<&[u8]> for Envelope {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let invalid = |reason: &str| Error::InvalidPayload(reason.to_owned());
+        let Some((&[m0, m1, m2, version, scheme], rest)) = bytes.split_first_chunk::<5>() else {
+            return Err(invalid("too short for a header"));
+        };
+        if [m0, m1, m2] != MAGIC {
+            return Err(invalid("not an encrypted payload"));
+        }
+        if version != VERSION {
+            return Err(Error::InvalidPayload(format!(
+                "unsupported version {version}"
+            )));
+        }
+        let (scheme, rest) = match scheme {
+            1 => {
+                let Some((iterations, rest)) = rest.split_first_chunk::<4>() else {
+                    return Err(invalid("too short for a password header"));
+                };
+                let Some((salt, rest)) = rest.split_first_chunk::<SALT_LEN>() else {
+                    return Err(invalid("too short for a password header"));
+                };
+                let iterations = u32::from_be_bytes(*iterations);
+                if !(1..=MAX_ITERATIONS).contains(&iterations) {
+                    return Err(Error::InvalidPayload(format!(
+                        "{iterations} key derivation iterations is out of range"
+                    )));
+                }
+                let scheme = Scheme::Password {
+                    iterations,
+                    salt: *salt,
+                };
+                (scheme, rest)
+            }
+            2 => {
+                let Some((ephemeral, rest)) = rest.split_first_chunk::<32>() else {
+                    return Err(invalid("too short for a recipients header"));
+                };
+                let Some((&count, mut rest)) = rest.split_first() else {
+                    return Err(invalid("too short for a recipients header"));
+                };
+                if count == 0 {
+                    return Err(invalid("no recipients"));
+                }
+                let mut wrapped_keys = Vec::with_capacity(count.into());
+                for _ in 0..count {
+                    let Some((wrapped, tail)) = rest.split_first_chunk::<WRAPPED_KEY_LEN>() else {
+                        return Err(invalid("too short for its recipients"));
+                    };
+                    wrapped_keys.push(*wrapped);
+                    rest = tail;
+                }
+                let scheme = Scheme::Recipients {
+                    ephemeral: PublicKey::from_bytes(*ephemeral),
+                    wrapped_keys,
+                };
+                (scheme, rest)
+            }
+            _ => {
+                return Err(Error::InvalidPayload(format!(
+                    "unknown key scheme {scheme}"
+                )))
+            }
+        };
+        match rest.split_first_chunk::<NONCE_LEN>() {
+            Some((nonce, ciphertext)) if ciphertext.len() >= TAG_LEN => Ok(Self {
+                scheme,
+                nonce: *nonce,
+                ciphertext: ciphertext.to_vec(),
+            }),
+            _ => Err(invalid("too short for a nonce and tag")),
+        }
+    }
+}
+
+impl Envelope {
+    /// Encrypts `message` for a chunk of type `chunk_type` under a key derived from `password`
+    /// with a fresh random salt and nonce.
+    pub fn seal_with_password(
+        chunk_type: ChunkType,
+        message: &[u8],
+        password: &str,
+    ) -> Result<Self, Error> {
+        Self::seal_password(chunk_type, message, password, DEFAULT_ITERATIONS)
+    }
+    /// Encrypts `message` for a chunk of type `chunk_type` so that the secret key of any one of
+    /// `recipients` can decrypt it. Accepts 1 to 255 recipients.
+    pub fn seal_to_recipients(
+        chunk_type: ChunkType,
+        message: &[u8],
+        recipients: &[PublicKey],
+    ) -> Result<Self, Error> {
+        if recipients.is_empty() || recipients.len() > 255 {
+            return Err(Error::InvalidKey(format!(
+                "expected 1 to 255 recipients, found {}",
+                recipients.len()
+            )));
+        }
+        let ephemeral_secret = SecretKey::generate()?;
+        let ephemeral = ephemeral_secret.public_key();
+        let key: [u8; KEY_LEN] = random_bytes()?;
+        let wrapped_keys = recipients
+            .iter()
+            .map(|recipient| {
+                let shared = ephemeral_secret.diffie_hellman(recipient)?;
+                let wrapping_key = recipient_key(&shared, &ephemeral, recipient);
+                let wrapped = chacha20poly1305::seal(&wrapping_key, &[0; NONCE_LEN], &[], &key);
+                Ok(wrapped.try_into().unwrap())
+            })
+            .collect::<Result<_, Error>>()?;
+        let mut envelope = Self {
+            scheme: Scheme::Recipients {
+                ephemeral,
+                wrapped_keys,
+            },
+            nonce: random_bytes()?,
+            ciphertext: vec![],
+        };
+        envelope.ciphertext =
+            chacha20poly1305::seal(&key, &envelope.nonce, &envelope.aad(chunk_type), message);
+        Ok(envelope)
+    }
+    /// Decrypts the message, failing with [`Error::DecryptionFailed`] if the password is wrong or
+    /// the payload, its header or `chunk_type` does not match what was sealed.
+    pub fn open_with_password(
+        &self,
+        chunk_type: ChunkType,
+        password: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let Scheme::Password { iterations, salt } = self.scheme else {
+            return Err(Error::InvalidPayload(
+                "sealed to recipients, not a password".to_owned(),
+            ));
+        };
+        let key = password_key(password, &salt, iterations);
+        chacha20poly1305::open(&key, &self.nonce, &self.aad(chunk_type), &self.ciphertext)
+    }
+    /// Decrypts the message with the secret key of one of its recipients, failing with
+    /// [`Error::DecryptionFailed`] if `identity` is not a recipient or the payload, its header or
+    /// `chunk_type` does not match what was sealed.
+    pub fn open_with_identity(
+        &self,
+        chunk_type: ChunkType,
+        identity: &SecretKey,
+    ) -> Result<Vec<u8>, Error> {
+        let Scheme::Recipients {
+            ephemeral,
+            wrapped_keys,
+        } = &self.scheme
+        else {
+            return Err(Error::InvalidPayload(
+                "sealed with a password, not to recipients".to_owned(),
+            ));
+        };
+        let shared = identity.diffie_hellman(ephemeral)?;
+        let wrapping_key = recipient_key(&shared, ephemeral, &identity.public_key());
+        // Recipients are not labeled, so try every wrapped key.
+        let key = wrapped_keys
+            .iter()
+            .find_map(|wrapped| {
+                chacha20poly1305::open(&wrapping_key, &[0; NONCE_LEN], &[], wrapped).ok()
+            })
+            .ok_or(Error::DecryptionFailed)?;
+        chacha20poly1305::open(
+            &key.try_into().unwrap(),
+            &self.nonce,
+            &self.aad(chunk_type),
+            &self.ciphertext,
+        )
+    }
+    /// Whether `data` parses as an encrypted payload: magic, version and scheme. Text that merely
+    /// starts with the magic bytes is not one. Authentication is only checked when opening.
+    pub fn is_envelope(data: &[u8]) -> bool {
+        Self::try_from(data).is_ok()
+    }
+    /// How the key is derived.
+    pub const fn scheme(&self) -> &Scheme {
+        &self.scheme
+    }
+    /// The nonce used for encryption.
+    pub const fn nonce(&self) -> &[u8; NONCE_LEN] {
+        &self.nonce
+    }
+    /// Serializes the header, nonce and ciphertext.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+    /// Creates a chunk of type `chunk_type` holding the payload. It must be the type the
+    /// message was sealed for.
+    pub fn to_chunk(&self, chunk_type: ChunkType) -> Chunk {
+        Chunk::new(chunk_type, self.to_bytes())
+    }
+
+    fn seal_password(
+        chunk_type: ChunkType,
+        message: &[u8],
+        password: &str,
+        iterations: u32,
+    ) -> Result<Self, Error> {
+        let salt = random_bytes()?;
+        let mut envelope = Self {
+            scheme: Scheme::Password { iterations, salt },
+            nonce: random_bytes()?,
+            ciphertext: vec![],
+        };
+        let key = password_key(password, &salt, iterations);
+        envelope.ciphertext =
+            chacha20poly1305::seal(&key, &envelope.nonce, &envelope.aad(chunk_type), message);
+        Ok(envelope)
+    }
+    /// Everything before the ciphertext.
+    fn header(&self) -> Vec<u8> {
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&[VERSION, self.scheme.id()]);
+        match &self.scheme {
+            Scheme::Password { iterations, salt } => {
+                header.extend_from_slice(&iterations.to_be_bytes());
+                header.extend_from_slice(salt);
+            }
+            Scheme::Recipients {
+                ephemeral,
+                wrapped_keys,
+            } => {
+                header.extend_from_slice(ephemeral.as_bytes());
+                header.push(wrapped_keys.len() as u8);
+                for wrapped in wrapped_keys {
+                    header.extend_from_slice(wrapped);
+                }
+            }
+        }
+        header.extend_from_slice(&self.nonce);
+        header
+    }
+    fn aad(&self, chunk_type: ChunkType) -> Vec<u8> {
+        let mut aad = chunk_type.as_bytes().to_vec();
+        aad.extend_from_slice(&self.header());
+        aad
+    }
+}
+
+fn password_key(password: &str, salt: &[u8], iterations: u32) -> [u8; KEY_LEN] {
+    let mut key = [0; KEY_LEN];
+    pbkdf2_sha256(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+/// Derives the key that encrypts the message key for `recipient`. Both public keys are mixed in
+/// so the wrapped key is bound to this exchange.
+fn recipient_key(shared: &[u8; 32], ephemeral: &PublicKey, recipient: &PublicKey) -> [u8; KEY_LEN] {
+    let mut salt = ephemeral.as_bytes().to_vec();
+    salt.extend_from_slice(recipient.as_bytes());
+    let mut key = [0; KEY_LEN];
+    hkdf_sha256(&salt, shared, RECIPIENT_INFO, &mut key);
+    key
+}
+
+/// Reads `N` bytes from the operating system's random number generator, `/dev/urandom`.
+#[cfg(unix)]
+pub(crate) fn random_bytes<const N: usize>() -> Result<[u8; N], Error> {
+    let mut bytes = [0; N];
+    File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Fails: the crate only reads randomness from `/dev/urandom`, which this platform lacks.
+#[cfg(not(unix))]
+pub(crate) fn random_bytes<const N: usize>() -> Result<[u8; N], Error> {
+    Err(Error::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "no random number generator is available on this platform",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_password_round_trip() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let sealed = Envelope::seal_password(chunk_type, b"hidden", "hunter2", 10).unwrap();
+        let bytes = sealed.to_bytes();
+        assert!(Envelope::is_envelope(&bytes));
+        assert!(!Envelope::is_envelope(&bytes[..MAGIC.len()]));
+        assert!(!Envelope::is_envelope(b"PMEmorandum: meet at noon"));
+        assert_eq!(bytes.len(), 5 + 4 + 16 + 12 + 6 + 16);
+        assert!(!bytes.windows(6).any(|w| w == b"hidden"));
+
+        let envelope = Envelope::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(envelope, sealed);
+        assert_eq!(
+            envelope.open_with_password(chunk_type, "hunter2").unwrap(),
+            b"hidden"
+        );
+        assert!(matches!(
+            envelope.open_with_password(chunk_type, "hunter3"),
+            Err(Error::DecryptionFailed)
+        ));
+        let other_type = ChunkType::from_str("ruSu").unwrap();
+        assert!(matches!(
+            envelope.open_with_password(other_type, "hunter2"),
+            Err(Error::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_recipients_round_trip() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let alice = SecretKey::generate().unwrap();
+        let bob = SecretKey::generate().unwrap();
+        let eve = SecretKey::generate().unwrap();
+        let bytes = Envelope::seal_to_recipients(
+            chunk_type,
+            b"hidden",
+            &[alice.public_key(), bob.public_key()],
+        )
+        .unwrap()
+        .to_bytes();
+        assert_eq!(bytes.len(), 5 + 32 + 1 + 2 * 48 + 12 + 6 + 16);
+
+        let envelope = Envelope::try_from(bytes.as_slice()).unwrap();
+        for identity in [&alice, &bob] {
+            assert_eq!(
+                envelope.open_with_identity(chunk_type, identity).unwrap(),
+                b"hidden"
+            );
+        }
+        assert!(matches!(
+            envelope.open_with_identity(chunk_type, &eve),
+            Err(Error::DecryptionFailed)
+        ));
+        assert!(matches!(
+            envelope.open_with_password(chunk_type, "hunter2"),
+            Err(Error::InvalidPayload(_))
+        ));
+
+        // Swapping the wrapped keys changes the authenticated header.
+        let mut swapped = bytes.clone();
+        swapped[38..86].copy_from_slice(&bytes[86..134]);
+        swapped[86..134].copy_from_slice(&bytes[38..86]);
+        let envelope = Envelope::try_from(swapped.as_slice()).unwrap();
+        assert!(matches!(
+            envelope.open_with_identity(chunk_type, &alice),
+            Err(Error::DecryptionFailed)
+        ));
+        assert!(Envelope::seal_to_recipients(chunk_type, b"hidden", &[]).is_err());
+    }
+
+    #[test]
+    fn test_tampered_payload() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let bytes = Envelope::seal_password(chunk_type, b"hidden", "hunter2", 10)
+            .unwrap()
+            .to_bytes();
+        // Flip one bit of the salt, the nonce and the ciphertext in turn.
+        for i in [10, 30, 40] {
+            let mut tampered = bytes.clone();
+            tampered[i] ^= 1;
+            let envelope = Envelope::try_from(tampered.as_slice()).unwrap();
+            assert!(matches!(
+                envelope.open_with_password(chunk_type, "hunter2"),
+                Err(Error::DecryptionFailed)
+            ));
+        }
+
+        let mut future = bytes.clone();
+        future[3] = 2;
+        assert!(matches!(
+            Envelope::try_from(future.as_slice()),
+            Err(Error::InvalidPayload(_))
+        ));
+        assert!(Envelope::try_from(&b"hidden"[..]).is_err());
+        assert!(Envelope::try_from(&bytes[..bytes.len() - 7]).is_err());
+    }
+}
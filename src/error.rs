@@ -0,0 +1,148 @@
+//! The crate error type.
+
+use std::fmt;
+
+use crate::{chunk_type::ChunkType, validate::Violation};
+
+/// Everything that can go wrong while parsing or editing a PNG.
+///
+/// Byte offsets are relative to the start of the parsed input: the whole file for
+/// [`Png`](crate::Png), the chunk itself for [`Chunk`](crate::Chunk).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input does not start with the PNG signature.
+    InvalidSignature,
+    /// The input ended early: `needed` bytes were required at `offset`.
+    Truncated { offset: usize, needed: usize },
+    /// A chunk declares a length above 2^31 - 1 or one that does not match its bytes.
+    InvalidLength { offset: usize, length: u32 },
+    /// A chunk's stored CRC does not match the CRC computed over its type and data.
+    CrcMismatch {
+        chunk_type: ChunkType,
+        offset: usize,
+        stored: u32,
+        computed: u32,
+    },
+    /// A chunk type is not made of four ASCII letters.
+    InvalidChunkType(Box<[u8]>),
+    /// A chunk's data does not follow the format defined for its type.
+    MalformedChunk {
+        chunk_type: ChunkType,
+        reason: String,
+    },
+    /// The PNG breaks the specification's chunk ordering or multiplicity rules.
+    Invalid(Vec<Violation>),
+    /// Compressed data is corrupt or decompresses beyond the allowed size.
+    Decompress(String),
+    /// No chunk with the given type exists.
+    ChunkNotFound(String),
+    /// No text chunk with the given keyword exists.
+    TextNotFound(String),
+    /// A text keyword or language tag breaks the specification's rules.
+    InvalidText(String),
+    /// Pixel data does not match the image dimensions or format.
+    InvalidImage(String),
+    /// A chunk placement name is not recognized.
+    InvalidPlacement(String),
+    /// A key is not in the expected format or cannot be used.
+    InvalidKey(String),
+    /// Chunk data is not an encrypted payload in a format this crate can read.
+    InvalidPayload(String),
+    /// An encrypted payload failed authentication: the password or key is wrong, or the payload
+    /// was modified.
+    DecryptionFailed,
+    /// A signature does not match the signed data and public key.
+    VerificationFailed,
+    /// A public chunk type was passed where only private chunk formats are accepted.
+    StandardChunk(ChunkType),
+    /// Chunk data is not valid UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// Reading or writing failed.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature => f.write_str("Invalid PNG signature"),
+            Self::Truncated { offset, needed } => {
+                write!(
+                    f,
+                    "Truncated input: needed {needed} bytes at offset {offset}"
+                )
+            }
+            Self::InvalidLength { offset, length } => {
+                write!(f, "Invalid chunk length {length} at offset {offset}")
+            }
+            Self::CrcMismatch {
+                chunk_type,
+                offset,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "Invalid CRC for chunk {chunk_type} at offset {offset}: \
+                 stored {stored:#010x}, computed {computed:#010x}"
+            ),
+            Self::InvalidChunkType(bytes) => {
+                write!(f, "Invalid chunk type: {}", bytes.escape_ascii())
+            }
+            Self::MalformedChunk { chunk_type, reason } => {
+                write!(f, "Malformed {chunk_type} chunk: {reason}")
+            }
+            Self::Invalid(violations) => {
+                f.write_str("Invalid PNG structure")?;
+                for v in violations {
+                    write!(f, "\n  {v}")?;
+                }
+                Ok(())
+            }
+            Self::Decompress(reason) => write!(f, "Decompression failed: {reason}"),
+            Self::ChunkNotFound(chunk_type) => write!(f, "No chunk with type {chunk_type}"),
+            Self::TextNotFound(keyword) => write!(f, "No text entry with keyword {keyword}"),
+            Self::InvalidText(reason) => write!(f, "Invalid text entry: {reason}"),
+            Self::InvalidImage(reason) => write!(f, "Invalid image: {reason}"),
+            Self::InvalidPlacement(s) => write!(
+                f,
+                "Invalid placement: {s} (expected after-ihdr, before-idat or before-iend)"
+            ),
+            Self::InvalidKey(reason) => write!(f, "Invalid key: {reason}"),
+            Self::InvalidPayload(reason) => write!(f, "Invalid encrypted payload: {reason}"),
+            Self::DecryptionFailed => {
+                f.write_str("Decryption failed: wrong password or key, or tampered data")
+            }
+            Self::VerificationFailed => {
+                f.write_str("Signature verification failed: wrong key, or tampered data")
+            }
+            Self::StandardChunk(chunk_type) => write!(
+                f,
+                "Chunk {chunk_type} is a public chunk type: only private chunk types are accepted"
+            ),
+            Self::Utf8(e) => write!(f, "Invalid UTF-8 in chunk data: {e}"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Utf8(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(e: std::str::Utf8Error) -> Self {
+        Self::Utf8(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
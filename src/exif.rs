@@ -0,0 +1,585 @@
+//! The `eXIf` chunk: EXIF metadata stored as a TIFF structure.
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, Error};
+
+const EXIF_IFD_POINTER: u16 = 0x8769;
+const GPS_IFD_POINTER: u16 = 0x8825;
+
+/// Names of common tags, by the directory they appear in.
+const TAG_NAMES: &[(Ifd, u16, &str)] = &[
+    (Ifd::Primary, 0x010E, "ImageDescription"),
+    (Ifd::Primary, 0x010F, "Make"),
+    (Ifd::Primary, 0x0110, "Model"),
+    (Ifd::Primary, 0x0112, "Orientation"),
+    (Ifd::Primary, 0x011A, "XResolution"),
+    (Ifd::Primary, 0x011B, "YResolution"),
+    (Ifd::Primary, 0x0128, "ResolutionUnit"),
+    (Ifd::Primary, 0x0131, "Software"),
+    (Ifd::Primary, 0x0132, "DateTime"),
+    (Ifd::Primary, 0x013B, "Artist"),
+    (Ifd::Primary, 0x0213, "YCbCrPositioning"),
+    (Ifd::Primary, 0x8298, "Copyright"),
+    (Ifd::Primary, EXIF_IFD_POINTER, "ExifIFDPointer"),
+    (Ifd::Primary, GPS_IFD_POINTER, "GPSInfoIFDPointer"),
+    (Ifd::Exif, 0x829A, "ExposureTime"),
+    (Ifd::Exif, 0x829D, "FNumber"),
+    (Ifd::Exif, 0x8822, "ExposureProgram"),
+    (Ifd::Exif, 0x8827, "PhotographicSensitivity"),
+    (Ifd::Exif, 0x9000, "ExifVersion"),
+    (Ifd::Exif, 0x9003, "DateTimeOriginal"),
+    (Ifd::Exif, 0x9004, "DateTimeDigitized"),
+    (Ifd::Exif, 0x9010, "OffsetTime"),
+    (Ifd::Exif, 0x9011, "OffsetTimeOriginal"),
+    (Ifd::Exif, 0x9201, "ShutterSpeedValue"),
+    (Ifd::Exif, 0x9202, "ApertureValue"),
+    (Ifd::Exif, 0x9204, "ExposureBiasValue"),
+    (Ifd::Exif, 0x9207, "MeteringMode"),
+    (Ifd::Exif, 0x9209, "Flash"),
+    (Ifd::Exif, 0x920A, "FocalLength"),
+    (Ifd::Exif, 0x927C, "MakerNote"),
+    (Ifd::Exif, 0x9286, "UserComment"),
+    (Ifd::Exif, 0xA001, "ColorSpace"),
+    (Ifd::Exif, 0xA002, "PixelXDimension"),
+    (Ifd::Exif, 0xA003, "PixelYDimension"),
+    (Ifd::Exif, 0xA405, "FocalLengthIn35mmFilm"),
+    (Ifd::Exif, 0xA430, "CameraOwnerName"),
+    (Ifd::Exif, 0xA431, "BodySerialNumber"),
+    (Ifd::Exif, 0xA433, "LensMake"),
+    (Ifd::Exif, 0xA434, "LensModel"),
+    (Ifd::Gps, 0x0000, "GPSVersionID"),
+    (Ifd::Gps, 0x0001, "GPSLatitudeRef"),
+    (Ifd::Gps, 0x0002, "GPSLatitude"),
+    (Ifd::Gps, 0x0003, "GPSLongitudeRef"),
+    (Ifd::Gps, 0x0004, "GPSLongitude"),
+    (Ifd::Gps, 0x0005, "GPSAltitudeRef"),
+    (Ifd::Gps, 0x0006, "GPSAltitude"),
+    (Ifd::Gps, 0x0007, "GPSTimeStamp"),
+    (Ifd::Gps, 0x0010, "GPSImgDirectionRef"),
+    (Ifd::Gps, 0x0011, "GPSImgDirection"),
+    (Ifd::Gps, 0x0012, "GPSMapDatum"),
+    (Ifd::Gps, 0x001D, "GPSDateStamp"),
+];
+
+/// Byte order of the TIFF structure.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ByteOrder {
+    /// `II`: Intel, least significant byte first.
+    LittleEndian,
+    /// `MM`: Motorola, most significant byte first.
+    BigEndian,
+}
+
+/// The image file directory a tag was read from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Ifd {
+    /// IFD0, describing the primary image.
+    Primary,
+    /// The EXIF private directory.
+    Exif,
+    /// The GPS directory.
+    Gps,
+}
+
+impl std::fmt::Display for Ifd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Primary => "IFD0",
+            Self::Exif => "Exif",
+            Self::Gps => "GPS",
+        })
+    }
+}
+
+/// A tag value, by TIFF field type.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Byte(Vec<u8>),
+    Ascii(String),
+    Short(Vec<u16>),
+    Long(Vec<u32>),
+    Rational(Vec<(u32, u32)>),
+    SByte(Vec<i8>),
+    Undefined(Vec<u8>),
+    SShort(Vec<i16>),
+    SLong(Vec<i32>),
+    SRational(Vec<(i32, i32)>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn list<T>(
+            f: &mut std::fmt::Formatter<'_>,
+            values: &[T],
+            fmt: impl Fn(&T) -> String,
+        ) -> std::fmt::Result {
+            f.write_str(&values.iter().map(fmt).collect::<Vec<_>>().join(", "))
+        }
+
+        match self {
+            Self::Byte(v) => list(f, v, u8::to_string),
+            Self::Ascii(s) => f.write_str(s),
+            Self::Short(v) => list(f, v, u16::to_string),
+            Self::Long(v) => list(f, v, u32::to_string),
+            Self::Rational(v) => list(f, v, |(n, d)| format!("{n}/{d}")),
+            Self::SByte(v) => list(f, v, i8::to_string),
+            Self::Undefined(v) if v.len() <= 16 => write!(f, "{}", v.escape_ascii()),
+            Self::Undefined(v) => write!(f, "{} bytes", v.len()),
+            Self::SShort(v) => list(f, v, i16::to_string),
+            Self::SLong(v) => list(f, v, i32::to_string),
+            Self::SRational(v) => list(f, v, |(n, d)| format!("{n}/{d}")),
+            Self::Float(v) => list(f, v, f32::to_string),
+            Self::Double(v) => list(f, v, f64::to_string),
+        }
+    }
+}
+
+/// One tag from an EXIF directory.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExifEntry {
+    ifd: Ifd,
+    tag: u16,
+    value: Value,
+}
+
+impl std::fmt::Display for ExifEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{} {name}: {}", self.ifd, self.value),
+            None => write!(f, "{} {:#06x}: {}", self.ifd, self.tag, self.value),
+        }
+    }
+}
+
+impl ExifEntry {
+    /// The directory the tag was read from.
+    pub const fn ifd(&self) -> Ifd {
+        self.ifd
+    }
+    /// The numeric tag.
+    pub const fn tag(&self) -> u16 {
+        self.tag
+    }
+    /// The tag's name, if it is a common one.
+    pub fn name(&self) -> Option<&'static str> {
+        TAG_NAMES
+            .iter()
+            .find(|(ifd, tag, _)| *ifd == self.ifd && *tag == self.tag)
+            .map(|(_, _, name)| *name)
+    }
+    /// The value.
+    pub const fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// Parsed EXIF metadata: the tags of IFD0 and of its EXIF and GPS sub-directories.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Exif {
+    data: Vec<u8>,
+    byte_order: ByteOrder,
+    entries: Vec<ExifEntry>,
+}
+
+impl TryFrom<&[u8]> for Exif {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let tiff = Tiff::new(data)?;
+        let mut entries = vec![];
+        let mut directories = vec![(Ifd::Primary, tiff.u32(4)?)];
+        let mut i = 0;
+        while let Some(&(ifd, offset)) = directories.get(i) {
+            if directories[..i].iter().any(|&(_, o)| o == offset) {
+                return Err(malformed(format!("directory loop at offset {offset}")));
+            }
+            i += 1;
+            for entry in tiff.directory(offset)? {
+                let Some(value) = tiff.value(&entry)? else {
+                    continue;
+                };
+                match (ifd, entry.tag, &value) {
+                    (Ifd::Primary, EXIF_IFD_POINTER, Value::Long(v)) if v.len() == 1 => {
+                        directories.push((Ifd::Exif, v[0]));
+                    }
+                    (Ifd::Primary, GPS_IFD_POINTER, Value::Long(v)) if v.len() == 1 => {
+                        directories.push((Ifd::Gps, v[0]));
+                    }
+                    _ => {}
+                }
+                entries.push(ExifEntry {
+                    ifd,
+                    tag: entry.tag,
+                    value,
+                });
+            }
+        }
+
+        Ok(Self {
+            data: data.to_vec(),
+            byte_order: tiff.order,
+            entries,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for Exif {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        if *chunk.r#type() != ChunkType::EXIF {
+            return Err(malformed(format!(
+                "expected eXIf, found {}",
+                chunk.r#type()
+            )));
+        }
+        Self::try_from(chunk.data())
+    }
+}
+
+impl Exif {
+    /// Byte order of the TIFF structure.
+    pub const fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+    /// Every tag, IFD0 first.
+    pub fn entries(&self) -> &[ExifEntry] {
+        &self.entries
+    }
+    /// The value of `tag` in `ifd`, if present.
+    pub fn get(&self, ifd: Ifd, tag: u16) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|e| e.ifd == ifd && e.tag == tag)
+            .map(|e| &e.value)
+    }
+    /// Whether any GPS tags are present.
+    pub fn has_gps(&self) -> bool {
+        self.entries.iter().any(|e| e.ifd == Ifd::Gps)
+    }
+    /// A copy with the GPS directory removed from IFD0 and its bytes zeroed. Everything else
+    /// keeps its offset, so other tags, including maker notes, stay intact.
+    ///
+    /// Fails if the GPS directory cannot be parsed, since its bytes could not all be found.
+    pub fn without_gps(&self) -> Result<Self, Error> {
+        let tiff = Tiff::new(&self.data)?;
+        let ifd0 = tiff.u32(4)?;
+        let directory = tiff.directory(ifd0)?;
+        let Some(index) = directory.iter().position(|e| e.tag == GPS_IFD_POINTER) else {
+            return Ok(self.clone());
+        };
+
+        let mut data = self.data.clone();
+        let gps = tiff.u32(directory[index].offset + 8)?;
+        let gps_directory = tiff
+            .directory(gps)
+            .map_err(|_| malformed(format!("cannot scrub unreadable GPS directory at {gps}")))?;
+        for entry in &gps_directory {
+            if let Some(range) = tiff.out_of_line(entry) {
+                data[range].fill(0);
+            }
+        }
+        let end = (gps as usize + 2 + 12 * gps_directory.len() + 4).min(data.len());
+        data[gps as usize..end].fill(0);
+
+        let ifd0 = ifd0 as usize;
+        let count = directory.len();
+        let entry = ifd0 + 2 + 12 * index;
+        let end = ifd0 + 2 + 12 * count + 4;
+        if end > data.len() {
+            return Err(malformed("IFD0 is truncated".to_owned()));
+        }
+        data.copy_within(entry + 12..end, entry);
+        data[end - 12..end].fill(0);
+        let count = match tiff.order {
+            ByteOrder::LittleEndian => ((count - 1) as u16).to_le_bytes(),
+            ByteOrder::BigEndian => ((count - 1) as u16).to_be_bytes(),
+        };
+        data[ifd0..ifd0 + 2].copy_from_slice(&count);
+        Self::try_from(data.as_slice())
+    }
+    /// The raw TIFF bytes.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Serializes the metadata as an `eXIf` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(ChunkType::EXIF, &self.data)
+    }
+}
+
+/// A raw directory entry.
+struct RawEntry {
+    /// Offset of the entry itself.
+    offset: u32,
+    tag: u16,
+    field_type: u16,
+    count: u32,
+}
+
+/// Bounds-checked reads from a TIFF structure.
+struct Tiff<'a> {
+    data: &'a [u8],
+    order: ByteOrder,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Result<Self, Error> {
+        let order = match data.get(..4) {
+            Some(b"II*\0") => ByteOrder::LittleEndian,
+            Some(b"MM\0*") => ByteOrder::BigEndian,
+            _ => return Err(malformed("invalid TIFF header".to_owned())),
+        };
+        Ok(Self { data, order })
+    }
+    fn bytes<const N: usize>(&self, offset: u32) -> Result<[u8; N], Error> {
+        self.data
+            .get(offset as usize..)
+            .and_then(|rest| rest.get(..N))
+            .map(|b| b.try_into().expect("slice has N bytes"))
+            .ok_or_else(|| malformed(format!("offset {offset} is out of bounds")))
+    }
+    fn u16(&self, offset: u32) -> Result<u16, Error> {
+        let b = self.bytes(offset)?;
+        Ok(match self.order {
+            ByteOrder::LittleEndian => u16::from_le_bytes(b),
+            ByteOrder::BigEndian => u16::from_be_bytes(b),
+        })
+    }
+    fn u32(&self, offset: u32) -> Result<u32, Error> {
+        let b = self.bytes(offset)?;
+        Ok(match self.order {
+            ByteOrder::LittleEndian => u32::from_le_bytes(b),
+            ByteOrder::BigEndian => u32::from_be_bytes(b),
+        })
+    }
+    fn u64(&self, offset: u32) -> Result<u64, Error> {
+        let b = self.bytes(offset)?;
+        Ok(match self.order {
+            ByteOrder::LittleEndian => u64::from_le_bytes(b),
+            ByteOrder::BigEndian => u64::from_be_bytes(b),
+        })
+    }
+    fn directory(&self, offset: u32) -> Result<Vec<RawEntry>, Error> {
+        let count = self.u16(offset)? as u32;
+        (0..count)
+            .map(|i| {
+                let offset = offset
+                    .checked_add(2 + 12 * i)
+                    .ok_or_else(|| malformed(format!("offset {offset} is out of bounds")))?;
+                Ok(RawEntry {
+                    offset,
+                    tag: self.u16(offset)?,
+                    field_type: self.u16(offset + 2)?,
+                    count: self.u32(offset + 4)?,
+                })
+            })
+            .collect()
+    }
+    /// The byte range of an entry's value when it does not fit in the entry itself, cut short at
+    /// the end of the data.
+    fn out_of_line(&self, entry: &RawEntry) -> Option<std::ops::Range<usize>> {
+        let len = type_size(entry.field_type)? as usize * entry.count as usize;
+        if len <= 4 {
+            return None;
+        }
+        let start = (self.u32(entry.offset + 8).ok()? as usize).min(self.data.len());
+        let end = start.saturating_add(len).min(self.data.len());
+        Some(start..end)
+    }
+    /// Reads an entry's value, or `None` for field types this parser does not know.
+    fn value(&self, entry: &RawEntry) -> Result<Option<Value>, Error> {
+        let Some(size) = type_size(entry.field_type) else {
+            return Ok(None);
+        };
+        let len = size as u64 * entry.count as u64;
+        let start = match len {
+            0..=4 => entry.offset + 8,
+            _ => self.u32(entry.offset + 8)?,
+        };
+        if start as u64 + len > self.data.len() as u64 {
+            return Err(malformed(format!(
+                "value of tag {:#06x} is out of bounds",
+                entry.tag
+            )));
+        }
+        let at = |i: u32| start + i * size;
+        let n = 0..entry.count;
+        let bytes = &self.data[start as usize..(start as u64 + len) as usize];
+
+        Ok(Some(match entry.field_type {
+            1 => Value::Byte(bytes.to_vec()),
+            2 => Value::Ascii(
+                String::from_utf8_lossy(bytes)
+                    .trim_end_matches('\0')
+                    .to_owned(),
+            ),
+            3 => Value::Short(n.map(|i| self.u16(at(i))).collect::<Result<_, _>>()?),
+            4 => Value::Long(n.map(|i| self.u32(at(i))).collect::<Result<_, _>>()?),
+            5 => Value::Rational(
+                n.map(|i| Ok((self.u32(at(i))?, self.u32(at(i) + 4)?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            6 => Value::SByte(bytes.iter().map(|&b| b as i8).collect()),
+            7 => Value::Undefined(bytes.to_vec()),
+            8 => Value::SShort(
+                n.map(|i| Ok(self.u16(at(i))? as i16))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            9 => Value::SLong(
+                n.map(|i| Ok(self.u32(at(i))? as i32))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            10 => Value::SRational(
+                n.map(|i| Ok((self.u32(at(i))? as i32, self.u32(at(i) + 4)? as i32)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            11 => Value::Float(
+                n.map(|i| Ok(f32::from_bits(self.u32(at(i))?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            _ => Value::Double(
+                n.map(|i| Ok(f64::from_bits(self.u64(at(i))?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+        }))
+    }
+}
+
+/// Size in bytes of one value of a TIFF field type.
+const fn type_size(field_type: u16) -> Option<u32> {
+    match field_type {
+        1 | 2 | 6 | 7 => Some(1),
+        3 | 8 => Some(2),
+        4 | 9 | 11 => Some(4),
+        5 | 10 | 12 => Some(8),
+        _ => None,
+    }
+}
+
+fn malformed(reason: String) -> Error {
+    Error::MalformedChunk {
+        chunk_type: ChunkType::EXIF,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a TIFF structure with IFD0 (Make, Orientation, Exif and GPS pointers), an Exif IFD
+    /// (ExposureTime) and a GPS IFD (GPSLatitudeRef, GPSLatitude).
+    fn tiff(order: ByteOrder) -> Vec<u8> {
+        let u16 = |v: u16| match order {
+            ByteOrder::LittleEndian => v.to_le_bytes().to_vec(),
+            ByteOrder::BigEndian => v.to_be_bytes().to_vec(),
+        };
+        let u32 = |v: u32| match order {
+            ByteOrder::LittleEndian => v.to_le_bytes().to_vec(),
+            ByteOrder::BigEndian => v.to_be_bytes().to_vec(),
+        };
+        let entry = |tag, field_type, count, value: Vec<u8>| {
+            [u16(tag), u16(field_type), u32(count), value].concat()
+        };
+
+        let mut data = match order {
+            ByteOrder::LittleEndian => b"II*\0".to_vec(),
+            ByteOrder::BigEndian => b"MM\0*".to_vec(),
+        };
+        // IFD0 at 8: 4 entries, ending at 8 + 2 + 48 + 4 = 62.
+        data.extend(u32(8));
+        data.extend(u16(4));
+        data.extend(entry(0x010F, 2, 6, u32(62)));
+        data.extend(entry(0x0112, 3, 1, [u16(6), u16(0)].concat()));
+        data.extend(entry(EXIF_IFD_POINTER, 4, 1, u32(68)));
+        data.extend(entry(GPS_IFD_POINTER, 4, 1, u32(94)));
+        data.extend(u32(0));
+        // Make at 62.
+        data.extend(b"Canon\0");
+        // Exif IFD at 68: 1 entry, ending at 86; ExposureTime at 86.
+        data.extend(u16(1));
+        data.extend(entry(0x829A, 5, 1, u32(86)));
+        data.extend(u32(0));
+        data.extend([u32(1), u32(250)].concat());
+        // GPS IFD at 94: 2 entries, ending at 124; GPSLatitude at 124.
+        data.extend(u16(2));
+        data.extend(entry(0x0001, 2, 2, b"N\0\0\0".to_vec()));
+        data.extend(entry(0x0002, 5, 3, u32(124)));
+        data.extend(u32(0));
+        for v in [52, 1, 31, 1, 0, 1] {
+            data.extend(u32(v));
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_exif() {
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let exif = Exif::try_from(tiff(order).as_slice()).unwrap();
+            assert_eq!(exif.byte_order(), order);
+            assert_eq!(exif.entries().len(), 7);
+            assert_eq!(
+                exif.get(Ifd::Primary, 0x010F),
+                Some(&Value::Ascii("Canon".to_owned()))
+            );
+            assert_eq!(exif.get(Ifd::Primary, 0x0112), Some(&Value::Short(vec![6])));
+            assert_eq!(
+                exif.get(Ifd::Exif, 0x829A),
+                Some(&Value::Rational(vec![(1, 250)]))
+            );
+            assert_eq!(
+                exif.get(Ifd::Gps, 0x0002),
+                Some(&Value::Rational(vec![(52, 1), (31, 1), (0, 1)]))
+            );
+            assert!(exif.has_gps());
+        }
+    }
+
+    #[test]
+    fn test_entry_display() {
+        let exif = Exif::try_from(tiff(ByteOrder::BigEndian).as_slice()).unwrap();
+        let lines = exif
+            .entries()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert!(lines.contains(&"IFD0 Make: Canon".to_owned()));
+        assert!(lines.contains(&"Exif ExposureTime: 1/250".to_owned()));
+        assert!(lines.contains(&"GPS GPSLatitude: 52/1, 31/1, 0/1".to_owned()));
+    }
+
+    #[test]
+    fn test_without_gps() {
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let exif = Exif::try_from(tiff(order).as_slice()).unwrap();
+            let scrubbed = exif.without_gps().unwrap();
+            assert!(!scrubbed.has_gps());
+            assert_eq!(scrubbed.data().len(), exif.data().len());
+            assert!(scrubbed.get(Ifd::Primary, GPS_IFD_POINTER).is_none());
+            assert_eq!(scrubbed.entries().len(), 4);
+            assert_eq!(scrubbed.get(Ifd::Exif, 0x829A), exif.get(Ifd::Exif, 0x829A));
+            assert!(scrubbed.data()[94..].iter().all(|&b| b == 0));
+            assert_eq!(scrubbed.without_gps().unwrap(), scrubbed);
+        }
+
+        // A GPS pointer of type IFD is not followed when parsing, and this GPS directory claims
+        // more entries than fit: scrubbing must fail rather than leave the coordinates behind.
+        let mut data = tiff(ByteOrder::LittleEndian);
+        data[48..50].copy_from_slice(&13_u16.to_le_bytes());
+        data[94..96].copy_from_slice(&u16::MAX.to_le_bytes());
+        let exif = Exif::try_from(data.as_slice()).unwrap();
+        assert!(exif.without_gps().is_err());
+    }
+
+    #[test]
+    fn test_malformed_exif() {
+        let data = tiff(ByteOrder::LittleEndian);
+        assert!(Exif::try_from(&data[..60]).is_err());
+        assert!(Exif::try_from(&b"XX*\0\x08\0\0\0"[..]).is_err());
+        let mut looped = data.clone();
+        looped[42..46].copy_from_slice(&8_u32.to_le_bytes());
+        assert!(Exif::try_from(looped.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Arithmetic in the field of integers modulo 2^255 - 19, shared by X25519 and Ed25519.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+const MASK: u64 = (1 << 51) - 1;
+
+/// A field element as five 51-bit limbs, least significant first. Limbs may exceed 51 bits
+/// between operations; [`Fe::to_bytes`] fully reduces.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fe([u64; 5]);
+
+impl Fe {
+    pub(crate) const ZERO: Self = Self([0; 5]);
+    pub(crate) const ONE: Self = Self([1, 0, 0, 0, 0]);
+
+    pub(crate) const fn from_u64(value: u64) -> Self {
+        Self([value & MASK, value >> 51, 0, 0, 0])
+    }
+    /// Wraps five 51-bit limbs, least significant first, for writing constants.
+    pub(crate) const fn from_limbs(limbs: [u64; 5]) -> Self {
+        Self(limbs)
+    }
+    /// Decodes a little-endian element, ignoring the top bit.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let word = |i: usize| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        let [w0, w1, w2, w3] = [word(0), word(1), word(2), word(3)];
+        Self([
+            w0 & MASK,
+            (w0 >> 51 | w1 << 13) & MASK,
+            (w1 >> 38 | w2 << 26) & MASK,
+            (w2 >> 25 | w3 << 39) & MASK,
+            w3 >> 12 & MASK,
+        ])
+    }
+    /// Encodes the fully reduced element in little-endian order.
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut l = self.carry().carry().0;
+        // Add 19 and see whether it carries past 2^255, which happens exactly when l >= p.
+        let mut q = (l[0] + 19) >> 51;
+        for limb in &l[1..] {
+            q = (limb + q) >> 51;
+        }
+        l[0] += 19 * q;
+        for i in 0..4 {
+            l[i + 1] += l[i] >> 51;
+            l[i] &= MASK;
+        }
+        l[4] &= MASK;
+
+        let words = [
+            l[0] | l[1] << 51,
+            l[1] >> 13 | l[2] << 38,
+            l[2] >> 26 | l[3] << 25,
+            l[3] >> 39 | l[4] << 12,
+        ];
+        let mut out = [0; 32];
+        for (bytes, word) in out.chunks_exact_mut(8).zip(words) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+    /// Whether the fully reduced element is odd, which Ed25519 treats as negative.
+    pub(crate) fn is_negative(self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+    pub(crate) fn square(self) -> Self {
+        self * self
+    }
+    /// Raises the element to a little-endian exponent. The exponent is not secret, so this
+    /// need not run in constant time with respect to it.
+    pub(crate) fn pow(self, exponent: &[u8; 32]) -> Self {
+        let mut result = Self::ONE;
+        for bit in (0..256).rev() {
+            result = result.square();
+            if exponent[bit / 8] >> (bit % 8) & 1 == 1 {
+                result = result * self;
+            }
+        }
+        result
+    }
+    /// The multiplicative inverse, or zero for zero.
+    pub(crate) fn invert(self) -> Self {
+        // x^(p - 2) = x^-1 by Fermat's little theorem.
+        let mut exponent = [0xff; 32];
+        exponent[0] = 0xeb;
+        exponent[31] = 0x7f;
+        self.pow(&exponent)
+    }
+    /// Swaps `a` and `b` if `swap` is 1, without branching on it.
+    pub(crate) fn swap(a: &mut Self, b: &mut Self, swap: u64) {
+        let mask = swap.wrapping_neg();
+        for (a, b) in a.0.iter_mut().zip(&mut b.0) {
+            let t = mask & (*a ^ *b);
+            *a ^= t;
+            *b ^= t;
+        }
+    }
+
+    /// Propagates carries so every limb fits in 51 bits plus a small excess in the lowest.
+    fn carry(self) -> Self {
+        let mut l = self.0;
+        for i in 0..4 {
+            l[i + 1] += l[i] >> 51;
+            l[i] &= MASK;
+        }
+        l[0] += 19 * (l[4] >> 51);
+        l[4] &= MASK;
+        Self(l)
+    }
+}
+
+impl PartialEq for Fe {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for Fe {}
+
+impl Add for Fe {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] + rhs.0[i])).carry()
+    }
+}
+
+impl Sub for Fe {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        // Add 4p first so no limb underflows.
+        const FOUR_P: [u64; 5] = [
+            0x1f_ffff_ffff_ffb4,
+            0x1f_ffff_ffff_fffc,
+            0x1f_ffff_ffff_fffc,
+            0x1f_ffff_ffff_fffc,
+            0x1f_ffff_ffff_fffc,
+        ];
+        let rhs = rhs.carry();
+        Self(std::array::from_fn(|i| self.0[i] + FOUR_P[i] - rhs.0[i])).carry()
+    }
+}
+
+impl Neg for Fe {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl Mul for Fe {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1, a2, a3, a4] = self.0.map(u128::from);
+        let [b0, b1, b2, b3, b4] = rhs.0.map(u128::from);
+        // Terms past 2^255 wrap around multiplied by 19.
+        let r = [
+            a0 * b0 + 19 * (a1 * b4 + a2 * b3 + a3 * b2 + a4 * b1),
+            a0 * b1 + a1 * b0 + 19 * (a2 * b4 + a3 * b3 + a4 * b2),
+            a0 * b2 + a1 * b1 + a2 * b0 + 19 * (a3 * b4 + a4 * b3),
+            a0 * b3 + a1 * b2 + a2 * b1 + a3 * b0 + 19 * (a4 * b4),
+            a0 * b4 + a1 * b3 + a2 * b2 + a3 * b1 + a4 * b0,
+        ];
+        let mut out = [0; 5];
+        let mut carry = 0;
+        for (out, r) in out.iter_mut().zip(r) {
+            let r = r + carry;
+            *out = (r as u64) & MASK;
+            carry = r >> 51;
+        }
+        out[0] += 19 * carry as u64;
+        Self(out).carry()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_arithmetic() {
+        let p_minus_one = {
+            let mut bytes = [0xff; 32];
+            bytes[0] = 0xec;
+            bytes[31] = 0x7f;
+            Fe::from_bytes(&bytes)
+        };
+        assert_eq!(p_minus_one + Fe::ONE, Fe::ZERO);
+        assert_eq!(-Fe::ONE, p_minus_one);
+        assert_eq!(p_minus_one * p_minus_one, Fe::ONE);
+        assert_eq!(Fe::ONE - Fe::ONE, Fe::ZERO);
+
+        let x = Fe::from_u64(123_456_789) * Fe::from_u64(1 << 60);
+        assert_eq!(x * x.invert(), Fe::ONE);
+        assert_eq!(Fe::from_bytes(&x.to_bytes()), x);
+
+        let (mut a, mut b) = (Fe::ONE, Fe::ZERO);
+        Fe::swap(&mut a, &mut b, 0);
+        assert_eq!((a, b), (Fe::ONE, Fe::ZERO));
+        Fe::swap(&mut a, &mut b, 1);
+        assert_eq!((a, b), (Fe::ZERO, Fe::ONE));
+    }
+}
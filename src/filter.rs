@@ -0,0 +1,321 @@
+//! Scanline filters applied before compression.
+
+use crate::{
+    chunk_type::ChunkType,
+    deflate::{self, Compression},
+    ihdr::{ColorType, Ihdr},
+    Error,
+};
+
+/// The per-scanline filter type byte.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FilterType {
+    None = 0,
+    Sub = 1,
+    Up = 2,
+    Average = 3,
+    Paeth = 4,
+}
+
+impl TryFrom<u8> for FilterType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Sub),
+            2 => Ok(Self::Up),
+            3 => Ok(Self::Average),
+            4 => Ok(Self::Paeth),
+            _ => Err(Error::MalformedChunk {
+                chunk_type: ChunkType::IDAT,
+                reason: format!("invalid filter type {value}"),
+            }),
+        }
+    }
+}
+
+impl FilterType {
+    /// Every filter type, in type byte order.
+    pub const ALL: [Self; 5] = [Self::None, Self::Sub, Self::Up, Self::Average, Self::Paeth];
+}
+
+/// How an encoder picks the filter of each scanline.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FilterStrategy {
+    /// The same filter for every scanline.
+    Fixed(FilterType),
+    /// The filter whose output, read as signed bytes, has the smallest sum of absolute values.
+    MinSum,
+    /// The filter whose output deflates smallest after the preceding filtered data, as it would in
+    /// the final stream. Slow: every scanline is compressed five times with up to 32 KiB of
+    /// history.
+    BruteForce,
+}
+
+impl FilterStrategy {
+    /// The encoder's default: no filtering for indexed images and those below 8 bits,
+    /// [`FilterType::Paeth`] otherwise.
+    pub const fn for_ihdr(ihdr: &Ihdr) -> Self {
+        if matches!(ihdr.color_type(), ColorType::Indexed) || ihdr.bit_depth() < 8 {
+            Self::Fixed(FilterType::None)
+        } else {
+            Self::Fixed(FilterType::Paeth)
+        }
+    }
+}
+
+/// The deflate window: how far back the compressor can refer to earlier data.
+const WINDOW: usize = 32 * 1024;
+
+/// Filters scanlines of `row_bytes` bytes with `strategy`, appending each with its filter type.
+/// `level` is the compression [`FilterStrategy::BruteForce`] measures with, and the tail of `out`
+/// the history it measures after.
+pub(crate) fn filter(
+    strategy: FilterStrategy,
+    data: &[u8],
+    row_bytes: usize,
+    bpp: usize,
+    level: Compression,
+    out: &mut Vec<u8>,
+) {
+    let mut prev = vec![0; row_bytes];
+    let mut candidate = Vec::with_capacity(row_bytes + 1);
+    for row in data.chunks_exact(row_bytes) {
+        let filter = match strategy {
+            FilterStrategy::Fixed(filter) => filter,
+            FilterStrategy::MinSum => best(row, &prev, bpp, &[], &mut candidate, |filtered| {
+                filtered[1..]
+                    .iter()
+                    .map(|&b| (b as i8).unsigned_abs() as usize)
+                    .sum()
+            }),
+            FilterStrategy::BruteForce => {
+                // The history is the same for every candidate, so comparing the compressed size
+                // of history and row compares what the row adds to the stream.
+                let history = &out[out.len().saturating_sub(WINDOW)..];
+                best(row, &prev, bpp, history, &mut candidate, |data| {
+                    deflate::deflate(data, level).len()
+                })
+            }
+        };
+        filter_row(filter, row, &prev, bpp, out);
+        prev.copy_from_slice(row);
+    }
+}
+
+/// The filter type whose output, after `history`, has the lowest `cost`, preferring earlier types
+/// on ties.
+fn best(
+    row: &[u8],
+    prev: &[u8],
+    bpp: usize,
+    history: &[u8],
+    candidate: &mut Vec<u8>,
+    cost: impl Fn(&[u8]) -> usize,
+) -> FilterType {
+    FilterType::ALL
+        .into_iter()
+        .min_by_key(|&filter| {
+            candidate.clear();
+            candidate.extend_from_slice(history);
+            filter_row(filter, row, prev, bpp, candidate);
+            cost(candidate)
+        })
+        .expect("there are five filter types")
+}
+
+/// Reverses the filters of `rows` scanlines of `row_bytes` bytes, each prefixed with its filter
+/// type, returning the unfiltered scanlines without the prefix. `bpp` is the number of bytes per
+/// complete pixel, rounded up to 1.
+pub(crate) fn unfilter(
+    data: &[u8],
+    row_bytes: usize,
+    rows: usize,
+    bpp: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut out = vec![0; row_bytes * rows];
+    let mut prev = vec![0; row_bytes];
+    for (y, filtered) in data.chunks_exact(row_bytes + 1).take(rows).enumerate() {
+        let row = &mut out[y * row_bytes..(y + 1) * row_bytes];
+        row.copy_from_slice(&filtered[1..]);
+        unfilter_row(FilterType::try_from(filtered[0])?, row, &prev, bpp);
+        prev.copy_from_slice(row);
+    }
+    Ok(out)
+}
+
+/// Appends `filter` followed by `row` filtered against the previous unfiltered row `prev`.
+pub(crate) fn filter_row(
+    filter: FilterType,
+    row: &[u8],
+    prev: &[u8],
+    bpp: usize,
+    out: &mut Vec<u8>,
+) {
+    out.push(filter as u8);
+    let left = |i: usize| if i >= bpp { row[i - bpp] } else { 0 };
+    let upper_left = |i: usize| if i >= bpp { prev[i - bpp] } else { 0 };
+    out.extend(row.iter().enumerate().map(|(i, &x)| {
+        x.wrapping_sub(match filter {
+            FilterType::None => 0,
+            FilterType::Sub => left(i),
+            FilterType::Up => prev[i],
+            FilterType::Average => ((left(i) as u16 + prev[i] as u16) / 2) as u8,
+            FilterType::Paeth => paeth(left(i), prev[i], upper_left(i)),
+        })
+    }));
+}
+
+fn unfilter_row(filter: FilterType, row: &mut [u8], prev: &[u8], bpp: usize) {
+    match filter {
+        FilterType::None => {}
+        FilterType::Sub => {
+            for i in bpp..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bpp]);
+            }
+        }
+        FilterType::Up => {
+            for (x, &b) in row.iter_mut().zip(prev) {
+                *x = x.wrapping_add(b);
+            }
+        }
+        FilterType::Average => {
+            for i in 0..row.len() {
+                let a = if i >= bpp { row[i - bpp] } else { 0 };
+                row[i] = row[i].wrapping_add(((a as u16 + prev[i] as u16) / 2) as u8);
+            }
+        }
+        FilterType::Paeth => {
+            for i in 0..row.len() {
+                let (a, c) = if i >= bpp {
+                    (row[i - bpp], prev[i - bpp])
+                } else {
+                    (0, 0)
+                };
+                row[i] = row[i].wrapping_add(paeth(a, prev[i], c));
+            }
+        }
+    }
+}
+
+/// The Paeth predictor: whichever of left, above and upper-left is closest to `a + b - c`.
+pub(crate) fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paeth() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 20), 10);
+        assert_eq!(paeth(0, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_unfilter() {
+        #[rustfmt::skip]
+        let data = [
+            1, 1, 2, 1, 2,     // Sub
+            2, 1, 1, 1, 1,     // Up
+            3, 0, 0, 1, 1,     // Average
+            4, 1, 0, 0, 0,     // Paeth
+            0, 9, 9, 9, 9,     // None
+        ];
+        #[rustfmt::skip]
+        let expected = [
+            1, 2, 2, 4,
+            2, 3, 3, 5,
+            1, 1, 3, 4,
+            2, 1, 3, 4,
+            9, 9, 9, 9,
+        ];
+        assert_eq!(unfilter(&data, 4, 5, 2).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_filter_round_trip() {
+        let rows = (0..60).map(|i| (i * i * 7 % 253) as u8).collect::<Vec<_>>();
+        for filter in FilterType::ALL {
+            let mut filtered = vec![];
+            let mut prev = [0; 12].as_slice();
+            for row in rows.chunks_exact(12) {
+                filter_row(filter, row, prev, 3, &mut filtered);
+                prev = row;
+            }
+            assert_eq!(unfilter(&filtered, 12, 5, 3).unwrap(), rows);
+        }
+    }
+
+    #[test]
+    fn test_filter_strategies() {
+        let gradient = (0..64).map(|i| i * 3).collect::<Vec<u8>>();
+        for (strategy, expected) in [
+            (FilterStrategy::Fixed(FilterType::Up), FilterType::Up),
+            (FilterStrategy::MinSum, FilterType::Sub),
+            (FilterStrategy::BruteForce, FilterType::Sub),
+        ] {
+            let mut filtered = vec![];
+            filter(
+                strategy,
+                &gradient,
+                64,
+                1,
+                Compression::Default,
+                &mut filtered,
+            );
+            assert_eq!(filtered[0], expected as u8);
+            assert_eq!(unfilter(&filtered, 64, 1, 1).unwrap(), gradient);
+        }
+    }
+
+    #[test]
+    fn test_brute_force_uses_history() {
+        // Alone, every filter of the last row looks like noise and `None` wins the tie, but `Up`
+        // repeats the first row exactly, which deflate encodes as a back-reference.
+        let noise = |seed: u32| {
+            let mut state = seed;
+            (0..256)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (state >> 16) as u8
+                })
+                .collect::<Vec<u8>>()
+        };
+        let (first, second) = (noise(1), noise(2));
+        let third = first.iter().zip(&second).map(|(a, b)| a.wrapping_add(*b));
+        let data = [first.clone(), second.clone(), third.collect()].concat();
+        let mut filtered = vec![];
+        filter(
+            FilterStrategy::BruteForce,
+            &data,
+            256,
+            1,
+            Compression::Default,
+            &mut filtered,
+        );
+        assert_eq!(filtered[2 * 257], FilterType::Up as u8);
+        assert_eq!(unfilter(&filtered, 256, 3, 1).unwrap(), data);
+    }
+
+    #[test]
+    fn test_invalid_filter_type() {
+        assert!(unfilter(&[5, 0], 1, 1, 1).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+//! Lowercase hexadecimal encoding for keys and signatures.
+
+/// Encodes `bytes` as lowercase hex.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decodes exactly `N` bytes of hex, in either case, or `None` if `s` is anything else.
+pub(crate) fn decode<const N: usize>(s: &str) -> Option<[u8; N]> {
+    if s.len() != N * 2 {
+        return None;
+    }
+    let mut out = [0; N];
+    for (byte, pair) in out.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+        let digit = |b: u8| char::from(b).to_digit(16);
+        *byte = (digit(pair[0])? << 4 | digit(pair[1])?) as u8;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode(&[0x00, 0xab, 0x7f]), "00ab7f");
+        assert_eq!(decode::<3>("00AB7f"), Some([0x00, 0xab, 0x7f]));
+        assert_eq!(decode::<3>("00ab7"), None);
+        assert_eq!(decode::<2>("+1ab"), None);
+        assert_eq!(decode::<2>("zzzz"), None);
+    }
+}
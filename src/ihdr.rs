@@ -0,0 +1,292 @@
+//! The `IHDR` image header chunk.
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, Error};
+
+/// How pixel samples are laid out, from the `IHDR` color type byte.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ColorType {
+    Grayscale = 0,
+    Rgb = 2,
+    Indexed = 3,
+    GrayscaleAlpha = 4,
+    Rgba = 6,
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Grayscale),
+            2 => Ok(Self::Rgb),
+            3 => Ok(Self::Indexed),
+            4 => Ok(Self::GrayscaleAlpha),
+            6 => Ok(Self::Rgba),
+            _ => Err(malformed(format!("invalid color type {value}"))),
+        }
+    }
+}
+
+impl std::fmt::Display for ColorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Grayscale => "grayscale",
+            Self::Rgb => "RGB",
+            Self::Indexed => "indexed",
+            Self::GrayscaleAlpha => "grayscale+alpha",
+            Self::Rgba => "RGBA",
+        })
+    }
+}
+
+impl ColorType {
+    /// Number of samples per pixel.
+    pub const fn channels(self) -> u8 {
+        match self {
+            Self::Grayscale | Self::Indexed => 1,
+            Self::GrayscaleAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+    /// Bit depths the PNG specification allows for this color type.
+    pub const fn allowed_bit_depths(self) -> &'static [u8] {
+        match self {
+            Self::Grayscale => &[1, 2, 4, 8, 16],
+            Self::Indexed => &[1, 2, 4, 8],
+            Self::Rgb | Self::GrayscaleAlpha | Self::Rgba => &[8, 16],
+        }
+    }
+    /// Whether pixels carry an alpha sample.
+    pub const fn has_alpha(self) -> bool {
+        matches!(self, Self::GrayscaleAlpha | Self::Rgba)
+    }
+}
+
+/// The pixel order of the image data, from the `IHDR` interlace method byte.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Interlace {
+    None = 0,
+    Adam7 = 1,
+}
+
+impl TryFrom<u8> for Interlace {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Adam7),
+            _ => Err(malformed(format!("invalid interlace method {value}"))),
+        }
+    }
+}
+
+/// The image header: dimensions and pixel format, always the first chunk of a PNG.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Ihdr {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: ColorType,
+    interlace: Interlace,
+}
+
+impl TryFrom<&[u8]> for Ihdr {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 13] = data
+            .try_into()
+            .map_err(|_| malformed(format!("expected 13 bytes, found {}", data.len())))?;
+        if data[10] != 0 {
+            return Err(malformed(format!(
+                "invalid compression method {}",
+                data[10]
+            )));
+        }
+        if data[11] != 0 {
+            return Err(malformed(format!("invalid filter method {}", data[11])));
+        }
+
+        Self::new(
+            u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            data[8],
+            ColorType::try_from(data[9])?,
+            Interlace::try_from(data[12])?,
+        )
+    }
+}
+
+impl TryFrom<&Chunk> for Ihdr {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        if *chunk.r#type() != ChunkType::IHDR {
+            return Err(Error::ChunkNotFound(ChunkType::IHDR.to_string()));
+        }
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Ihdr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}, {}-bit {}, {}",
+            self.width,
+            self.height,
+            self.bit_depth,
+            self.color_type,
+            match self.interlace {
+                Interlace::None => "non-interlaced",
+                Interlace::Adam7 => "Adam7 interlaced",
+            }
+        )
+    }
+}
+
+impl Ihdr {
+    /// Largest width or height allowed by the PNG specification.
+    pub const MAX_DIMENSION: u32 = (1 << 31) - 1;
+
+    /// Creates a header, checking dimensions and the bit depth / color type combination.
+    pub fn new(
+        width: u32,
+        height: u32,
+        bit_depth: u8,
+        color_type: ColorType,
+        interlace: Interlace,
+    ) -> Result<Self, Error> {
+        if !(1..=Self::MAX_DIMENSION).contains(&width)
+            || !(1..=Self::MAX_DIMENSION).contains(&height)
+        {
+            return Err(malformed(format!("invalid dimensions {width}x{height}")));
+        }
+        if !color_type.allowed_bit_depths().contains(&bit_depth) {
+            return Err(malformed(format!(
+                "bit depth {bit_depth} is not allowed for color type {color_type}"
+            )));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            interlace,
+        })
+    }
+    /// Image width in pixels.
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+    /// Image height in pixels.
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+    /// Bits per sample, or per palette index for indexed images.
+    pub const fn bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+    /// Pixel layout.
+    pub const fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+    /// Always 0 (deflate), the only method defined by the specification.
+    pub const fn compression_method(&self) -> u8 {
+        0
+    }
+    /// Always 0 (adaptive filtering), the only method defined by the specification.
+    pub const fn filter_method(&self) -> u8 {
+        0
+    }
+    /// Pixel order of the image data.
+    pub const fn interlace(&self) -> Interlace {
+        self.interlace
+    }
+    /// Bits used by one pixel.
+    pub const fn bits_per_pixel(&self) -> usize {
+        self.color_type.channels() as usize * self.bit_depth as usize
+    }
+    /// Bytes in one unfiltered scanline of `width` pixels, excluding the filter type byte.
+    pub const fn row_bytes(&self, width: u32) -> usize {
+        (width as usize * self.bits_per_pixel()).div_ceil(8)
+    }
+    /// Serializes the header as an `IHDR` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = Vec::with_capacity(13);
+        data.extend_from_slice(&self.width.to_be_bytes());
+        data.extend_from_slice(&self.height.to_be_bytes());
+        data.extend_from_slice(&[
+            self.bit_depth,
+            self.color_type as u8,
+            self.compression_method(),
+            self.filter_method(),
+            self.interlace as u8,
+        ]);
+        Chunk::new(ChunkType::IHDR, data)
+    }
+}
+
+fn malformed(reason: String) -> Error {
+    Error::MalformedChunk {
+        chunk_type: ChunkType::IHDR,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ihdr_chunk(data: [u8; 13]) -> Chunk {
+        Chunk::new(ChunkType::IHDR, data)
+    }
+
+    #[test]
+    fn test_ihdr_from_chunk() {
+        let ihdr = Ihdr::try_from(&ihdr_chunk([0, 0, 0, 50, 0, 0, 0, 40, 8, 6, 0, 0, 0])).unwrap();
+        assert_eq!(ihdr.width(), 50);
+        assert_eq!(ihdr.height(), 40);
+        assert_eq!(ihdr.bit_depth(), 8);
+        assert_eq!(ihdr.color_type(), ColorType::Rgba);
+        assert_eq!(ihdr.interlace(), Interlace::None);
+        assert_eq!(ihdr.row_bytes(ihdr.width()), 200);
+        assert_eq!(ihdr.to_string(), "50x40, 8-bit RGBA, non-interlaced");
+    }
+
+    #[test]
+    fn test_ihdr_round_trip() {
+        let ihdr = Ihdr::new(3, 7, 2, ColorType::Indexed, Interlace::Adam7).unwrap();
+        assert_eq!(Ihdr::try_from(&ihdr.to_chunk()).unwrap(), ihdr);
+        assert_eq!(ihdr.row_bytes(3), 1);
+    }
+
+    #[test]
+    fn test_invalid_bit_depth_for_color_type() {
+        for (bit_depth, color_type) in [(4, 2), (16, 3), (1, 6), (3, 0), (2, 4)] {
+            let chunk = ihdr_chunk([0, 0, 0, 1, 0, 0, 0, 1, bit_depth, color_type, 0, 0, 0]);
+            assert!(matches!(
+                Ihdr::try_from(&chunk),
+                Err(Error::MalformedChunk { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_ihdr_fields() {
+        for data in [
+            [0, 0, 0, 0, 0, 0, 0, 1, 8, 0, 0, 0, 0],
+            [128, 0, 0, 0, 0, 0, 0, 1, 8, 0, 0, 0, 0],
+            [0, 0, 0, 1, 0, 0, 0, 1, 8, 1, 0, 0, 0],
+            [0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 1, 0, 0],
+            [0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 1, 0],
+            [0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 2],
+        ] {
+            assert!(Ihdr::try_from(&ihdr_chunk(data)).is_err());
+        }
+        assert!(Ihdr::try_from(&Chunk::new(ChunkType::IHDR, [0; 12])).is_err());
+    }
+}
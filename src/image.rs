@@ -0,0 +1,341 @@
+//! Decoded pixel data.
+
+use crate::{
+    adam7,
+    chunk_type::ChunkType,
+    filter,
+    ihdr::{ColorType, Ihdr, Interlace},
+    palette::Transparency,
+    zlib, Error,
+};
+
+/// Unfiltered pixel rows in the PNG sample layout: big-endian 16-bit samples, and pixels below
+/// 8 bits packed from the most significant bit with each row padded to a whole byte.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    color_type: ColorType,
+    bit_depth: u8,
+    data: Vec<u8>,
+}
+
+impl Image {
+    /// Wraps `data` as rows of `width` pixels, checking the format and that the length matches.
+    pub fn new(
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+        bit_depth: u8,
+        data: Vec<u8>,
+    ) -> Result<Self, Error> {
+        let ihdr = Ihdr::new(width, height, bit_depth, color_type, Interlace::None)
+            .map_err(|e| Error::InvalidImage(e.to_string()))?;
+        let expected = ihdr.row_bytes(width) as u64 * height as u64;
+        if data.len() as u64 != expected {
+            return Err(Error::InvalidImage(format!(
+                "expected {expected} bytes of pixel data, found {}",
+                data.len()
+            )));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            color_type,
+            bit_depth,
+            data,
+        })
+    }
+    /// Image width in pixels.
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+    /// Image height in pixels.
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+    /// Pixel layout.
+    pub const fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+    /// Bits per sample, or per palette index for indexed images.
+    pub const fn bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+    /// Bytes in one row.
+    pub const fn row_bytes(&self) -> usize {
+        (self.width as usize * self.color_type.channels() as usize * self.bit_depth as usize)
+            .div_ceil(8)
+    }
+    /// The row at `y`, or `None` past the last row.
+    pub fn row(&self, y: u32) -> Option<&[u8]> {
+        self.rows().nth(y as usize)
+    }
+    /// All rows from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks_exact(self.row_bytes())
+    }
+    /// All rows as one buffer.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Consumes the image, returning its rows as one buffer.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+    /// Converts the image to RGBA with 16-bit samples if it has them, 8-bit otherwise. Pixels
+    /// matching a gray or RGB `transparency` value become fully transparent. Fails for indexed
+    /// images, which must first be expanded through their palette.
+    pub fn to_rgba(&self, transparency: Option<&Transparency>) -> Result<Image, Error> {
+        if self.color_type == ColorType::Indexed {
+            return Err(Error::InvalidImage(
+                "indexed images have no RGBA samples".to_owned(),
+            ));
+        }
+        let depth = self.bit_depth as usize;
+        let channels = self.color_type.channels() as usize;
+        let (out_depth, max) = match depth {
+            16 => (16, u16::MAX),
+            _ => (8, u8::MAX as u16),
+        };
+        let scale = |s: u16| match depth {
+            1 | 2 | 4 => s * 255 / ((1 << depth) - 1),
+            _ => s,
+        };
+        let mut out =
+            Vec::with_capacity(self.width as usize * self.height as usize * 4 * out_depth / 8);
+        for row in self.rows() {
+            for x in 0..self.width as usize {
+                let s = |c: usize| sample(row, x * channels + c, depth);
+                let (rgb, raw, alpha) = match self.color_type {
+                    ColorType::Grayscale => ([s(0); 3], [s(0); 3], max),
+                    ColorType::GrayscaleAlpha => ([s(0); 3], [s(0); 3], s(1)),
+                    ColorType::Rgb => ([s(0), s(1), s(2)], [s(0), s(1), s(2)], max),
+                    _ => ([s(0), s(1), s(2)], [s(0), s(1), s(2)], s(3)),
+                };
+                let transparent = match transparency {
+                    Some(Transparency::Gray(gray)) => {
+                        self.color_type == ColorType::Grayscale && raw[0] == *gray
+                    }
+                    Some(&Transparency::Rgb(r, g, b)) => {
+                        self.color_type == ColorType::Rgb && raw == [r, g, b]
+                    }
+                    _ => false,
+                };
+                let alpha = if transparent { 0 } else { alpha };
+                for value in rgb.map(scale).into_iter().chain([alpha]) {
+                    match out_depth {
+                        16 => out.extend_from_slice(&value.to_be_bytes()),
+                        _ => out.push(value as u8),
+                    }
+                }
+            }
+        }
+        Image::new(
+            self.width,
+            self.height,
+            ColorType::Rgba,
+            out_depth as u8,
+            out,
+        )
+    }
+}
+
+/// Sample `index` of a row packed at `depth` bits per sample.
+pub(crate) fn sample(row: &[u8], index: usize, depth: usize) -> u16 {
+    match depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * depth;
+            (row[bit / 8] >> (8 - depth - bit % 8) & ((1 << depth) - 1) as u8) as u16
+        }
+    }
+}
+
+/// Decompresses `IDAT` data into filtered scanlines, reading no more than the header allows.
+pub(crate) fn decompress(ihdr: &Ihdr, compressed: &[u8]) -> Result<Vec<u8>, Error> {
+    let (width, height) = (ihdr.width(), ihdr.height());
+    let expected = match ihdr.interlace() {
+        Interlace::None => (ihdr.row_bytes(width) + 1).checked_mul(height as usize),
+        Interlace::Adam7 => adam7::filtered_len(ihdr),
+    }
+    .ok_or_else(|| Error::InvalidImage(format!("{width}x{height} is too large")))?;
+    let filtered = zlib::decompress_limited(compressed, expected)?;
+    if filtered.len() != expected {
+        return Err(Error::MalformedChunk {
+            chunk_type: ChunkType::IDAT,
+            reason: format!(
+                "expected {expected} bytes of image data, found {}",
+                filtered.len()
+            ),
+        });
+    }
+    Ok(filtered)
+}
+
+/// Decodes zlib-compressed `IDAT` data, de-interlacing Adam7 images. Indexed images are expanded
+/// to 8-bit RGB through `plte`, or to RGBA when `trns` is present.
+pub(crate) fn decode(
+    ihdr: &Ihdr,
+    compressed: &[u8],
+    plte: Option<&[u8]>,
+    trns: Option<&[u8]>,
+) -> Result<Image, Error> {
+    let (width, height) = (ihdr.width(), ihdr.height());
+    let row_bytes = ihdr.row_bytes(width);
+    let filtered = decompress(ihdr, compressed)?;
+    let data = match ihdr.interlace() {
+        Interlace::None => {
+            let bpp = ihdr.bits_per_pixel().div_ceil(8);
+            filter::unfilter(&filtered, row_bytes, height as usize, bpp)?
+        }
+        Interlace::Adam7 => adam7::deinterlace(ihdr, &filtered)?,
+    };
+
+    if ihdr.color_type() != ColorType::Indexed {
+        return Image::new(width, height, ihdr.color_type(), ihdr.bit_depth(), data);
+    }
+    let plte = plte.ok_or_else(|| Error::ChunkNotFound(ChunkType::PLTE.to_string()))?;
+    expand_palette(ihdr, &data, plte, trns)
+}
+
+fn expand_palette(
+    ihdr: &Ihdr,
+    data: &[u8],
+    plte: &[u8],
+    trns: Option<&[u8]>,
+) -> Result<Image, Error> {
+    let (width, height) = (ihdr.width(), ihdr.height());
+    let (color_type, channels) = match trns {
+        Some(_) => (ColorType::Rgba, 4),
+        None => (ColorType::Rgb, 3),
+    };
+    let entries = plte.len() / 3;
+    let depth = ihdr.bit_depth() as usize;
+    let mask = ((1_u16 << depth) - 1) as u8;
+    let mut out = Vec::with_capacity(width as usize * height as usize * channels);
+    for row in data.chunks_exact(ihdr.row_bytes(width)) {
+        for x in 0..width as usize {
+            let bit = x * depth;
+            let index = (row[bit / 8] >> (8 - depth - bit % 8) & mask) as usize;
+            if index >= entries {
+                return Err(Error::MalformedChunk {
+                    chunk_type: ChunkType::PLTE,
+                    reason: format!("palette index {index} out of range for {entries} entries"),
+                });
+            }
+            out.extend_from_slice(&plte[index * 3..index * 3 + 3]);
+            if let Some(trns) = trns {
+                out.push(trns.get(index).copied().unwrap_or(255));
+            }
+        }
+    }
+    Image::new(width, height, color_type, 8, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deflate::Compression;
+
+    fn compress_rows(rows: &[&[u8]]) -> Vec<u8> {
+        let filtered = rows
+            .iter()
+            .flat_map(|row| [&[0][..], row].concat())
+            .collect::<Vec<_>>();
+        zlib::compress(&filtered, Compression::Default)
+    }
+
+    #[test]
+    fn test_to_rgba() {
+        let gray = Image::new(3, 1, ColorType::Grayscale, 2, vec![0b00_01_11_00]).unwrap();
+        assert_eq!(
+            gray.to_rgba(Some(&Transparency::Gray(1))).unwrap().data(),
+            [0, 0, 0, 255, 85, 85, 85, 0, 255, 255, 255, 255]
+        );
+        let rgb = Image::new(1, 1, ColorType::Rgb, 16, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let rgba = rgb.to_rgba(None).unwrap();
+        assert_eq!(rgba.bit_depth(), 16);
+        assert_eq!(rgba.data(), [1, 2, 3, 4, 5, 6, 255, 255]);
+        let gray_alpha = Image::new(1, 1, ColorType::GrayscaleAlpha, 8, vec![7, 9]).unwrap();
+        assert_eq!(gray_alpha.to_rgba(None).unwrap().data(), [7, 7, 7, 9]);
+        assert!(Image::new(1, 1, ColorType::Indexed, 8, vec![0])
+            .unwrap()
+            .to_rgba(None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_grayscale_16_bit() {
+        let ihdr = Ihdr::new(2, 1, 16, ColorType::Grayscale, Interlace::None).unwrap();
+        let image = decode(&ihdr, &compress_rows(&[&[1, 2, 3, 4]]), None, None).unwrap();
+        assert_eq!(image.row_bytes(), 4);
+        assert_eq!(image.data(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_decode_indexed_with_transparency() {
+        let ihdr = Ihdr::new(3, 2, 2, ColorType::Indexed, Interlace::None).unwrap();
+        let plte = [10, 11, 12, 20, 21, 22, 30, 31, 32];
+        let compressed = compress_rows(&[&[0b00_01_10_00], &[0b10_00_00_00]]);
+        let image = decode(&ihdr, &compressed, Some(&plte), Some(&[0])).unwrap();
+        assert_eq!(image.color_type(), ColorType::Rgba);
+        assert_eq!(image.bit_depth(), 8);
+        assert_eq!(
+            image.row(0).unwrap(),
+            [10, 11, 12, 0, 20, 21, 22, 255, 30, 31, 32, 255]
+        );
+        assert_eq!(
+            image.row(1).unwrap(),
+            [30, 31, 32, 255, 10, 11, 12, 0, 10, 11, 12, 0]
+        );
+
+        let image = decode(&ihdr, &compressed, Some(&plte), None).unwrap();
+        assert_eq!(image.color_type(), ColorType::Rgb);
+        assert_eq!(image.row(1).unwrap(), [30, 31, 32, 10, 11, 12, 10, 11, 12]);
+    }
+
+    #[test]
+    fn test_decode_interlaced() {
+        let ihdr = Ihdr::new(3, 2, 8, ColorType::Grayscale, Interlace::Adam7).unwrap();
+        let compressed = zlib::compress(&[0, 1, 0, 2, 0, 3, 0, 4, 5, 6], Compression::Default);
+        let image = decode(&ihdr, &compressed, None, None).unwrap();
+        assert_eq!(image.data(), [1, 3, 2, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_decode_palette_errors() {
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Indexed, Interlace::None).unwrap();
+        let compressed = compress_rows(&[&[1]]);
+        assert!(matches!(
+            decode(&ihdr, &compressed, None, None),
+            Err(Error::ChunkNotFound(_))
+        ));
+        assert!(matches!(
+            decode(&ihdr, &compressed, Some(&[0, 0, 0]), None),
+            Err(Error::MalformedChunk { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_wrong_length() {
+        let ihdr = Ihdr::new(2, 2, 8, ColorType::Grayscale, Interlace::None).unwrap();
+        assert!(matches!(
+            decode(&ihdr, &compress_rows(&[&[1, 2]]), None, None),
+            Err(Error::MalformedChunk { .. })
+        ));
+        assert!(decode(&ihdr, &compress_rows(&[&[1, 2][..]; 3]), None, None).is_err());
+    }
+
+    #[test]
+    fn test_image_new_checks_length() {
+        assert!(Image::new(3, 1, ColorType::Grayscale, 1, vec![0]).is_ok());
+        assert!(matches!(
+            Image::new(3, 1, ColorType::Grayscale, 1, vec![0, 0]),
+            Err(Error::InvalidImage(_))
+        ));
+        assert!(Image::new(1, 1, ColorType::Rgb, 4, vec![0, 0]).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! Raw DEFLATE decompression (RFC 1951).
+
+use crate::Error;
+
+/// Lookup table width for Huffman codes; longer codes fall back to a canonical walk.
+const FAST_BITS: u32 = 9;
+const MAX_BITS: usize = 15;
+
+pub(crate) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(crate) const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+pub(crate) const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+pub(crate) const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored in a dynamic block header.
+pub(crate) const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompresses a raw DEFLATE stream, returning the data and the number of input bytes used.
+///
+/// Fails if the output would grow beyond `limit` bytes.
+pub fn inflate(data: &[u8], limit: usize) -> Result<(Vec<u8>, usize), Error> {
+    let mut bits = BitReader::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored(&mut bits, &mut out, limit)?,
+            1 => {
+                let (lit, dist) = fixed_codes();
+                codes(&mut bits, &mut out, limit, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_codes(&mut bits)?;
+                codes(&mut bits, &mut out, limit, &lit, &dist)?;
+            }
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            break;
+        }
+    }
+    Ok((out, bits.byte_position()))
+}
+
+fn stored(bits: &mut BitReader, out: &mut Vec<u8>, limit: usize) -> Result<(), Error> {
+    bits.align();
+    let len = bits.bits(16)? as u16;
+    let nlen = bits.bits(16)? as u16;
+    if len != !nlen {
+        return Err(corrupt("stored block length check failed"));
+    }
+    let mut remaining = len as usize;
+    if out.len() + remaining > limit {
+        return Err(too_large(limit));
+    }
+    while remaining > 0 && bits.count >= 8 {
+        out.push(bits.bits(8)? as u8);
+        remaining -= 1;
+    }
+    let bytes = bits
+        .data
+        .get(bits.pos..bits.pos + remaining)
+        .ok_or_else(|| corrupt("unexpected end of stream"))?;
+    out.extend_from_slice(bytes);
+    bits.pos += remaining;
+    Ok(())
+}
+
+fn codes(
+    bits: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), Error> {
+    loop {
+        let symbol = lit.decode(bits)? as usize;
+        match symbol {
+            0..=255 => {
+                if out.len() >= limit {
+                    return Err(too_large(limit));
+                }
+                out.push(symbol as u8);
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let i = symbol - 257;
+                let len = LENGTH_BASE[i] as usize + bits.bits(LENGTH_EXTRA[i] as u32)? as usize;
+                let i = dist.decode(bits)? as usize;
+                if i >= DIST_BASE.len() {
+                    return Err(corrupt("invalid distance code"));
+                }
+                let distance = DIST_BASE[i] as usize + bits.bits(DIST_EXTRA[i] as u32)? as usize;
+                if distance > out.len() {
+                    return Err(corrupt("distance too far back"));
+                }
+                if out.len() + len > limit {
+                    return Err(too_large(limit));
+                }
+                let start = out.len() - distance;
+                if distance >= len {
+                    out.extend_from_within(start..start + len);
+                } else {
+                    for i in start..start + len {
+                        out.push(out[i]);
+                    }
+                }
+            }
+            _ => return Err(corrupt("invalid literal/length code")),
+        }
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    (
+        Huffman::new(&lengths).expect("fixed literal/length code is valid"),
+        Huffman::new(&[5; 30]).expect("fixed distance code is valid"),
+    )
+}
+
+fn dynamic_codes(bits: &mut BitReader) -> Result<(Huffman, Huffman), Error> {
+    let nlen = bits.bits(5)? as usize + 257;
+    let ndist = bits.bits(5)? as usize + 1;
+    let ncode = bits.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return Err(corrupt("too many length or distance codes"));
+    }
+    let mut code_lengths = [0; 19];
+    for &i in &CODE_LENGTH_ORDER[..ncode] {
+        code_lengths[i] = bits.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0; nlen + ndist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_lengths.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => {
+                lengths[i] = symbol as u8;
+                i += 1;
+                continue;
+            }
+            16 => match i.checked_sub(1) {
+                Some(prev) => (lengths[prev], 3 + bits.bits(2)?),
+                None => return Err(corrupt("repeat with no previous length")),
+            },
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        let end = i + repeat as usize;
+        if end > lengths.len() {
+            return Err(corrupt("code lengths overflow"));
+        }
+        lengths[i..end].fill(value);
+        i = end;
+    }
+    if lengths[256] == 0 {
+        return Err(corrupt("missing end-of-block code"));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..nlen])?,
+        Huffman::new(&lengths[nlen..])?,
+    ))
+}
+
+/// A canonical Huffman code, decoded through a lookup table for short codes.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+    /// `symbol << 4 | length` for every `FAST_BITS`-bit prefix that completes a code, or 0.
+    fast: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, Error> {
+        let mut counts = [0_u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut left = 1_i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(corrupt("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0_u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        let mut fast = vec![0; 1 << FAST_BITS];
+        let mut code = 0_u32;
+        let mut symbols_by_length = symbols.iter();
+        for (len, &count) in counts
+            .iter()
+            .enumerate()
+            .take(FAST_BITS as usize + 1)
+            .skip(1)
+        {
+            for symbol in symbols_by_length.by_ref().take(count as usize) {
+                let reversed = code.reverse_bits() >> (32 - len);
+                let entry = symbol << 4 | len as u16;
+                for fill in (reversed as usize..fast.len()).step_by(1 << len) {
+                    fast[fill] = entry;
+                }
+                code += 1;
+            }
+            code <<= 1;
+        }
+
+        Ok(Self {
+            counts,
+            symbols,
+            fast,
+        })
+    }
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, Error> {
+        bits.refill();
+        let entry = self.fast[bits.peek(FAST_BITS) as usize];
+        if entry != 0 {
+            bits.consume((entry & 0xf) as u32)?;
+            return Ok(entry >> 4);
+        }
+
+        let (mut code, mut first, mut index) = (0_i32, 0_i32, 0_i32);
+        for len in 1..=MAX_BITS {
+            code |= (bits.peek(len as u32) >> (len - 1)) as i32 & 1;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                bits.consume(len as u32)?;
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(corrupt("invalid Huffman code"))
+    }
+}
+
+/// Reads bits least-significant first, as DEFLATE packs them.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buf: 0,
+            count: 0,
+        }
+    }
+    fn refill(&mut self) {
+        while self.count <= 56 && self.pos < self.data.len() {
+            self.buf |= (self.data[self.pos] as u64) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+    }
+    /// The next `n` bits without consuming them, zero-padded past the end of the input.
+    const fn peek(&self, n: u32) -> u64 {
+        self.buf & ((1 << n) - 1)
+    }
+    fn consume(&mut self, n: u32) -> Result<(), Error> {
+        if n > self.count {
+            return Err(corrupt("unexpected end of stream"));
+        }
+        self.buf >>= n;
+        self.count -= n;
+        Ok(())
+    }
+    fn bits(&mut self, n: u32) -> Result<u32, Error> {
+        self.refill();
+        let value = self.peek(n) as u32;
+        self.consume(n)?;
+        Ok(value)
+    }
+    /// Drops bits up to the next byte boundary.
+    fn align(&mut self) {
+        let skip = self.count % 8;
+        self.buf >>= skip;
+        self.count -= skip;
+    }
+    /// Input bytes consumed, counting a partially read byte as consumed.
+    const fn byte_position(&self) -> usize {
+        self.pos - (self.count / 8) as usize
+    }
+}
+
+fn corrupt(reason: &str) -> Error {
+    Error::Decompress(reason.to_owned())
+}
+
+fn too_large(limit: usize) -> Error {
+    Error::Decompress(format!("output exceeds {limit} bytes"))
+}
@@ -0,0 +1,112 @@
+//! HMAC-SHA-256 (RFC 2104) and the key derivations built on it: PBKDF2 for passwords (RFC 8018)
+//! and HKDF for shared secrets (RFC 5869).
+
+use crate::sha2::Sha256;
+
+/// The HMAC-SHA-256 tag of `data` under `key`.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0; Sha256::BLOCK_SIZE];
+    if key.len() > Sha256::BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(&block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(&block.map(|b| b ^ 0x5c));
+    outer.update(&inner.finalize());
+    outer.finalize()
+}
+
+/// Fills `out` with PBKDF2-HMAC-SHA-256 output for `password` and `salt`.
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    for (i, block) in out.chunks_mut(32).enumerate() {
+        let mut message = salt.to_vec();
+        message.extend_from_slice(&(i as u32 + 1).to_be_bytes());
+        let mut u = hmac_sha256(password, &message);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac_sha256(password, &u);
+            for (t, u) in t.iter_mut().zip(u) {
+                *t ^= u;
+            }
+        }
+        block.copy_from_slice(&t[..block.len()]);
+    }
+}
+
+/// Fills `out`, at most 8160 bytes, with HKDF-SHA-256 output for the input keying material
+/// `ikm`.
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], out: &mut [u8]) {
+    assert!(
+        out.len() <= 255 * 32,
+        "HKDF output is limited to 255 blocks"
+    );
+    let prk = hmac_sha256(salt, ikm);
+    let mut previous = vec![];
+    for (i, block) in out.chunks_mut(32).enumerate() {
+        previous.extend_from_slice(info);
+        previous.push(i as u8 + 1);
+        let t = hmac_sha256(&prk, &previous);
+        block.copy_from_slice(&t[..block.len()]);
+        previous = t.to_vec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231 test cases 1 and 6.
+        assert_eq!(
+            hex::encode(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hex::encode(&hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_sha256() {
+        // RFC 5869 test case 1.
+        let salt: Vec<u8> = (0..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        let mut out = [0; 42];
+        hkdf_sha256(&salt, &[0x0b; 22], &info, &mut out);
+        assert_eq!(
+            hex::encode(&out),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256() {
+        let mut out = [0; 40];
+        pbkdf2_sha256(b"password", b"salt", 2, &mut out[..32]);
+        assert_eq!(
+            hex::encode(&out[..32]),
+            "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43"
+        );
+        pbkdf2_sha256(
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            4096,
+            &mut out,
+        );
+        assert_eq!(
+            hex::encode(&out),
+            "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! PNG chunk parsing and editing with no dependencies.
+//!
+//! The crate exposes [`Png`], [`Chunk`] and [`ChunkType`] for reading a PNG file into its chunks,
+//! inspecting or editing them, and serializing the result back to bytes.
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use pngme::{Chunk, ChunkType, Png};
+//!
+//! let mut png = Png::from_chunks([]);
+//! png.append_chunk(Chunk::new(ChunkType::from_str("ruSt")?, "hidden message"));
+//!
+//! let png = Png::try_from(png.bytes().as_slice())?;
+//! assert_eq!(png.chunk_by_type("ruSt").unwrap().data_as_string()?, "hidden message");
+//! # Ok::<(), pngme::Error>(())
+//! ```
+
+pub mod adam7;
+pub mod apng;
+pub mod chacha20poly1305;
+pub mod chunk;
+pub mod chunk_type;
+pub mod codec;
+pub mod color;
+pub mod deflate;
+pub mod ed25519;
+pub mod encoder;
+pub mod envelope;
+pub mod error;
+pub mod exif;
+pub(crate) mod field25519;
+pub mod filter;
+pub(crate) mod hex;
+pub mod ihdr;
+pub mod image;
+pub mod inflate;
+pub mod kdf;
+pub mod palette;
+pub mod physical;
+pub mod png;
+pub mod reader;
+pub mod sha2;
+pub mod signing;
+pub mod text;
+pub mod time;
+pub mod validate;
+pub mod writer;
+pub mod x25519;
+pub mod zlib;
+
+pub use crate::{
+    apng::{Animation, AnimationControl, BlendOp, DisposeOp, Frame, FrameControl},
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    codec::{ChunkCodec, CodecRegistry},
+    deflate::Compression,
+    encoder::PngEncoder,
+    envelope::{Envelope, Scheme},
+    error::Error,
+    exif::Exif,
+    filter::{FilterStrategy, FilterType},
+    ihdr::{ColorType, Ihdr, Interlace},
+    image::Image,
+    palette::{Background, Histogram, Palette, SuggestedEntry, SuggestedPalette, Transparency},
+    physical::{
+        Equation, Offset, OffsetUnit, PhysicalDimensions, PhysicalScale, PhysicalUnit,
+        PixelCalibration, ScaleUnit, SignificantBits,
+    },
+    png::{Placement, Png},
+    reader::PngReader,
+    signing::ChunkSignature,
+    text::{TextChunk, TextFormat},
+    time::Timestamp,
+    validate::{Violation, ViolationKind},
+    writer::PngWriter,
+};
+
+/// Result alias using the crate [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
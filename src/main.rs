@@ -1,13 +1,13 @@
-mod chunk;
-mod chunk_type;
-mod cli;
-mod png;
-
-pub type Error = Box<dyn std::error::Error>;
-pub type Result<T> = std::result::Result<T, Error>;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    cli::run()?;
+mod cli;
 
-    Ok(())
+fn main() -> ExitCode {
+    match cli::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            e.exit_code()
+        }
+    }
 }
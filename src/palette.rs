@@ -0,0 +1,635 @@
+//! Palette chunks and the chunks that depend on it: `PLTE`, `tRNS`, `bKGD`, `hIST` and `sPLT`.
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    color::{check_type, malformed},
+    ihdr::{ColorType, Ihdr},
+    text::{self, check_keyword},
+    Error,
+};
+
+/// The RGB entries of a `PLTE` chunk.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Palette {
+    entries: Vec<[u8; 3]>,
+}
+
+impl TryFrom<&[u8]> for Palette {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if !data.len().is_multiple_of(3) {
+            return Err(malformed(
+                ChunkType::PLTE,
+                format!("length {} is not a multiple of 3", data.len()),
+            ));
+        }
+        Self::new(
+            data.chunks_exact(3)
+                .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl TryFrom<&Chunk> for Palette {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::PLTE)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Palette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} entries", self.entries.len())
+    }
+}
+
+impl Palette {
+    /// Creates a palette of 1 to 256 RGB entries.
+    pub fn new(entries: impl Into<Vec<[u8; 3]>>) -> Result<Self, Error> {
+        let entries = entries.into();
+        if !(1..=256).contains(&entries.len()) {
+            return Err(malformed(
+                ChunkType::PLTE,
+                format!("expected 1 to 256 entries, found {}", entries.len()),
+            ));
+        }
+        Ok(Self { entries })
+    }
+    /// The RGB entries in index order.
+    pub fn entries(&self) -> &[[u8; 3]] {
+        &self.entries
+    }
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Always `false`: a palette has at least one entry.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// The entry at `index`, if there is one.
+    pub fn get(&self, index: u8) -> Option<[u8; 3]> {
+        self.entries.get(index as usize).copied()
+    }
+    /// Checks that images with this header may carry the palette, and that indexed images can
+    /// address every entry.
+    pub fn check(&self, ihdr: &Ihdr) -> Result<(), Error> {
+        match ihdr.color_type() {
+            color_type @ (ColorType::Grayscale | ColorType::GrayscaleAlpha) => Err(malformed(
+                ChunkType::PLTE,
+                format!("{color_type} images cannot have a palette"),
+            )),
+            ColorType::Indexed if self.len() > 1 << ihdr.bit_depth() => Err(malformed(
+                ChunkType::PLTE,
+                format!(
+                    "{} entries do not fit in {} bits",
+                    self.len(),
+                    ihdr.bit_depth()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+    /// Serializes the palette as a `PLTE` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(ChunkType::PLTE, self.entries.concat())
+    }
+}
+
+/// Simple transparency from a `tRNS` chunk, whose layout depends on the color type.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Transparency {
+    /// The gray sample value of fully transparent pixels.
+    Gray(u16),
+    /// The RGB sample values of fully transparent pixels.
+    Rgb(u16, u16, u16),
+    /// Alpha of the first palette entries. Missing entries are opaque.
+    Alpha(Vec<u8>),
+}
+
+impl std::fmt::Display for Transparency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gray(gray) => write!(f, "gray {gray}"),
+            Self::Rgb(r, g, b) => write!(f, "RGB ({r}, {g}, {b})"),
+            Self::Alpha(alpha) => write!(f, "alpha for {} palette entries", alpha.len()),
+        }
+    }
+}
+
+impl Transparency {
+    /// Parses a `tRNS` chunk laid out for `color_type`.
+    pub fn from_chunk(chunk: &Chunk, color_type: ColorType) -> Result<Self, Error> {
+        check_type(chunk, ChunkType::TRNS)?;
+        let data = chunk.data();
+        match color_type {
+            ColorType::Grayscale => Ok(Self::Gray(samples::<1>(ChunkType::TRNS, data)?[0])),
+            ColorType::Rgb => {
+                let [r, g, b] = samples(ChunkType::TRNS, data)?;
+                Ok(Self::Rgb(r, g, b))
+            }
+            ColorType::Indexed if data.len() > 256 => Err(malformed(
+                ChunkType::TRNS,
+                format!("expected at most 256 alpha values, found {}", data.len()),
+            )),
+            ColorType::Indexed => Ok(Self::Alpha(data.to_vec())),
+            ColorType::GrayscaleAlpha | ColorType::Rgba => Err(malformed(
+                ChunkType::TRNS,
+                format!("{color_type} images cannot have transparency data"),
+            )),
+        }
+    }
+    /// Alpha of palette entry `index`: its `tRNS` value, or opaque if it has none. Always opaque
+    /// for gray and RGB transparency.
+    pub fn alpha(&self, index: u8) -> u8 {
+        match self {
+            Self::Alpha(alpha) => alpha.get(index as usize).copied().unwrap_or(u8::MAX),
+            _ => u8::MAX,
+        }
+    }
+    /// Checks that the layout matches the color type, samples fit in the bit depth and there is
+    /// no more alpha than palette entries.
+    pub fn check(&self, ihdr: &Ihdr, palette: Option<&Palette>) -> Result<(), Error> {
+        match (self, ihdr.color_type()) {
+            (Self::Gray(gray), ColorType::Grayscale) => {
+                check_samples(ChunkType::TRNS, &[*gray], ihdr.bit_depth())
+            }
+            (Self::Rgb(r, g, b), ColorType::Rgb) => {
+                check_samples(ChunkType::TRNS, &[*r, *g, *b], ihdr.bit_depth())
+            }
+            (Self::Alpha(alpha), ColorType::Indexed) => {
+                let entries = palette.map_or(0, Palette::len);
+                if alpha.len() > entries {
+                    return Err(malformed(
+                        ChunkType::TRNS,
+                        format!("{} alpha values for {entries} palette entries", alpha.len()),
+                    ));
+                }
+                Ok(())
+            }
+            (_, color_type) => Err(malformed(
+                ChunkType::TRNS,
+                format!("{self} does not apply to {color_type} images"),
+            )),
+        }
+    }
+    /// Serializes the transparency as a `tRNS` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let data = match self {
+            Self::Gray(gray) => gray.to_be_bytes().to_vec(),
+            Self::Rgb(r, g, b) => [r, g, b].iter().flat_map(|s| s.to_be_bytes()).collect(),
+            Self::Alpha(alpha) => alpha.clone(),
+        };
+        Chunk::new(ChunkType::TRNS, data)
+    }
+}
+
+/// The default background color from a `bKGD` chunk, whose layout depends on the color type.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Background {
+    /// A gray sample value, for grayscale images with or without alpha.
+    Gray(u16),
+    /// RGB sample values, for RGB images with or without alpha.
+    Rgb(u16, u16, u16),
+    /// A palette index, for indexed images.
+    Index(u8),
+}
+
+impl std::fmt::Display for Background {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gray(gray) => write!(f, "gray {gray}"),
+            Self::Rgb(r, g, b) => write!(f, "RGB ({r}, {g}, {b})"),
+            Self::Index(index) => write!(f, "palette index {index}"),
+        }
+    }
+}
+
+impl Background {
+    /// Parses a `bKGD` chunk laid out for `color_type`.
+    pub fn from_chunk(chunk: &Chunk, color_type: ColorType) -> Result<Self, Error> {
+        check_type(chunk, ChunkType::BKGD)?;
+        let data = chunk.data();
+        match color_type {
+            ColorType::Grayscale | ColorType::GrayscaleAlpha => {
+                Ok(Self::Gray(samples::<1>(ChunkType::BKGD, data)?[0]))
+            }
+            ColorType::Rgb | ColorType::Rgba => {
+                let [r, g, b] = samples(ChunkType::BKGD, data)?;
+                Ok(Self::Rgb(r, g, b))
+            }
+            ColorType::Indexed => match data {
+                &[index] => Ok(Self::Index(index)),
+                _ => Err(malformed(
+                    ChunkType::BKGD,
+                    format!("expected 1 byte, found {}", data.len()),
+                )),
+            },
+        }
+    }
+    /// Checks that the layout matches the color type, samples fit in the bit depth and the index
+    /// names a palette entry.
+    pub fn check(&self, ihdr: &Ihdr, palette: Option<&Palette>) -> Result<(), Error> {
+        match (self, ihdr.color_type()) {
+            (Self::Gray(gray), ColorType::Grayscale | ColorType::GrayscaleAlpha) => {
+                check_samples(ChunkType::BKGD, &[*gray], ihdr.bit_depth())
+            }
+            (Self::Rgb(r, g, b), ColorType::Rgb | ColorType::Rgba) => {
+                check_samples(ChunkType::BKGD, &[*r, *g, *b], ihdr.bit_depth())
+            }
+            (Self::Index(index), ColorType::Indexed) => {
+                let entries = palette.map_or(0, Palette::len);
+                if *index as usize >= entries {
+                    return Err(malformed(
+                        ChunkType::BKGD,
+                        format!("index {index} is outside the {entries}-entry palette"),
+                    ));
+                }
+                Ok(())
+            }
+            (_, color_type) => Err(malformed(
+                ChunkType::BKGD,
+                format!("{self} does not apply to {color_type} images"),
+            )),
+        }
+    }
+    /// Serializes the background as a `bKGD` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let data = match self {
+            Self::Gray(gray) => gray.to_be_bytes().to_vec(),
+            Self::Rgb(r, g, b) => [r, g, b].iter().flat_map(|s| s.to_be_bytes()).collect(),
+            Self::Index(index) => vec![*index],
+        };
+        Chunk::new(ChunkType::BKGD, data)
+    }
+}
+
+/// Approximate usage frequency of each palette entry, from a `hIST` chunk.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Histogram(Vec<u16>);
+
+impl TryFrom<&[u8]> for Histogram {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.is_empty() || !data.len().is_multiple_of(2) || data.len() > 512 {
+            return Err(malformed(
+                ChunkType::HIST,
+                format!("invalid length {}", data.len()),
+            ));
+        }
+        Ok(Self(
+            data.chunks_exact(2)
+                .map(|f| u16::from_be_bytes([f[0], f[1]]))
+                .collect(),
+        ))
+    }
+}
+
+impl TryFrom<&Chunk> for Histogram {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::HIST)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Histogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} entries", self.0.len())
+    }
+}
+
+impl Histogram {
+    /// Creates a histogram with one frequency per palette entry.
+    pub fn new(frequencies: impl Into<Vec<u16>>) -> Self {
+        Self(frequencies.into())
+    }
+    /// The frequencies in palette order.
+    pub fn frequencies(&self) -> &[u16] {
+        &self.0
+    }
+    /// Checks that there is exactly one frequency per palette entry.
+    pub fn check(&self, palette: &Palette) -> Result<(), Error> {
+        if self.0.len() != palette.len() {
+            return Err(malformed(
+                ChunkType::HIST,
+                format!(
+                    "{} entries for {} palette entries",
+                    self.0.len(),
+                    palette.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+    /// Serializes the histogram as a `hIST` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(
+            ChunkType::HIST,
+            self.0
+                .iter()
+                .flat_map(|f| f.to_be_bytes())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// One color of a [`SuggestedPalette`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SuggestedEntry {
+    rgba: [u16; 4],
+    frequency: u16,
+}
+
+impl SuggestedEntry {
+    /// Creates an entry from RGBA samples at the palette's sample depth.
+    pub const fn new(rgba: [u16; 4], frequency: u16) -> Self {
+        Self { rgba, frequency }
+    }
+    /// Red, green, blue and alpha samples.
+    pub const fn rgba(&self) -> [u16; 4] {
+        self.rgba
+    }
+    /// Relative usage frequency.
+    pub const fn frequency(&self) -> u16 {
+        self.frequency
+    }
+}
+
+/// A named palette suggested for displays with fewer colors, from an `sPLT` chunk.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SuggestedPalette {
+    name: String,
+    sample_depth: u8,
+    entries: Vec<SuggestedEntry>,
+}
+
+impl TryFrom<&[u8]> for SuggestedPalette {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let malformed = |reason: String| malformed(ChunkType::SPLT, reason);
+        let (name, rest) = text::split_nul(data)
+            .ok_or_else(|| malformed("missing palette name separator".to_owned()))?;
+        let name = text::latin1(name);
+        text::check_stored_keyword(ChunkType::SPLT, &name)?;
+        let Some((&sample_depth, entries)) = rest.split_first() else {
+            return Err(malformed("missing sample depth".to_owned()));
+        };
+        let entry_len = match sample_depth {
+            8 => 6,
+            16 => 10,
+            _ => return Err(malformed(format!("invalid sample depth {sample_depth}"))),
+        };
+        if !entries.len().is_multiple_of(entry_len) {
+            return Err(malformed(format!(
+                "entry data length {} is not a multiple of {entry_len}",
+                entries.len()
+            )));
+        }
+
+        let entries = entries
+            .chunks_exact(entry_len)
+            .map(|entry| {
+                let (samples, frequency) = entry.split_at(entry_len - 2);
+                let mut rgba = [0; 4];
+                let width = sample_depth as usize / 8;
+                for (sample, bytes) in rgba.iter_mut().zip(samples.chunks_exact(width)) {
+                    *sample = match bytes {
+                        &[s] => s as u16,
+                        bytes => u16::from_be_bytes([bytes[0], bytes[1]]),
+                    };
+                }
+                SuggestedEntry::new(rgba, u16::from_be_bytes([frequency[0], frequency[1]]))
+            })
+            .collect();
+        Ok(Self {
+            name,
+            sample_depth,
+            entries,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for SuggestedPalette {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::SPLT)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for SuggestedPalette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}, {}-bit, {} entries",
+            self.name,
+            self.sample_depth,
+            self.entries.len()
+        )
+    }
+}
+
+impl SuggestedPalette {
+    /// Creates a suggested palette, checking that `name` is a valid keyword, `sample_depth` is 8
+    /// or 16 and every sample fits in it.
+    pub fn new(
+        name: impl Into<String>,
+        sample_depth: u8,
+        entries: impl Into<Vec<SuggestedEntry>>,
+    ) -> Result<Self, Error> {
+        let name = name.into();
+        check_keyword(&name)?;
+        if !matches!(sample_depth, 8 | 16) {
+            return Err(malformed(
+                ChunkType::SPLT,
+                format!("invalid sample depth {sample_depth}"),
+            ));
+        }
+        let entries = entries.into();
+        for entry in &entries {
+            check_samples(ChunkType::SPLT, &entry.rgba, sample_depth)?;
+        }
+        Ok(Self {
+            name,
+            sample_depth,
+            entries,
+        })
+    }
+    /// The palette name, unique among the file's `sPLT` chunks.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Bits per sample: 8 or 16.
+    pub const fn sample_depth(&self) -> u8 {
+        self.sample_depth
+    }
+    /// The suggested colors.
+    pub fn entries(&self) -> &[SuggestedEntry] {
+        &self.entries
+    }
+    /// Serializes the palette as an `sPLT` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = text::to_latin1(&self.name);
+        data.extend_from_slice(&[0, self.sample_depth]);
+        for entry in &self.entries {
+            for sample in entry.rgba {
+                match self.sample_depth {
+                    8 => data.push(sample as u8),
+                    _ => data.extend_from_slice(&sample.to_be_bytes()),
+                }
+            }
+            data.extend_from_slice(&entry.frequency.to_be_bytes());
+        }
+        Chunk::new(ChunkType::SPLT, data)
+    }
+}
+
+/// Reads exactly `N` big-endian 16-bit samples.
+fn samples<const N: usize>(chunk_type: ChunkType, data: &[u8]) -> Result<[u16; N], Error> {
+    if data.len() != N * 2 {
+        return Err(malformed(
+            chunk_type,
+            format!("expected {} bytes, found {}", N * 2, data.len()),
+        ));
+    }
+    Ok(std::array::from_fn(|i| {
+        u16::from_be_bytes([data[i * 2], data[i * 2 + 1]])
+    }))
+}
+
+/// Checks that every sample fits in `bit_depth` bits.
+fn check_samples(chunk_type: ChunkType, samples: &[u16], bit_depth: u8) -> Result<(), Error> {
+    match samples.iter().find(|&&s| u32::from(s) >> bit_depth != 0) {
+        Some(sample) => Err(malformed(
+            chunk_type,
+            format!("sample {sample} does not fit in {bit_depth} bits"),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::Interlace;
+
+    fn ihdr(color_type: ColorType, bit_depth: u8) -> Ihdr {
+        Ihdr::new(1, 1, bit_depth, color_type, Interlace::None).unwrap()
+    }
+
+    #[test]
+    fn test_palette() {
+        let palette = Palette::new([[1, 2, 3], [4, 5, 6], [7, 8, 9]]).unwrap();
+        assert_eq!(Palette::try_from(&palette.to_chunk()).unwrap(), palette);
+        assert_eq!(palette.get(1), Some([4, 5, 6]));
+        assert_eq!(palette.to_string(), "3 entries");
+
+        assert!(palette.check(&ihdr(ColorType::Indexed, 2)).is_ok());
+        assert!(palette.check(&ihdr(ColorType::Rgb, 8)).is_ok());
+        assert!(palette.check(&ihdr(ColorType::Indexed, 1)).is_err());
+        assert!(palette.check(&ihdr(ColorType::Grayscale, 8)).is_err());
+
+        assert!(Palette::new([]).is_err());
+        assert!(Palette::try_from(&Chunk::new(ChunkType::PLTE, [0; 4])).is_err());
+        assert!(Palette::try_from(&Chunk::new(ChunkType::PLTE, [0; 771])).is_err());
+    }
+
+    #[test]
+    fn test_transparency_and_background() {
+        let palette = Palette::new([[0; 3]; 2]).unwrap();
+        let indexed = ihdr(ColorType::Indexed, 8);
+        let alpha = Transparency::Alpha(vec![0]);
+        let chunk = alpha.to_chunk();
+        assert_eq!(
+            Transparency::from_chunk(&chunk, ColorType::Indexed).unwrap(),
+            alpha
+        );
+        assert_eq!((alpha.alpha(0), alpha.alpha(1)), (0, 255));
+        assert!(alpha.check(&indexed, Some(&palette)).is_ok());
+        assert!(Transparency::Alpha(vec![0; 3])
+            .check(&indexed, Some(&palette))
+            .is_err());
+        assert!(alpha.check(&indexed, None).is_err());
+        assert!(Transparency::from_chunk(&chunk, ColorType::Rgba).is_err());
+
+        let rgb = Transparency::Rgb(1, 2, 300);
+        assert_eq!(
+            Transparency::from_chunk(&rgb.to_chunk(), ColorType::Rgb).unwrap(),
+            rgb
+        );
+        assert!(rgb.check(&ihdr(ColorType::Rgb, 16), None).is_ok());
+        assert!(rgb.check(&ihdr(ColorType::Rgb, 8), None).is_err());
+        assert!(Transparency::Gray(3)
+            .check(&ihdr(ColorType::Grayscale, 1), None)
+            .is_err());
+        assert!(Transparency::from_chunk(&rgb.to_chunk(), ColorType::Grayscale).is_err());
+
+        let background = Background::Index(1);
+        assert_eq!(
+            Background::from_chunk(&background.to_chunk(), ColorType::Indexed).unwrap(),
+            background
+        );
+        assert!(background.check(&indexed, Some(&palette)).is_ok());
+        assert!(Background::Index(2)
+            .check(&indexed, Some(&palette))
+            .is_err());
+        let gray = Background::Gray(15);
+        assert_eq!(
+            Background::from_chunk(&gray.to_chunk(), ColorType::GrayscaleAlpha).unwrap(),
+            gray
+        );
+        assert!(gray.check(&ihdr(ColorType::Grayscale, 4), None).is_ok());
+        assert!(gray.check(&ihdr(ColorType::Rgb, 8), None).is_err());
+        assert!(Background::from_chunk(&gray.to_chunk(), ColorType::Indexed).is_err());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = Histogram::new([10, 0, 65535]);
+        assert_eq!(
+            Histogram::try_from(&histogram.to_chunk()).unwrap(),
+            histogram
+        );
+        assert!(histogram.check(&Palette::new([[0; 3]; 3]).unwrap()).is_ok());
+        assert!(histogram
+            .check(&Palette::new([[0; 3]; 2]).unwrap())
+            .is_err());
+        assert!(Histogram::try_from(&Chunk::new(ChunkType::HIST, [0; 3])).is_err());
+    }
+
+    #[test]
+    fn test_suggested_palette() {
+        for sample_depth in [8, 16] {
+            let entries = [
+                SuggestedEntry::new([255, 0, 0, 255], 3),
+                SuggestedEntry::new([0, 128, 0, 0], 1),
+            ];
+            let splt = SuggestedPalette::new("web safe", sample_depth, entries).unwrap();
+            let parsed = SuggestedPalette::try_from(&splt.to_chunk()).unwrap();
+            assert_eq!(parsed, splt);
+            assert_eq!(parsed.entries()[1].rgba(), [0, 128, 0, 0]);
+            assert_eq!(
+                parsed.to_string(),
+                format!(r#""web safe", {sample_depth}-bit, 2 entries"#)
+            );
+        }
+        let splt = SuggestedPalette::new("deep", 16, [SuggestedEntry::new([300; 4], 0)]).unwrap();
+        assert_eq!(splt.to_chunk().length(), 4 + 2 + 10);
+
+        assert!(SuggestedPalette::new("shallow", 8, [SuggestedEntry::new([300; 4], 0)]).is_err());
+        assert!(SuggestedPalette::new("odd", 4, []).is_err());
+        let mut data = splt.to_chunk().data().to_vec();
+        data.pop();
+        assert!(SuggestedPalette::try_from(&Chunk::new(ChunkType::SPLT, data)).is_err());
+    }
+}
@@ -0,0 +1,676 @@
+//! Physical layout and calibration chunks: `pHYs`, `sBIT`, `oFFs`, `pCAL` and `sCAL`.
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    color::{check_type, fixed, malformed},
+    ihdr::{ColorType, Ihdr},
+    text::{self, check_keyword},
+    Error,
+};
+
+/// Meters per inch, for converting pixels per meter to DPI.
+const METERS_PER_INCH: f64 = 0.0254;
+
+/// The unit of [`PhysicalDimensions`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PhysicalUnit {
+    /// Only the pixel aspect ratio is known.
+    Unknown = 0,
+    Meter = 1,
+}
+
+/// Pixels per unit along each axis, from a `pHYs` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PhysicalDimensions {
+    x: u32,
+    y: u32,
+    unit: PhysicalUnit,
+}
+
+impl TryFrom<&[u8]> for PhysicalDimensions {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 9] = fixed(data, ChunkType::PHYS)?;
+        let unit = match data[8] {
+            0 => PhysicalUnit::Unknown,
+            1 => PhysicalUnit::Meter,
+            unit => return Err(malformed(ChunkType::PHYS, format!("invalid unit {unit}"))),
+        };
+        Ok(Self {
+            x: u32::from_be_bytes(data[..4].try_into().unwrap()),
+            y: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+            unit,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for PhysicalDimensions {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::PHYS)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for PhysicalDimensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.dpi() {
+            Some((x, _)) if self.x == self.y => {
+                write!(f, "{} pixels per meter ({x:.0} dpi)", self.x)
+            }
+            Some((x, y)) => write!(
+                f,
+                "{}x{} pixels per meter ({x:.0}x{y:.0} dpi)",
+                self.x, self.y
+            ),
+            None => write!(f, "{}:{} pixel aspect ratio", self.x, self.y),
+        }
+    }
+}
+
+impl PhysicalDimensions {
+    /// Creates dimensions from pixels per unit.
+    pub const fn new(x: u32, y: u32, unit: PhysicalUnit) -> Self {
+        Self { x, y, unit }
+    }
+    /// Creates metric dimensions from dots per inch, rounded to whole pixels per meter.
+    pub fn from_dpi(x: f64, y: f64) -> Self {
+        let ppm = |dpi: f64| (dpi / METERS_PER_INCH).round() as u32;
+        Self::new(ppm(x), ppm(y), PhysicalUnit::Meter)
+    }
+    /// Pixels per unit along the x axis.
+    pub const fn x(&self) -> u32 {
+        self.x
+    }
+    /// Pixels per unit along the y axis.
+    pub const fn y(&self) -> u32 {
+        self.y
+    }
+    /// The unit, or [`PhysicalUnit::Unknown`] if only the aspect ratio is given.
+    pub const fn unit(&self) -> PhysicalUnit {
+        self.unit
+    }
+    /// Dots per inch along each axis, if the unit is known.
+    pub fn dpi(&self) -> Option<(f64, f64)> {
+        match self.unit {
+            PhysicalUnit::Meter => Some((
+                self.x as f64 * METERS_PER_INCH,
+                self.y as f64 * METERS_PER_INCH,
+            )),
+            PhysicalUnit::Unknown => None,
+        }
+    }
+    /// Serializes the dimensions as a `pHYs` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = self.x.to_be_bytes().to_vec();
+        data.extend_from_slice(&self.y.to_be_bytes());
+        data.push(self.unit as u8);
+        Chunk::new(ChunkType::PHYS, data)
+    }
+}
+
+/// Significant bits per channel of the original data, from an `sBIT` chunk.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignificantBits {
+    bits: Vec<u8>,
+}
+
+impl std::fmt::Display for SignificantBits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bits = self.bits.iter().map(u8::to_string).collect::<Vec<_>>();
+        f.write_str(&bits.join(", "))
+    }
+}
+
+impl SignificantBits {
+    /// Creates significant bit counts, one per channel: gray, RGB, gray and alpha, or RGBA.
+    /// Indexed images give the bits of the palette's RGB channels.
+    pub fn new(bits: impl Into<Vec<u8>>) -> Result<Self, Error> {
+        let bits = bits.into();
+        if !(1..=4).contains(&bits.len()) || bits.contains(&0) {
+            return Err(malformed(
+                ChunkType::SBIT,
+                format!("invalid significant bits {bits:?}"),
+            ));
+        }
+        Ok(Self { bits })
+    }
+    /// Parses an `sBIT` chunk laid out for `color_type`.
+    pub fn from_chunk(chunk: &Chunk, color_type: ColorType) -> Result<Self, Error> {
+        check_type(chunk, ChunkType::SBIT)?;
+        let channels = channels(color_type);
+        if chunk.data().len() != channels {
+            return Err(malformed(
+                ChunkType::SBIT,
+                format!(
+                    "expected {channels} bytes for {color_type} images, found {}",
+                    chunk.length()
+                ),
+            ));
+        }
+        Self::new(chunk.data())
+    }
+    /// Bit counts per channel.
+    pub fn bits(&self) -> &[u8] {
+        &self.bits
+    }
+    /// Checks that there is one count per channel and none exceeds the sample depth, which is 8
+    /// for indexed images.
+    pub fn check(&self, ihdr: &Ihdr) -> Result<(), Error> {
+        let color_type = ihdr.color_type();
+        let depth = match color_type {
+            ColorType::Indexed => 8,
+            _ => ihdr.bit_depth(),
+        };
+        if self.bits.len() != channels(color_type) {
+            return Err(malformed(
+                ChunkType::SBIT,
+                format!(
+                    "{} channels do not match {color_type} images",
+                    self.bits.len()
+                ),
+            ));
+        }
+        if let Some(bits) = self.bits.iter().find(|&&b| b > depth) {
+            return Err(malformed(
+                ChunkType::SBIT,
+                format!("{bits} bits exceed the sample depth {depth}"),
+            ));
+        }
+        Ok(())
+    }
+    /// Serializes the counts as an `sBIT` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        Chunk::new(ChunkType::SBIT, &self.bits)
+    }
+}
+
+/// The unit of an [`Offset`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OffsetUnit {
+    Pixel = 0,
+    Micrometer = 1,
+}
+
+/// The image position on a page, from an `oFFs` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Offset {
+    x: i32,
+    y: i32,
+    unit: OffsetUnit,
+}
+
+impl TryFrom<&[u8]> for Offset {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let data: [u8; 9] = fixed(data, ChunkType::OFFS)?;
+        let unit = match data[8] {
+            0 => OffsetUnit::Pixel,
+            1 => OffsetUnit::Micrometer,
+            unit => return Err(malformed(ChunkType::OFFS, format!("invalid unit {unit}"))),
+        };
+        Ok(Self {
+            x: i32::from_be_bytes(data[..4].try_into().unwrap()),
+            y: i32::from_be_bytes(data[4..8].try_into().unwrap()),
+            unit,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for Offset {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::OFFS)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for Offset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = match self.unit {
+            OffsetUnit::Pixel => "pixels",
+            OffsetUnit::Micrometer => "µm",
+        };
+        write!(f, "({}, {}) {unit}", self.x, self.y)
+    }
+}
+
+impl Offset {
+    /// Creates an offset from the page's left and top edges.
+    pub const fn new(x: i32, y: i32, unit: OffsetUnit) -> Self {
+        Self { x, y, unit }
+    }
+    /// Distance from the left edge.
+    pub const fn x(&self) -> i32 {
+        self.x
+    }
+    /// Distance from the top edge.
+    pub const fn y(&self) -> i32 {
+        self.y
+    }
+    /// The unit of both distances.
+    pub const fn unit(&self) -> OffsetUnit {
+        self.unit
+    }
+    /// Serializes the offset as an `oFFs` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = self.x.to_be_bytes().to_vec();
+        data.extend_from_slice(&self.y.to_be_bytes());
+        data.push(self.unit as u8);
+        Chunk::new(ChunkType::OFFS, data)
+    }
+}
+
+/// How a [`PixelCalibration`] maps sample values to physical values.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Equation {
+    /// `p0 + p1 * x / (x1 - x0)`.
+    Linear = 0,
+    /// `p0 + p1 * e^(p2 * x / (x1 - x0))`.
+    Exponential = 1,
+    /// `p0 + p1 * p3^(p2 * x / (x1 - x0))`.
+    ArbitraryExponential = 2,
+    /// `p0 + p1 * sinh(p2 * (x - p3) / (x1 - x0))`.
+    Hyperbolic = 3,
+}
+
+impl std::fmt::Display for Equation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Linear => "linear",
+            Self::Exponential => "exponential",
+            Self::ArbitraryExponential => "arbitrary-base exponential",
+            Self::Hyperbolic => "hyperbolic sine",
+        })
+    }
+}
+
+impl Equation {
+    /// Number of parameters the equation takes.
+    pub const fn parameters(self) -> usize {
+        match self {
+            Self::Linear => 2,
+            Self::Exponential => 3,
+            Self::ArbitraryExponential | Self::Hyperbolic => 4,
+        }
+    }
+}
+
+/// The physical meaning of sample values, from a `pCAL` chunk.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PixelCalibration {
+    name: String,
+    x0: i32,
+    x1: i32,
+    equation: Equation,
+    unit: String,
+    parameters: Vec<f64>,
+}
+
+impl TryFrom<&[u8]> for PixelCalibration {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let malformed = |reason: String| malformed(ChunkType::PCAL, reason);
+        let (name, rest) = text::split_nul(data)
+            .ok_or_else(|| malformed("missing calibration name separator".to_owned()))?;
+        let Some((header, rest)) = rest.split_first_chunk::<10>() else {
+            return Err(malformed("calibration header is truncated".to_owned()));
+        };
+        let equation = match header[8] {
+            0 => Equation::Linear,
+            1 => Equation::Exponential,
+            2 => Equation::ArbitraryExponential,
+            3 => Equation::Hyperbolic,
+            equation => return Err(malformed(format!("invalid equation type {equation}"))),
+        };
+        let mut fields = rest.split(|&b| b == 0);
+        let unit = text::latin1(fields.next().unwrap_or_default());
+        let parameters = fields
+            .map(|p| {
+                std::str::from_utf8(p)
+                    .ok()
+                    .and_then(parse_float)
+                    .ok_or_else(|| malformed(format!("invalid parameter {:?}", p.escape_ascii())))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if parameters.len() != header[9] as usize {
+            return Err(malformed(format!(
+                "{} parameters found, {} declared",
+                parameters.len(),
+                header[9]
+            )));
+        }
+
+        Self::new(
+            text::latin1(name),
+            i32::from_be_bytes(header[..4].try_into().unwrap()),
+            i32::from_be_bytes(header[4..8].try_into().unwrap()),
+            equation,
+            unit,
+            parameters,
+        )
+        .map_err(|e| match e {
+            Error::InvalidText(reason) => malformed(reason),
+            e => e,
+        })
+    }
+}
+
+impl TryFrom<&Chunk> for PixelCalibration {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::PCAL)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for PixelCalibration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}, samples {} to {}, {} with parameters {:?}, unit {:?}",
+            self.name, self.x0, self.x1, self.equation, self.parameters, self.unit
+        )
+    }
+}
+
+impl PixelCalibration {
+    /// Creates a calibration, checking that `name` is a valid keyword, `x0` and `x1` differ,
+    /// `equation` gets as many parameters as it takes and they are all finite.
+    pub fn new(
+        name: impl Into<String>,
+        x0: i32,
+        x1: i32,
+        equation: Equation,
+        unit: impl Into<String>,
+        parameters: impl Into<Vec<f64>>,
+    ) -> Result<Self, Error> {
+        let name = name.into();
+        check_keyword(&name)?;
+        let parameters = parameters.into();
+        let invalid = |reason: String| Err(malformed(ChunkType::PCAL, reason));
+        if x0 == x1 {
+            return invalid(format!("x0 and x1 are both {x0}"));
+        }
+        if parameters.len() != equation.parameters() {
+            return invalid(format!(
+                "{equation} equations take {} parameters, found {}",
+                equation.parameters(),
+                parameters.len()
+            ));
+        }
+        if parameters.iter().any(|p| !p.is_finite()) {
+            return invalid("parameters must be finite".to_owned());
+        }
+
+        Ok(Self {
+            name,
+            x0,
+            x1,
+            equation,
+            unit: unit.into(),
+            parameters,
+        })
+    }
+    /// The calibration name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The sample value mapped to the start of the physical range.
+    pub const fn x0(&self) -> i32 {
+        self.x0
+    }
+    /// The sample value mapped to the end of the physical range.
+    pub const fn x1(&self) -> i32 {
+        self.x1
+    }
+    /// The mapping equation.
+    pub const fn equation(&self) -> Equation {
+        self.equation
+    }
+    /// The physical unit, such as `m` or `K`.
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+    /// The equation parameters `p0` to `p3`.
+    pub fn parameters(&self) -> &[f64] {
+        &self.parameters
+    }
+    /// The physical value of a sample stored at `bit_depth` bits, which is first mapped onto
+    /// `x0` to `x1`.
+    pub fn physical_value(&self, sample: u16, bit_depth: u8) -> f64 {
+        let max = (1_i64 << bit_depth) - 1;
+        let span = i64::from(self.x1) - i64::from(self.x0);
+        let x = ((i64::from(sample) * span + max / 2) / max + i64::from(self.x0)) as f64;
+        let span = span as f64;
+        let p = &self.parameters;
+        match self.equation {
+            Equation::Linear => p[0] + p[1] * x / span,
+            Equation::Exponential => p[0] + p[1] * (p[2] * x / span).exp(),
+            Equation::ArbitraryExponential => p[0] + p[1] * p[3].powf(p[2] * x / span),
+            Equation::Hyperbolic => p[0] + p[1] * (p[2] * (x - p[3]) / span).sinh(),
+        }
+    }
+    /// Serializes the calibration as a `pCAL` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = text::to_latin1(&self.name);
+        data.push(0);
+        data.extend_from_slice(&self.x0.to_be_bytes());
+        data.extend_from_slice(&self.x1.to_be_bytes());
+        data.extend_from_slice(&[self.equation as u8, self.parameters.len() as u8]);
+        data.extend(text::to_latin1(&self.unit));
+        for parameter in &self.parameters {
+            data.push(0);
+            data.extend_from_slice(parameter.to_string().as_bytes());
+        }
+        Chunk::new(ChunkType::PCAL, data)
+    }
+}
+
+/// The unit of a [`PhysicalScale`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ScaleUnit {
+    Meter = 1,
+    Radian = 2,
+}
+
+/// The physical size of each pixel, from an `sCAL` chunk.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PhysicalScale {
+    width: f64,
+    height: f64,
+    unit: ScaleUnit,
+}
+
+impl TryFrom<&[u8]> for PhysicalScale {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let malformed = |reason: String| malformed(ChunkType::SCAL, reason);
+        let (unit, rest) = data
+            .split_first()
+            .ok_or_else(|| malformed("missing unit".to_owned()))?;
+        let unit = match unit {
+            1 => ScaleUnit::Meter,
+            2 => ScaleUnit::Radian,
+            unit => return Err(malformed(format!("invalid unit {unit}"))),
+        };
+        let (width, height) = text::split_nul(rest)
+            .ok_or_else(|| malformed("missing height separator".to_owned()))?;
+        let parse = |value: &[u8]| {
+            std::str::from_utf8(value)
+                .ok()
+                .and_then(parse_float)
+                .ok_or_else(|| malformed(format!("invalid size {:?}", value.escape_ascii())))
+        };
+        Self::new(parse(width)?, parse(height)?, unit)
+    }
+}
+
+impl TryFrom<&Chunk> for PhysicalScale {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::SCAL)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl std::fmt::Display for PhysicalScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = match self.unit {
+            ScaleUnit::Meter => "m",
+            ScaleUnit::Radian => "rad",
+        };
+        write!(f, "{} x {} {unit} per pixel", self.width, self.height)
+    }
+}
+
+impl PhysicalScale {
+    /// Creates a pixel size, checking that both dimensions are positive and finite.
+    pub fn new(width: f64, height: f64, unit: ScaleUnit) -> Result<Self, Error> {
+        if !(width > 0.0 && width.is_finite() && height > 0.0 && height.is_finite()) {
+            return Err(malformed(
+                ChunkType::SCAL,
+                format!("invalid pixel size {width} x {height}"),
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            unit,
+        })
+    }
+    /// Pixel width.
+    pub const fn width(&self) -> f64 {
+        self.width
+    }
+    /// Pixel height.
+    pub const fn height(&self) -> f64 {
+        self.height
+    }
+    /// The unit of both dimensions.
+    pub const fn unit(&self) -> ScaleUnit {
+        self.unit
+    }
+    /// Serializes the pixel size as an `sCAL` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = vec![self.unit as u8];
+        data.extend_from_slice(self.width.to_string().as_bytes());
+        data.push(0);
+        data.extend_from_slice(self.height.to_string().as_bytes());
+        Chunk::new(ChunkType::SCAL, data)
+    }
+}
+
+/// Samples per pixel that `sBIT` describes: indexed images are described by their RGB palette.
+const fn channels(color_type: ColorType) -> usize {
+    match color_type {
+        ColorType::Indexed => 3,
+        color_type => color_type.channels() as usize,
+    }
+}
+
+/// Parses the specification's ASCII floating-point format: an optional sign, digits with an
+/// optional decimal point, and an optional exponent.
+fn parse_float(s: &str) -> Option<f64> {
+    let valid = s.bytes().any(|b| b.is_ascii_digit())
+        && s.bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E'));
+    valid.then(|| s.parse().ok()).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ihdr::Interlace;
+
+    #[test]
+    fn test_physical_dimensions() {
+        let phys = PhysicalDimensions::from_dpi(72.0, 72.0);
+        assert_eq!((phys.x(), phys.y()), (2835, 2835));
+        assert_eq!(
+            PhysicalDimensions::try_from(&phys.to_chunk()).unwrap(),
+            phys
+        );
+        let (x, _) = phys.dpi().unwrap();
+        assert!((x - 72.0).abs() < 0.01);
+        assert_eq!(phys.to_string(), "2835 pixels per meter (72 dpi)");
+
+        let aspect = PhysicalDimensions::new(2, 1, PhysicalUnit::Unknown);
+        assert_eq!(aspect.dpi(), None);
+        assert_eq!(aspect.to_string(), "2:1 pixel aspect ratio");
+        let mut data = aspect.to_chunk().data().to_vec();
+        data[8] = 2;
+        assert!(PhysicalDimensions::try_from(&Chunk::new(ChunkType::PHYS, data)).is_err());
+    }
+
+    #[test]
+    fn test_significant_bits() {
+        let sbit = SignificantBits::new([5, 6, 5]).unwrap();
+        let chunk = sbit.to_chunk();
+        assert_eq!(
+            SignificantBits::from_chunk(&chunk, ColorType::Indexed).unwrap(),
+            sbit
+        );
+        assert!(SignificantBits::from_chunk(&chunk, ColorType::Rgba).is_err());
+        assert_eq!(sbit.to_string(), "5, 6, 5");
+
+        let ihdr = |color_type, bit_depth| {
+            Ihdr::new(1, 1, bit_depth, color_type, Interlace::None).unwrap()
+        };
+        assert!(sbit.check(&ihdr(ColorType::Rgb, 8)).is_ok());
+        assert!(sbit.check(&ihdr(ColorType::Indexed, 1)).is_ok());
+        assert!(sbit.check(&ihdr(ColorType::Grayscale, 8)).is_err());
+        let gray = SignificantBits::new([3]).unwrap();
+        assert!(gray.check(&ihdr(ColorType::Grayscale, 2)).is_err());
+        assert!(SignificantBits::new([0]).is_err());
+    }
+
+    #[test]
+    fn test_offset() {
+        let offset = Offset::new(-10, 20, OffsetUnit::Micrometer);
+        assert_eq!(Offset::try_from(&offset.to_chunk()).unwrap(), offset);
+        assert_eq!(offset.to_string(), "(-10, 20) µm");
+    }
+
+    #[test]
+    fn test_pixel_calibration() {
+        let pcal =
+            PixelCalibration::new("height", 0, 1000, Equation::Linear, "m", [-10.0, 0.5]).unwrap();
+        let chunk = pcal.to_chunk();
+        assert_eq!(chunk.data().len(), 7 + 10 + 1 + 4 + 4);
+        assert_eq!(PixelCalibration::try_from(&chunk).unwrap(), pcal);
+        assert_eq!(pcal.physical_value(0, 16), -10.0);
+        assert_eq!(pcal.physical_value(255, 8), -9.5);
+
+        assert!(PixelCalibration::new("height", 0, 0, Equation::Linear, "m", [0.0, 1.0]).is_err());
+        assert!(PixelCalibration::new("height", 0, 1, Equation::Hyperbolic, "", [0.0]).is_err());
+        let mut data = chunk.data().to_vec();
+        data[16] = 3;
+        assert!(PixelCalibration::try_from(&Chunk::new(ChunkType::PCAL, data)).is_err());
+    }
+
+    #[test]
+    fn test_physical_scale() {
+        let scal = PhysicalScale::new(0.000_25, 1.5, ScaleUnit::Meter).unwrap();
+        let chunk = scal.to_chunk();
+        assert_eq!(chunk.data(), b"\x010.00025\x001.5");
+        assert_eq!(PhysicalScale::try_from(&chunk).unwrap(), scal);
+        assert_eq!(scal.to_string(), "0.00025 x 1.5 m per pixel");
+
+        let parse = |data: &[u8]| PhysicalScale::try_from(&Chunk::new(ChunkType::SCAL, data));
+        assert_eq!(parse(b"\x022.5e-3\x001").unwrap().width(), 0.0025);
+        assert!(parse(b"\x01inf\x001").is_err());
+        assert!(parse(b"\x01-1\x001").is_err());
+        assert!(parse(b"\x011").is_err());
+        assert!(PhysicalScale::new(0.0, 1.0, ScaleUnit::Radian).is_err());
+    }
+}
@@ -1,5 +1,32 @@
-use crate::{chunk::Chunk, Error};
+//! PNG files as a signature followed by a list of chunks.
 
+use std::io::{Read, Write};
+
+use crate::{
+    apng::Animation,
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    codec::{self, ChunkCodec},
+    color::{
+        Chromaticities, Cicp, ContentLightLevel, Gamma, IccProfile, MasteringDisplay,
+        RenderingIntent,
+    },
+    ed25519::SigningKey,
+    exif::Exif,
+    ihdr::Ihdr,
+    image::{self, Image},
+    palette::{Background, Histogram, Palette, SuggestedPalette, Transparency},
+    physical::{Offset, PhysicalDimensions, PhysicalScale, PixelCalibration, SignificantBits},
+    reader::PngReader,
+    signing::ChunkSignature,
+    text::TextChunk,
+    time::Timestamp,
+    validate::{self, Violation},
+    writer::PngWriter,
+    Error,
+};
+
+/// A PNG file: the 8-byte signature followed by its chunks in file order.
 #[derive(Debug)]
 pub struct Png {
     header: [u8; 8],
@@ -10,36 +37,55 @@ impl TryFrom<&[u8]> for Png {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if value.len() < 20 {
-            return Err("Invalid PNG length".into());
+        let png = Self::read_from(value)?;
+        if png.chunks.is_empty() {
+            return Err(Error::Truncated {
+                offset: 8,
+                needed: 12,
+            });
         }
-        let header = value.get(..8).ok_or("Invalid PNG length: PNG header")?;
-        if header != Self::STANDARD_HEADER {
-            return Err("Invalid PNG header".into());
-        }
-        let mut chunks = vec![];
-
-        let mut i = 8;
-        while i < value.len() {
-            let offset = 12
-                + u32::from_be_bytes(
-                    value
-                        .get(i..i + 4)
-                        .ok_or("Invalid PNG length: chunk length")?
-                        .try_into()?,
-                ) as usize;
-            chunks.push(Chunk::try_from(
-                value
-                    .get(i..i + offset)
-                    .ok_or("Invalid PNG length: chunk")?,
-            )?);
-            i += offset;
+        Ok(png)
+    }
+}
+
+/// Where [`Png::insert_chunk`] places a new chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Placement {
+    /// Directly after the `IHDR` chunk.
+    AfterIhdr,
+    /// Directly before the first `IDAT` chunk, or before the `fcTL` chunk that introduces it in
+    /// an animated PNG.
+    BeforeIdat,
+    /// Directly before the `IEND` chunk, or at the end if there is none.
+    #[default]
+    BeforeIend,
+}
+
+impl std::str::FromStr for Placement {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "after-ihdr" => Ok(Self::AfterIhdr),
+            "before-idat" => Ok(Self::BeforeIdat),
+            "before-iend" => Ok(Self::BeforeIend),
+            _ => Err(Error::InvalidPlacement(s.to_owned())),
         }
+    }
+}
 
-        Ok(Self {
-            header: header.try_into()?,
-            chunks,
-        })
+impl Placement {
+    /// Whether a chunk with this placement goes between `previous` and `next` when chunks are
+    /// written in order.
+    pub fn is_between(self, previous: Option<&ChunkType>, next: &ChunkType) -> bool {
+        match self {
+            Self::AfterIhdr => previous == Some(&ChunkType::IHDR),
+            Self::BeforeIdat => {
+                *next == ChunkType::FCTL
+                    || (*next == ChunkType::IDAT && previous != Some(&ChunkType::FCTL))
+            }
+            Self::BeforeIend => *next == ChunkType::IEND,
+        }
     }
 }
 
@@ -50,41 +96,442 @@ impl std::fmt::Display for Png {
 }
 
 impl Png {
+    /// The 8-byte signature every PNG file starts with.
     pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
 
+    /// Creates a PNG with the standard signature from a list of chunks.
     pub fn from_chunks(chunks: impl AsRef<[Chunk]>) -> Self {
         Self {
             header: Self::STANDARD_HEADER,
             chunks: chunks.as_ref().to_vec(),
         }
     }
+    /// Reads a whole PNG from `reader`. See [`PngReader`] to process one chunk at a time.
+    pub fn read_from(reader: impl Read) -> Result<Self, Error> {
+        Ok(Self {
+            header: Self::STANDARD_HEADER,
+            chunks: PngReader::new(reader)?.collect::<Result<_, _>>()?,
+        })
+    }
+    /// Reads a whole PNG from `reader`, failing with [`Error::Invalid`] if it breaks the
+    /// specification's chunk ordering or multiplicity rules.
+    pub fn read_strict(reader: impl Read) -> Result<Self, Error> {
+        let png = Self::read_from(reader)?;
+        let violations = png.validate();
+        if violations.is_empty() {
+            Ok(png)
+        } else {
+            Err(Error::Invalid(violations))
+        }
+    }
+    /// Checks the chunks against the specification's ordering and multiplicity rules, returning
+    /// every violation found.
+    pub fn validate(&self) -> Vec<Violation> {
+        validate::validate(&self.chunks)
+    }
+    /// Appends a chunk after the last chunk, which may put it after `IEND`.
     pub fn append_chunk(&mut self, chunk: Chunk) {
         self.chunks.push(chunk);
     }
+    /// Inserts a chunk at `index` in the chunk list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > self.chunks().len()`.
+    pub fn insert_at(&mut self, index: usize, chunk: Chunk) {
+        self.chunks.insert(index, chunk);
+    }
+    /// Inserts a chunk directly after the first chunk with the given type.
+    pub fn insert_after(&mut self, chunk_type: &str, chunk: Chunk) -> Result<(), Error> {
+        let index = self.position(chunk_type)?;
+        self.chunks.insert(index + 1, chunk);
+        Ok(())
+    }
+    /// Inserts a chunk directly before the first chunk with the given type.
+    pub fn insert_before(&mut self, chunk_type: &str, chunk: Chunk) -> Result<(), Error> {
+        let index = self.position(chunk_type)?;
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+    /// Inserts a chunk directly before `IEND`, or appends it if there is no `IEND`.
+    pub fn insert_before_iend(&mut self, chunk: Chunk) {
+        match self.position("IEND") {
+            Ok(index) => self.chunks.insert(index, chunk),
+            Err(_) => self.chunks.push(chunk),
+        }
+    }
+    /// Inserts a chunk at a spec-legal position for ancillary chunks. In animated PNGs, the
+    /// chunk never separates an `fcTL` chunk from its frame data.
+    pub fn insert_chunk(&mut self, chunk: Chunk, placement: Placement) -> Result<(), Error> {
+        match placement {
+            Placement::AfterIhdr => self.insert_after("IHDR", chunk),
+            Placement::BeforeIdat => {
+                let mut index = self.position("IDAT")?;
+                if index > 0 && *self.chunks[index - 1].r#type() == ChunkType::FCTL {
+                    index -= 1;
+                }
+                self.chunks.insert(index, chunk);
+                Ok(())
+            }
+            Placement::BeforeIend => {
+                self.insert_before_iend(chunk);
+                Ok(())
+            }
+        }
+    }
+    /// Removes and returns the first chunk with the given type.
     pub fn remove_chunk(&mut self, chunk_type: &str) -> Result<Chunk, Error> {
-        Ok(self.chunks.remove(
-            self.chunks
-                .iter()
-                .position(|c| c.r#type().to_string() == chunk_type)
-                .ok_or("No matching chunk type")?,
-        ))
+        let index = self.position(chunk_type)?;
+        Ok(self.chunks.remove(index))
     }
+    /// The file signature.
     pub const fn header(&self) -> &[u8; 8] {
         &self.header
     }
+    /// All chunks in file order.
     pub fn chunks(&self) -> &[Chunk] {
         &self.chunks
     }
+    /// The parsed image header from the first chunk.
+    pub fn ihdr(&self) -> Result<Ihdr, Error> {
+        match self.chunks.first() {
+            Some(chunk) => Ihdr::try_from(chunk),
+            None => Err(Error::ChunkNotFound(ChunkType::IHDR.to_string())),
+        }
+    }
+    /// The concatenated `IDAT` data, decompressed. Fails if it decompresses to more or less than
+    /// the size the image header implies.
+    pub fn image_data(&self) -> Result<Vec<u8>, Error> {
+        image::decompress(&self.ihdr()?, &self.compressed_data()?)
+    }
+    /// Decodes the image data into unfiltered pixel rows, de-interlacing Adam7 images. Indexed
+    /// images are expanded to 8-bit RGB through `PLTE`, or to RGBA when a `tRNS` chunk is present.
+    pub fn decode_image(&self) -> Result<Image, Error> {
+        image::decode(
+            &self.ihdr()?,
+            &self.compressed_data()?,
+            self.find(ChunkType::PLTE).map(Chunk::data),
+            self.find(ChunkType::TRNS).map(Chunk::data),
+        )
+    }
+    /// Decodes the first chunk of `T`'s chunk type, if there is one.
+    pub fn decode_chunk<T: ChunkCodec>(&self) -> Result<Option<T>, Error> {
+        self.find(T::CHUNK_TYPE)
+            .map(|chunk| T::decode(chunk.data()))
+            .transpose()
+    }
+    /// Replaces the first chunk of `T`'s chunk type with `value`, or inserts it before `IDAT`.
+    /// Fails with [`Error::StandardChunk`] for public chunk types: replacing image data blindly
+    /// corrupts the image, and typed setters keep dependent chunks such as `tRNS` consistent.
+    pub fn encode_chunk<T: ChunkCodec>(&mut self, value: &T) -> Result<(), Error> {
+        if T::CHUNK_TYPE.is_public() {
+            return Err(Error::StandardChunk(T::CHUNK_TYPE));
+        }
+        self.put(codec::to_chunk(value), &[])
+    }
+    /// Every `siGN` signature, in file order. Use [`ChunkSignature::verify`] with
+    /// [`Self::chunks`] to check one.
+    pub fn signatures(&self) -> Result<Vec<ChunkSignature>, Error> {
+        self.chunks
+            .iter()
+            .filter(|c| *c.r#type() == ChunkSignature::CHUNK_TYPE)
+            .map(|c| ChunkSignature::decode(c.data()))
+            .collect()
+    }
+    /// Signs the critical and [`IMAGE_CHUNKS`](crate::signing::IMAGE_CHUNKS) chunks and every
+    /// chunk of the `extra` types with `key`. The signature goes before `IEND`, replacing an
+    /// earlier signature by the same key.
+    pub fn sign(&mut self, key: &SigningKey, extra: &[ChunkType]) -> Result<ChunkSignature, Error> {
+        let signature = ChunkSignature::sign(key, &self.chunks, extra)?;
+        let public_key = signature.public_key();
+        // Signatures this crate cannot read, say in a newer format, belong to someone else.
+        self.chunks.retain(|c| {
+            *c.r#type() != ChunkSignature::CHUNK_TYPE
+                || !ChunkSignature::decode(c.data()).is_ok_and(|s| s.public_key() == public_key)
+        });
+        self.insert_before_iend(codec::to_chunk(&signature));
+        Ok(signature)
+    }
+    /// The animation of an APNG with its frames in order, or `None` for a still image.
+    pub fn animation(&self) -> Result<Option<Animation>, Error> {
+        Animation::from_chunks(&self.chunks)
+    }
+    /// Decodes every frame of an APNG and composites it onto the canvas, returning the fully
+    /// rendered canvas for each frame as RGBA. A still image yields one frame. Samples are 16-bit
+    /// for 16-bit images and 8-bit otherwise.
+    pub fn frames(&self) -> Result<Vec<Image>, Error> {
+        let ihdr = self.ihdr()?;
+        let plte = self.find(ChunkType::PLTE).map(Chunk::data);
+        let trns = self.find(ChunkType::TRNS).map(Chunk::data);
+        let transparency = self.transparency()?;
+        let decode = |ihdr: &Ihdr, data: &[u8]| {
+            image::decode(ihdr, data, plte, trns)?.to_rgba(transparency.as_ref())
+        };
+        match self.animation()? {
+            Some(animation) => animation.composite(&ihdr, decode),
+            None => Ok(vec![decode(&ihdr, &self.compressed_data()?)?]),
+        }
+    }
+    /// The parsed `eXIf` chunk, if there is one.
+    pub fn exif(&self) -> Result<Option<Exif>, Error> {
+        self.parse_first(ChunkType::EXIF)
+    }
+    /// The parsed `iCCP` chunk, if there is one.
+    pub fn icc_profile(&self) -> Result<Option<IccProfile>, Error> {
+        self.parse_first(ChunkType::ICCP)
+    }
+    /// The rendering intent from the `sRGB` chunk, if there is one.
+    pub fn srgb(&self) -> Result<Option<RenderingIntent>, Error> {
+        self.parse_first(ChunkType::SRGB)
+    }
+    /// The parsed `gAMA` chunk, if there is one.
+    pub fn gamma(&self) -> Result<Option<Gamma>, Error> {
+        self.parse_first(ChunkType::GAMA)
+    }
+    /// The parsed `cHRM` chunk, if there is one.
+    pub fn chromaticities(&self) -> Result<Option<Chromaticities>, Error> {
+        self.parse_first(ChunkType::CHRM)
+    }
+    /// The parsed `cICP` chunk, if there is one.
+    pub fn cicp(&self) -> Result<Option<Cicp>, Error> {
+        self.parse_first(ChunkType::CICP)
+    }
+    /// The parsed `mDCV` chunk, if there is one.
+    pub fn mastering_display(&self) -> Result<Option<MasteringDisplay>, Error> {
+        self.parse_first(ChunkType::MDCV)
+    }
+    /// The parsed `cLLI` chunk, if there is one.
+    pub fn content_light_level(&self) -> Result<Option<ContentLightLevel>, Error> {
+        self.parse_first(ChunkType::CLLI)
+    }
+    /// The parsed `PLTE` chunk, if there is one.
+    pub fn palette(&self) -> Result<Option<Palette>, Error> {
+        self.parse_first(ChunkType::PLTE)
+    }
+    /// The parsed `tRNS` chunk, laid out for the `IHDR` color type, if there is one.
+    pub fn transparency(&self) -> Result<Option<Transparency>, Error> {
+        match self.find(ChunkType::TRNS) {
+            Some(chunk) => Transparency::from_chunk(chunk, self.ihdr()?.color_type()).map(Some),
+            None => Ok(None),
+        }
+    }
+    /// The parsed `bKGD` chunk, laid out for the `IHDR` color type, if there is one.
+    pub fn background(&self) -> Result<Option<Background>, Error> {
+        match self.find(ChunkType::BKGD) {
+            Some(chunk) => Background::from_chunk(chunk, self.ihdr()?.color_type()).map(Some),
+            None => Ok(None),
+        }
+    }
+    /// The parsed `hIST` chunk, if there is one.
+    pub fn histogram(&self) -> Result<Option<Histogram>, Error> {
+        self.parse_first(ChunkType::HIST)
+    }
+    /// Every `sPLT` chunk, parsed, in file order.
+    pub fn suggested_palettes(&self) -> Result<Vec<SuggestedPalette>, Error> {
+        self.chunks
+            .iter()
+            .filter(|c| *c.r#type() == ChunkType::SPLT)
+            .map(SuggestedPalette::try_from)
+            .collect()
+    }
+    /// Replaces or inserts the `PLTE` chunk after checking it against `IHDR`.
+    ///
+    /// Dependent chunks are kept consistent: `tRNS` alpha values beyond the new palette are
+    /// dropped, and a `bKGD` index outside it or a `hIST` of a different size is removed.
+    pub fn set_palette(&mut self, palette: Palette) -> Result<(), Error> {
+        palette.check(&self.ihdr()?)?;
+        if let Some(Transparency::Alpha(mut alpha)) = self.transparency()? {
+            if alpha.len() > palette.len() {
+                alpha.truncate(palette.len());
+                self.put(Transparency::Alpha(alpha).to_chunk(), &[])?;
+            }
+        }
+        if let Some(Background::Index(index)) = self.background()? {
+            if index as usize >= palette.len() {
+                self.remove(ChunkType::BKGD);
+            }
+        }
+        if self.find(ChunkType::HIST).is_some()
+            && self.histogram()?.map(|h| h.frequencies().len()) != Some(palette.len())
+        {
+            self.remove(ChunkType::HIST);
+        }
+        self.put(
+            palette.to_chunk(),
+            &[ChunkType::TRNS, ChunkType::BKGD, ChunkType::HIST],
+        )
+    }
+    /// Replaces or inserts the `tRNS` chunk after checking it against `IHDR` and `PLTE`.
+    pub fn set_transparency(&mut self, transparency: Transparency) -> Result<(), Error> {
+        transparency.check(&self.ihdr()?, self.palette()?.as_ref())?;
+        self.put(transparency.to_chunk(), &[])
+    }
+    /// Replaces or inserts the `bKGD` chunk after checking it against `IHDR` and `PLTE`.
+    pub fn set_background(&mut self, background: Background) -> Result<(), Error> {
+        background.check(&self.ihdr()?, self.palette()?.as_ref())?;
+        self.put(background.to_chunk(), &[])
+    }
+    /// Replaces or inserts the `hIST` chunk after checking it has one entry per `PLTE` entry.
+    pub fn set_histogram(&mut self, histogram: Histogram) -> Result<(), Error> {
+        let palette = self
+            .palette()?
+            .ok_or_else(|| Error::ChunkNotFound(ChunkType::PLTE.to_string()))?;
+        histogram.check(&palette)?;
+        self.put(histogram.to_chunk(), &[])
+    }
+    /// Inserts an `sPLT` chunk before the image data, replacing the one with the same name if
+    /// there is one.
+    pub fn add_suggested_palette(&mut self, palette: SuggestedPalette) -> Result<(), Error> {
+        let existing = self.chunks.iter().position(|c| {
+            *c.r#type() == ChunkType::SPLT
+                && SuggestedPalette::try_from(c).is_ok_and(|p| p.name() == palette.name())
+        });
+        match existing {
+            Some(index) => self.chunks[index] = palette.to_chunk(),
+            None => self.insert_chunk(palette.to_chunk(), Placement::BeforeIdat)?,
+        }
+        Ok(())
+    }
+    /// The parsed `pHYs` chunk, if there is one.
+    pub fn physical_dimensions(&self) -> Result<Option<PhysicalDimensions>, Error> {
+        self.parse_first(ChunkType::PHYS)
+    }
+    /// Replaces or inserts the `pHYs` chunk.
+    pub fn set_physical_dimensions(&mut self, phys: PhysicalDimensions) -> Result<(), Error> {
+        self.put(phys.to_chunk(), &[])
+    }
+    /// The last-modification time from the `tIME` chunk, if there is one.
+    pub fn modified(&self) -> Result<Option<Timestamp>, Error> {
+        self.parse_first(ChunkType::TIME)
+    }
+    /// Replaces or inserts the `tIME` chunk.
+    pub fn set_modified(&mut self, time: Timestamp) -> Result<(), Error> {
+        self.put(time.to_chunk(), &[])
+    }
+    /// The parsed `sBIT` chunk, laid out for the `IHDR` color type, if there is one.
+    pub fn significant_bits(&self) -> Result<Option<SignificantBits>, Error> {
+        match self.find(ChunkType::SBIT) {
+            Some(chunk) => SignificantBits::from_chunk(chunk, self.ihdr()?.color_type()).map(Some),
+            None => Ok(None),
+        }
+    }
+    /// Replaces or inserts the `sBIT` chunk after checking it against `IHDR`.
+    pub fn set_significant_bits(&mut self, sbit: SignificantBits) -> Result<(), Error> {
+        sbit.check(&self.ihdr()?)?;
+        self.put(sbit.to_chunk(), &[ChunkType::PLTE])
+    }
+    /// The parsed `oFFs` chunk, if there is one.
+    pub fn offset(&self) -> Result<Option<Offset>, Error> {
+        self.parse_first(ChunkType::OFFS)
+    }
+    /// Replaces or inserts the `oFFs` chunk.
+    pub fn set_offset(&mut self, offset: Offset) -> Result<(), Error> {
+        self.put(offset.to_chunk(), &[])
+    }
+    /// The parsed `pCAL` chunk, if there is one.
+    pub fn pixel_calibration(&self) -> Result<Option<PixelCalibration>, Error> {
+        self.parse_first(ChunkType::PCAL)
+    }
+    /// Replaces or inserts the `pCAL` chunk.
+    pub fn set_pixel_calibration(&mut self, pcal: PixelCalibration) -> Result<(), Error> {
+        self.put(pcal.to_chunk(), &[])
+    }
+    /// The parsed `sCAL` chunk, if there is one.
+    pub fn physical_scale(&self) -> Result<Option<PhysicalScale>, Error> {
+        self.parse_first(ChunkType::SCAL)
+    }
+    /// Replaces or inserts the `sCAL` chunk.
+    pub fn set_physical_scale(&mut self, scal: PhysicalScale) -> Result<(), Error> {
+        self.put(scal.to_chunk(), &[])
+    }
+    /// Every `tEXt`, `zTXt` and `iTXt` entry in file order.
+    pub fn text_entries(&self) -> Result<Vec<TextChunk>, Error> {
+        self.chunks
+            .iter()
+            .filter(|c| TextChunk::CHUNK_TYPES.contains(c.r#type()))
+            .map(TextChunk::try_from)
+            .collect()
+    }
+    /// The first chunk with the given type, if any.
     pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
         self.chunks
             .iter()
             .find(|c| c.r#type().to_string() == chunk_type)
     }
+    /// Serializes the PNG as it appears in a file.
     pub fn bytes(&self) -> Vec<u8> {
-        self.header
-            .into_iter()
-            .chain(self.chunks.iter().flat_map(Chunk::bytes))
-            .collect()
+        let len = self
+            .chunks
+            .iter()
+            .map(|c| c.data().len() + 12)
+            .sum::<usize>()
+            + 8;
+        let mut bytes = Vec::with_capacity(len);
+        bytes.extend_from_slice(&self.header);
+        for chunk in &self.chunks {
+            bytes.extend_from_slice(&chunk.bytes());
+        }
+        bytes
+    }
+    /// Writes the PNG to `writer`. See [`PngWriter`] to write one chunk at a time.
+    pub fn write_to(&self, writer: impl Write) -> Result<(), Error> {
+        let mut writer = PngWriter::new(writer)?;
+        for chunk in &self.chunks {
+            writer.write_chunk(chunk)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    fn find(&self, chunk_type: ChunkType) -> Option<&Chunk> {
+        self.chunks.iter().find(|c| *c.r#type() == chunk_type)
+    }
+    /// Parses the first chunk of `chunk_type`, if any, as `T`.
+    fn parse_first<'a, T>(&'a self, chunk_type: ChunkType) -> Result<Option<T>, Error>
+    where
+        T: TryFrom<&'a Chunk, Error = Error>,
+    {
+        self.find(chunk_type).map(T::try_from).transpose()
+    }
+    /// Replaces the first chunk of the same type as `chunk`, or inserts it before the first
+    /// chunk whose type is in `before`, or else before `IDAT`.
+    fn put(&mut self, chunk: Chunk, before: &[ChunkType]) -> Result<(), Error> {
+        if let Some(index) = self
+            .chunks
+            .iter()
+            .position(|c| c.r#type() == chunk.r#type())
+        {
+            self.chunks[index] = chunk;
+            return Ok(());
+        }
+        let index = self
+            .chunks
+            .iter()
+            .position(|c| *c.r#type() == ChunkType::IDAT || before.contains(c.r#type()))
+            .ok_or_else(|| Error::ChunkNotFound(ChunkType::IDAT.to_string()))?;
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+    fn remove(&mut self, chunk_type: ChunkType) {
+        self.chunks.retain(|c| *c.r#type() != chunk_type);
+    }
+    fn compressed_data(&self) -> Result<Vec<u8>, Error> {
+        let compressed = self
+            .chunks
+            .iter()
+            .filter(|c| *c.r#type() == ChunkType::IDAT)
+            .flat_map(|c| c.data().iter().copied())
+            .collect::<Vec<_>>();
+        if compressed.is_empty() {
+            return Err(Error::ChunkNotFound(ChunkType::IDAT.to_string()));
+        }
+        Ok(compressed)
+    }
+    fn position(&self, chunk_type: &str) -> Result<usize, Error> {
+        self.chunks
+            .iter()
+            .position(|c| c.r#type().to_string() == chunk_type)
+            .ok_or_else(|| Error::ChunkNotFound(chunk_type.to_owned()))
     }
 }
 
@@ -93,7 +540,7 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
-    use crate::chunk_type::ChunkType;
+    use crate::ihdr::ColorType;
 
     fn testing_chunks() -> Vec<Chunk> {
         vec![
@@ -108,6 +555,13 @@ mod tests {
         Png::from_chunks(chunks)
     }
 
+    fn chunk_types(png: &Png) -> Vec<String> {
+        png.chunks()
+            .iter()
+            .map(|c| c.r#type().to_string())
+            .collect()
+    }
+
     fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk, Error> {
         let chunk_type = ChunkType::from_str(chunk_type)?;
         let data: Vec<u8> = data.bytes().collect();
@@ -181,6 +635,40 @@ mod tests {
         assert!(png.is_err());
     }
 
+    #[test]
+    fn test_invalid_signature_error() {
+        let err = Png::try_from(&[13, 80, 78, 71][..]).unwrap_err();
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    #[test]
+    fn test_truncated_png_error() {
+        let err = Png::try_from(&PNG_FILE[..PNG_FILE.len() - 3]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Truncated {
+                offset: 4791,
+                needed: 12
+            }
+        ));
+    }
+
+    #[test]
+    fn test_crc_mismatch_error_offset() {
+        let mut bytes = PNG_FILE;
+        bytes[29] ^= 1;
+        let err = Png::try_from(&bytes[..]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::CrcMismatch {
+                offset: 29,
+                stored: 0x1f3f88b1,
+                computed: 0x1e3f88b1,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_list_chunks() {
         let png = testing_png();
@@ -214,6 +702,72 @@ mod tests {
         assert!(chunk.is_none());
     }
 
+    #[test]
+    fn test_insert_before_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_before_iend(chunk_from_strings("TeSt", "Message").unwrap());
+        let types = chunk_types(&png);
+        assert_eq!(&types[types.len() - 2..], ["TeSt", "IEND"]);
+    }
+
+    #[test]
+    fn test_insert_before_iend_without_iend() {
+        let mut png = testing_png();
+        png.insert_before_iend(chunk_from_strings("TeSt", "Message").unwrap());
+        assert_eq!(chunk_types(&png), ["FrSt", "miDl", "LASt", "TeSt"]);
+    }
+
+    #[test]
+    fn test_insert_after() {
+        let mut png = testing_png();
+        png.insert_after("FrSt", chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+        assert_eq!(chunk_types(&png), ["FrSt", "TeSt", "miDl", "LASt"]);
+
+        let err = png
+            .insert_after("NoPe", chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap_err();
+        assert!(matches!(err, Error::ChunkNotFound(_)));
+    }
+
+    #[test]
+    fn test_insert_at() {
+        let mut png = testing_png();
+        png.insert_at(0, chunk_from_strings("TeSt", "Message").unwrap());
+        assert_eq!(chunk_types(&png), ["TeSt", "FrSt", "miDl", "LASt"]);
+    }
+
+    #[test]
+    fn test_insert_chunk_placement() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_chunk(
+            chunk_from_strings("ihDr", "Message").unwrap(),
+            Placement::AfterIhdr,
+        )
+        .unwrap();
+        png.insert_chunk(
+            chunk_from_strings("idAt", "Message").unwrap(),
+            Placement::BeforeIdat,
+        )
+        .unwrap();
+        assert_eq!(
+            chunk_types(&png),
+            ["IHDR", "ihDr", "sRGB", "gAMA", "pHYs", "idAt", "IDAT", "RuSt", "IEND"]
+        );
+        assert_eq!(
+            Placement::from_str("before-iend").unwrap(),
+            Placement::BeforeIend
+        );
+        assert!(Placement::from_str("after-iend").is_err());
+    }
+
+    #[test]
+    fn test_remove_missing_chunk() {
+        let mut png = testing_png();
+        let err = png.remove_chunk("TeSt").unwrap_err();
+        assert!(matches!(err, Error::ChunkNotFound(ref t) if t == "TeSt"));
+    }
+
     #[test]
     fn test_png_from_image_file() {
         let png = Png::try_from(&PNG_FILE[..]);
@@ -228,6 +782,369 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_ihdr() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let ihdr = png.ihdr().unwrap();
+        assert_eq!((ihdr.width(), ihdr.height()), (50, 50));
+        assert_eq!(ihdr.color_type(), crate::ihdr::ColorType::Rgba);
+
+        assert!(matches!(testing_png().ihdr(), Err(Error::ChunkNotFound(_))));
+    }
+
+    #[test]
+    fn test_validate() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let violations = png.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].index(), Some(5));
+
+        let err = Png::read_strict(&PNG_FILE[..]).unwrap_err();
+        assert!(matches!(err, Error::Invalid(v) if v == violations));
+    }
+
+    #[test]
+    fn test_image_data() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let data = png.image_data().unwrap();
+        assert_eq!(data.len(), 50 * (1 + 50 * 4));
+        assert!(data.chunks(201).all(|row| row[0] <= 4));
+
+        // Data that inflates past the size implied by the header is rejected.
+        let ihdr = Ihdr::new(1, 1, 8, ColorType::Grayscale, crate::Interlace::None).unwrap();
+        let bomb = crate::zlib::compress(&[0; 1 << 20], Default::default());
+        let png = Png::from_chunks([ihdr.to_chunk(), Chunk::new(ChunkType::IDAT, bomb)]);
+        assert!(png.image_data().is_err());
+    }
+
+    #[test]
+    fn test_decode_image() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let image = png.decode_image().unwrap();
+        assert_eq!((image.width(), image.height()), (50, 50));
+        assert_eq!(image.color_type(), ColorType::Rgba);
+        assert_eq!(image.rows().count(), 50);
+        assert!(image.rows().all(|row| row.len() == 200));
+    }
+
+    #[test]
+    fn test_color_chunks() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(
+            png.srgb().unwrap(),
+            Some(crate::color::RenderingIntent::Perceptual)
+        );
+        assert_eq!(png.gamma().unwrap(), Some(crate::color::Gamma::SRGB));
+        assert!(png.chromaticities().unwrap().is_none());
+        assert!(png.icc_profile().unwrap().is_none());
+
+        let clli = crate::color::ContentLightLevel::new(1, 2);
+        png.insert_chunk(clli.to_chunk(), Placement::AfterIhdr)
+            .unwrap();
+        assert_eq!(png.content_light_level().unwrap(), Some(clli));
+        assert!(png.validate().iter().all(|v| v.index() != Some(1)));
+    }
+
+    #[test]
+    fn test_palette_editing() {
+        use crate::palette::{Background, Histogram, Palette, Transparency};
+
+        let mut png = crate::encoder::PngEncoder::new(2, 1, ColorType::Indexed, 2)
+            .unwrap()
+            .palette([0; 9])
+            .transparency([0, 1, 2])
+            .encode(&[0b0001_0000])
+            .unwrap();
+        png.set_background(Background::Index(2)).unwrap();
+        png.set_histogram(Histogram::new([1, 1, 0])).unwrap();
+        assert!(png.validate().is_empty());
+        assert!(png.set_background(Background::Index(3)).is_err());
+        assert!(png.set_background(Background::Gray(0)).is_err());
+        assert!(png.set_histogram(Histogram::new([1])).is_err());
+
+        png.set_palette(Palette::new([[255, 0, 0], [0, 0, 255]]).unwrap())
+            .unwrap();
+        assert!(png.validate().is_empty());
+        assert_eq!(png.palette().unwrap().unwrap().len(), 2);
+        assert_eq!(
+            png.transparency().unwrap(),
+            Some(Transparency::Alpha(vec![0, 1]))
+        );
+        assert!(png.background().unwrap().is_none());
+        assert!(png.histogram().unwrap().is_none());
+        assert!(png.set_palette(Palette::new([[0; 3]; 5]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_physical_and_time_chunks() {
+        use crate::{physical::SignificantBits, time::Timestamp};
+
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let time = Timestamp::new(2024, 5, 1, 12, 0, 0).unwrap();
+        png.set_modified(time).unwrap();
+        png.set_modified(time).unwrap();
+        assert_eq!(png.modified().unwrap(), Some(time));
+
+        png.set_physical_dimensions(PhysicalDimensions::from_dpi(300.0, 300.0))
+            .unwrap();
+        let (dpi, _) = png.physical_dimensions().unwrap().unwrap().dpi().unwrap();
+        assert!((dpi - 300.0).abs() < 0.01);
+
+        png.set_significant_bits(SignificantBits::new([5, 6, 5, 8]).unwrap())
+            .unwrap();
+        assert!(png
+            .set_significant_bits(SignificantBits::new([5, 6, 5]).unwrap())
+            .is_err());
+        assert_eq!(
+            png.significant_bits().unwrap().unwrap().bits(),
+            [5, 6, 5, 8]
+        );
+        // The fixture's only violation is its unknown critical chunk.
+        assert_eq!(png.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_codec_chunks() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.decode_chunk::<ChunkSignature>().unwrap().is_none());
+        for seed in [1, 2] {
+            let key = SigningKey::from_bytes([seed; 32]);
+            let signature = ChunkSignature::sign(&key, png.chunks(), &[]).unwrap();
+            png.encode_chunk(&signature).unwrap();
+        }
+        let count = |t| png.chunks().iter().filter(|c| *c.r#type() == t).count();
+        assert_eq!(count(ChunkSignature::CHUNK_TYPE), 1);
+        assert_eq!(
+            png.decode_chunk::<ChunkSignature>().unwrap().unwrap(),
+            png.signatures().unwrap()[0]
+        );
+
+        // Standard chunks go through their typed setters.
+        assert!(matches!(
+            png.encode_chunk(&ContentLightLevel::new(4000, 400)),
+            Err(Error::StandardChunk(ChunkType::CLLI))
+        ));
+        // So do public chunk types without a built-in codec, even from a user codec.
+        struct Raw<const T: u32>;
+        impl<const T: u32> ChunkCodec for Raw<T> {
+            const CHUNK_TYPE: ChunkType = ChunkType::new(T.to_be_bytes());
+
+            fn decode(_: &[u8]) -> Result<Self, Error> {
+                Ok(Self)
+            }
+            fn encode(&self) -> Vec<u8> {
+                vec![]
+            }
+        }
+        let before = png.bytes();
+        assert!(matches!(
+            png.encode_chunk(&Raw::<{ u32::from_be_bytes(*b"IDAT") }>),
+            Err(Error::StandardChunk(ChunkType::IDAT))
+        ));
+        assert!(png
+            .encode_chunk(&Raw::<{ u32::from_be_bytes(*b"tRNS") }>)
+            .is_err());
+        assert_eq!(png.bytes(), before);
+        assert_eq!(png.decode_chunk::<Gamma>().unwrap(), png.gamma().unwrap());
+        assert_eq!(
+            png.decode_chunk::<Ihdr>().unwrap().unwrap().width(),
+            png.ihdr().unwrap().width()
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_before_iend(Chunk::new(ChunkType::from_str("ruSt").unwrap(), "message"));
+        let alice = SigningKey::from_bytes([1; 32]);
+        let bob = SigningKey::from_bytes([2; 32]);
+        png.sign(&alice, &[]).unwrap();
+        png.sign(&bob, &[ChunkType::from_str("ruSt").unwrap()])
+            .unwrap();
+        png.sign(&alice, &[]).unwrap();
+
+        let signatures = png.signatures().unwrap();
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0].public_key(), &bob.verifying_key());
+        assert_eq!(chunk_types(&png).last().unwrap(), "IEND");
+        for signature in &signatures {
+            signature.verify(png.chunks()).unwrap();
+        }
+        // The fixture's unknown critical chunk is covered like the standard ones.
+        assert!(signatures[1]
+            .covered()
+            .contains(&ChunkType::from_str("RuSt").unwrap()));
+
+        // Re-signing keeps signatures in a format this crate cannot read.
+        png.insert_before_iend(Chunk::new(ChunkSignature::CHUNK_TYPE, [2; 101]));
+        png.sign(&alice, &[]).unwrap();
+        let count = chunk_types(&png).iter().filter(|t| *t == "siGN").count();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_exif() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.exif().unwrap().is_none());
+        png.insert_before("IDAT", Chunk::new(ChunkType::EXIF, b"MM\0*\0\0\0\x08\0\0"))
+            .unwrap();
+        assert!(png.exif().unwrap().unwrap().entries().is_empty());
+        png.remove_chunk("eXIf").unwrap();
+        png.insert_before("IDAT", Chunk::new(ChunkType::EXIF, b"MM"))
+            .unwrap();
+        assert!(png.exif().is_err());
+    }
+
+    #[test]
+    fn test_text_entries() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.text_entries().unwrap().is_empty());
+        let entry = TextChunk::new("Comment", "hello").unwrap();
+        png.insert_before_iend(entry.clone().compressed().to_chunk());
+        png.insert_before_iend(TextChunk::new("Title", "dice").unwrap().to_chunk());
+        let entries = png.text_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].text(), "hello");
+        assert_eq!(entries[1].keyword(), "Title");
+    }
+
+    #[test]
+    fn test_write_to() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut bytes = vec![];
+        png.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes, PNG_FILE);
+    }
+
+    #[test]
+    fn test_placement_is_between() {
+        let ihdr = ChunkType::IHDR;
+        let idat = ChunkType::IDAT;
+        assert!(Placement::AfterIhdr.is_between(Some(&ihdr), &idat));
+        assert!(!Placement::AfterIhdr.is_between(None, &ihdr));
+        assert!(Placement::BeforeIdat.is_between(Some(&ihdr), &idat));
+        assert!(!Placement::BeforeIend.is_between(Some(&ihdr), &idat));
+        let fctl = ChunkType::FCTL;
+        assert!(Placement::BeforeIdat.is_between(Some(&ihdr), &fctl));
+        assert!(!Placement::BeforeIdat.is_between(Some(&fctl), &idat));
+    }
+
+    #[test]
+    fn test_animation() {
+        use crate::{
+            apng::{self, AnimationControl, BlendOp, DisposeOp, FrameControl},
+            encoder::PngEncoder,
+        };
+
+        let still = PngEncoder::new(2, 2, ColorType::Rgb, 8)
+            .unwrap()
+            .encode(&[0; 12])
+            .unwrap();
+        assert!(still.animation().unwrap().is_none());
+        let fctl = |sequence_number| {
+            FrameControl::new(
+                sequence_number,
+                2,
+                2,
+                0,
+                0,
+                1,
+                2,
+                DisposeOp::None,
+                BlendOp::Source,
+            )
+            .to_chunk()
+        };
+        let mut chunks = still.chunks().to_vec();
+        let iend = chunks.pop().unwrap();
+        let idat = chunks.pop().unwrap();
+        chunks.extend([
+            AnimationControl::new(2, 0).to_chunk(),
+            fctl(0),
+            idat.clone(),
+            fctl(1),
+            apng::frame_data_chunk(2, idat.data()),
+            iend,
+        ]);
+        let mut png = Png::from_chunks(chunks);
+        assert!(png.validate().is_empty());
+
+        png.insert_chunk(
+            chunk_from_strings("ruSt", "Message").unwrap(),
+            Placement::BeforeIdat,
+        )
+        .unwrap();
+        png.insert_chunk(
+            chunk_from_strings("ruSt", "Message").unwrap(),
+            Placement::BeforeIend,
+        )
+        .unwrap();
+        assert!(png.validate().is_empty());
+        assert_eq!(
+            chunk_types(&png),
+            ["IHDR", "acTL", "ruSt", "fcTL", "IDAT", "fcTL", "fdAT", "ruSt", "IEND"]
+        );
+        let animation = png.animation().unwrap().unwrap();
+        assert_eq!(animation.frames().len(), 2);
+        assert_eq!(animation.frames()[1].data(), idat.data());
+    }
+
+    #[test]
+    fn test_frames() {
+        use crate::{
+            apng::{self, AnimationControl, BlendOp, DisposeOp, FrameControl},
+            encoder::PngEncoder,
+        };
+
+        let encode = |size, data: &[u8]| {
+            PngEncoder::new(size, size, ColorType::Rgba, 8)
+                .unwrap()
+                .encode(data)
+                .unwrap()
+        };
+        let fctl = |sequence_number, size, offset, dispose_op, blend_op| {
+            FrameControl::new(
+                sequence_number,
+                size,
+                size,
+                offset,
+                offset,
+                1,
+                10,
+                dispose_op,
+                blend_op,
+            )
+            .to_chunk()
+        };
+        let red = [255, 0, 0, 255];
+        let still = encode(2, &red.repeat(4));
+        assert_eq!(still.frames().unwrap()[0].data(), red.repeat(4));
+
+        let mut chunks = still.chunks().to_vec();
+        let iend = chunks.pop().unwrap();
+        let idat = chunks.pop().unwrap();
+        let blue = encode(1, &[0, 0, 255, 128]).compressed_data().unwrap();
+        let green = encode(1, &[0, 255, 0, 255]).compressed_data().unwrap();
+        chunks.extend([
+            AnimationControl::new(3, 0).to_chunk(),
+            fctl(0, 2, 0, DisposeOp::None, BlendOp::Source),
+            idat,
+            fctl(1, 1, 1, DisposeOp::Previous, BlendOp::Over),
+            apng::frame_data_chunk(2, &blue),
+            fctl(3, 1, 0, DisposeOp::Background, BlendOp::Source),
+            apng::frame_data_chunk(4, &green),
+            iend,
+        ]);
+        let frames = Png::from_chunks(chunks).frames().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].data(), red.repeat(4));
+        assert_eq!(
+            frames[1].data(),
+            [red, red, red, [127, 0, 128, 255]].concat()
+        );
+        assert_eq!(frames[2].data(), [[0, 255, 0, 255], red, red, red].concat());
+    }
+
     #[test]
     fn test_png_trait_impls() {
         let chunk_bytes: Vec<u8> = testing_chunks()
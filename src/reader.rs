@@ -0,0 +1,343 @@
+//! Streaming PNG parsing over [`std::io::Read`].
+
+use std::io::{self, Read};
+
+use crate::{
+    chunk::{crc32, Chunk, Crc32},
+    chunk_type::ChunkType,
+    png::Png,
+    Error,
+};
+
+const SKIP_BUFFER_LEN: usize = 8 * 1024;
+
+/// The length and type of a chunk, read before its data.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ChunkHeader {
+    length: u32,
+    r#type: ChunkType,
+    offset: usize,
+}
+
+impl ChunkHeader {
+    /// Length of the chunk data in bytes.
+    pub const fn length(&self) -> u32 {
+        self.length
+    }
+    /// The chunk type code.
+    pub const fn r#type(&self) -> &ChunkType {
+        &self.r#type
+    }
+    /// Byte offset of the chunk from the start of the file.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Reads a PNG one chunk at a time, holding at most one chunk's data in memory.
+///
+/// Chunk types registered with [`PngReader::skip_data`] are read past without buffering their
+/// data, which keeps listing or extracting ancillary chunks cheap on large images. Skipped data
+/// still has its CRC checked.
+///
+/// ```
+/// # use pngme::{Chunk, ChunkType, Png, reader::PngReader};
+/// # use std::str::FromStr;
+/// # let mut png = Png::from_chunks([]);
+/// # png.append_chunk(Chunk::new(ChunkType::from_str("ruSt")?, "message"));
+/// # let bytes = png.bytes();
+/// let mut reader = PngReader::new(bytes.as_slice())?.skip_data(ChunkType::from_str("IDAT")?);
+/// while let Some(chunk) = reader.next_chunk()? {
+///     println!("{}", chunk.r#type());
+/// }
+/// # Ok::<(), pngme::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PngReader<R> {
+    inner: R,
+    offset: usize,
+    pending: Option<ChunkHeader>,
+    skipped: Vec<ChunkType>,
+    failed: bool,
+}
+
+impl<R: Read> Iterator for PngReader<R> {
+    type Item = Result<Chunk, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let next = self.next_chunk().transpose();
+        self.failed = matches!(next, Some(Err(_)));
+        next
+    }
+}
+
+impl<R: Read> PngReader<R> {
+    /// Reads and validates the PNG signature.
+    pub fn new(mut inner: R) -> Result<Self, Error> {
+        let mut signature = [0; 8];
+        let n = read_full(&mut inner, &mut signature)?;
+        if signature[..n] != Png::STANDARD_HEADER[..n] {
+            return Err(Error::InvalidSignature);
+        } else if n < signature.len() {
+            return Err(Error::Truncated {
+                offset: 0,
+                needed: signature.len(),
+            });
+        }
+
+        Ok(Self {
+            inner,
+            offset: signature.len(),
+            pending: None,
+            skipped: vec![],
+            failed: false,
+        })
+    }
+    /// Skips the data of chunks with the given type in [`PngReader::next_chunk`].
+    pub fn skip_data(mut self, chunk_type: ChunkType) -> Self {
+        self.skipped.push(chunk_type);
+        self
+    }
+    /// Number of bytes consumed from the underlying reader.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+    /// Reads the next chunk, skipping chunks registered with [`PngReader::skip_data`].
+    ///
+    /// Returns `None` once the input ends on a chunk boundary.
+    pub fn next_chunk(&mut self) -> Result<Option<Chunk>, Error> {
+        while let Some(header) = self.next_header()? {
+            if !self.skipped.contains(header.r#type()) {
+                return self.read_data().map(Some);
+            }
+        }
+        Ok(None)
+    }
+    /// Reads the next chunk's length and type, skipping the previous chunk's data if it was not
+    /// read.
+    pub fn next_header(&mut self) -> Result<Option<ChunkHeader>, Error> {
+        if self.pending.is_some() {
+            self.skip_pending()?;
+        }
+        let offset = self.offset;
+        let mut header = [0; 8];
+        match self.read_full(&mut header)? {
+            0 => return Ok(None),
+            8 => {}
+            _ => return Err(Error::Truncated { offset, needed: 12 }),
+        }
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        if length > Chunk::MAX_LENGTH {
+            return Err(Error::InvalidLength { offset, length });
+        }
+        let r#type = ChunkType::try_from([header[4], header[5], header[6], header[7]])?;
+        let header = ChunkHeader {
+            length,
+            r#type,
+            offset,
+        };
+        self.pending = Some(header);
+        Ok(Some(header))
+    }
+    /// Reads the data and CRC of the chunk returned by the last [`PngReader::next_header`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such chunk, or its data was already read.
+    pub fn read_data(&mut self) -> Result<Chunk, Error> {
+        let header = self.pending.take().expect("no chunk header pending");
+        let mut data = Vec::new();
+        let n = (&mut self.inner)
+            .take(header.length as u64)
+            .read_to_end(&mut data)?;
+        self.offset += n;
+        if n < header.length as usize {
+            return Err(Self::truncated(header));
+        }
+        let crc = self.read_crc(header, crc32(header.r#type.as_bytes(), &data))?;
+        Ok(Chunk::from_parts(header.r#type, data.into(), crc))
+    }
+    /// Consumes the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn skip_pending(&mut self) -> Result<(), Error> {
+        let header = self.pending.take().expect("no chunk header pending");
+        let mut crc = Crc32::new();
+        crc.update(header.r#type.as_bytes());
+        let mut buf = [0; SKIP_BUFFER_LEN];
+        let mut remaining = header.length as usize;
+        while remaining > 0 {
+            let len = remaining.min(buf.len());
+            let n = self.read_full(&mut buf[..len])?;
+            crc.update(&buf[..n]);
+            if n < len {
+                return Err(Self::truncated(header));
+            }
+            remaining -= n;
+        }
+        self.read_crc(header, crc.finish())?;
+        Ok(())
+    }
+    fn read_crc(&mut self, header: ChunkHeader, computed: u32) -> Result<u32, Error> {
+        let offset = self.offset;
+        let mut crc = [0; 4];
+        if self.read_full(&mut crc)? < crc.len() {
+            return Err(Self::truncated(header));
+        }
+        let stored = u32::from_be_bytes(crc);
+        if stored != computed {
+            return Err(Error::CrcMismatch {
+                chunk_type: header.r#type,
+                offset,
+                stored,
+                computed,
+            });
+        }
+        Ok(stored)
+    }
+    fn read_full(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = read_full(&mut self.inner, buf)?;
+        self.offset += n;
+        Ok(n)
+    }
+    const fn truncated(header: ChunkHeader) -> Error {
+        Error::Truncated {
+            offset: header.offset,
+            needed: header.length as usize + 12,
+        }
+    }
+}
+
+/// Reads until `buf` is full or the reader is exhausted, returning the number of bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(read) => n += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn testing_bytes() -> Vec<u8> {
+        let chunks = [
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), [0; 13]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), [1; 20_000]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), "message"),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), []),
+        ];
+        Png::from_chunks(chunks).bytes()
+    }
+
+    /// Hands out at most three bytes per read to exercise partial reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_read_all_chunks() {
+        let bytes = testing_bytes();
+        let chunks = PngReader::new(Trickle(&bytes))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let png = Png::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[1].data(), png.chunks()[1].data());
+    }
+
+    #[test]
+    fn test_skip_data() {
+        let bytes = testing_bytes();
+        let types = PngReader::new(bytes.as_slice())
+            .unwrap()
+            .skip_data(ChunkType::from_str("IDAT").unwrap())
+            .map(|c| c.unwrap().r#type().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(types, ["IHDR", "ruSt", "IEND"]);
+    }
+
+    #[test]
+    fn test_next_header() {
+        let bytes = testing_bytes();
+        let mut reader = PngReader::new(bytes.as_slice()).unwrap();
+        let mut offsets = vec![];
+        while let Some(header) = reader.next_header().unwrap() {
+            offsets.push((header.r#type().to_string(), header.offset()));
+        }
+        assert_eq!(
+            offsets,
+            [
+                ("IHDR".to_owned(), 8),
+                ("IDAT".to_owned(), 33),
+                ("ruSt".to_owned(), 20_045),
+                ("IEND".to_owned(), 20_064)
+            ]
+        );
+        assert_eq!(reader.offset(), bytes.len());
+    }
+
+    #[test]
+    fn test_skipped_crc_mismatch() {
+        let mut bytes = testing_bytes();
+        bytes[100] ^= 1;
+        let err = PngReader::new(bytes.as_slice())
+            .unwrap()
+            .skip_data(ChunkType::from_str("IDAT").unwrap())
+            .find_map(Result::err)
+            .unwrap();
+        assert!(matches!(err, Error::CrcMismatch { offset: 20_041, .. }));
+    }
+
+    #[test]
+    fn test_truncated_chunk() {
+        let bytes = testing_bytes();
+        let mut reader = PngReader::new(&bytes[..1000]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Truncated {
+                offset: 33,
+                needed: 20_012
+            }
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        assert!(matches!(
+            PngReader::new(&b"GIF89a"[..]).unwrap_err(),
+            Error::InvalidSignature
+        ));
+        assert!(matches!(
+            PngReader::new(&Png::STANDARD_HEADER[..5]).unwrap_err(),
+            Error::Truncated {
+                offset: 0,
+                needed: 8
+            }
+        ));
+    }
+}
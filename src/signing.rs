@@ -0,0 +1,294 @@
+//! Ed25519 signatures over selected chunks, stored in a private `siGN` chunk.
+//!
+//! A signature always covers every critical chunk and every chunk of the [`IMAGE_CHUNKS`]
+//! types, which change how the image is decoded, colored or animated, plus every chunk of the
+//! extra types chosen when signing, such as the chunk holding a message. Other ancillary chunks,
+//! including `tIME` and other `siGN` chunks, can be added, edited or removed freely.
+//!
+//! The signed message is the SHA-256 digest of a domain string, the list of covered chunk types
+//! and, for each covered chunk in file order, its type, length and data. The chunk data is:
+//!
+//! - the format version (1),
+//! - the signer's 32-byte public key,
+//! - the number of covered chunk types followed by the types themselves,
+//! - the 64-byte signature.
+
+use std::fmt;
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    codec::ChunkCodec,
+    color::malformed,
+    ed25519::{Signature, SigningKey, VerifyingKey},
+    sha2::Sha256,
+    Error,
+};
+
+/// Ancillary chunk types that affect the rendered pixels: transparency, background, color space
+/// and animation. Signatures always cover the ones present, like critical chunks.
+pub const IMAGE_CHUNKS: [ChunkType; 12] = [
+    ChunkType::TRNS,
+    ChunkType::BKGD,
+    ChunkType::GAMA,
+    ChunkType::CHRM,
+    ChunkType::SRGB,
+    ChunkType::ICCP,
+    ChunkType::CICP,
+    ChunkType::MDCV,
+    ChunkType::CLLI,
+    ChunkType::ACTL,
+    ChunkType::FCTL,
+    ChunkType::FDAT,
+];
+
+/// The signature format version written by this crate.
+pub const VERSION: u8 = 1;
+
+/// Prefix of the digest, so a signature over chunks cannot be replayed as one over anything
+/// else.
+const DOMAIN: &[u8] = b"pngme chunk signature v1\0";
+
+/// An Ed25519 signature over the critical chunks and chosen ancillary chunks of a PNG.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ChunkSignature {
+    public_key: VerifyingKey,
+    covered: Vec<ChunkType>,
+    signature: Signature,
+}
+
+impl ChunkSignature {
+    /// Signs every critical or [`IMAGE_CHUNKS`] chunk in `chunks` and every chunk whose type is
+    /// in `extra`. Fails if no chunk has one of the `extra` types.
+    pub fn sign(key: &SigningKey, chunks: &[Chunk], extra: &[ChunkType]) -> Result<Self, Error> {
+        let mut covered = vec![];
+        let types = chunks
+            .iter()
+            .map(Chunk::r#type)
+            .filter(|t| always_covered(t));
+        for &chunk_type in types.chain(extra) {
+            if chunk_type == Self::CHUNK_TYPE {
+                return Err(malformed(
+                    Self::CHUNK_TYPE,
+                    "a signature cannot cover signatures".to_owned(),
+                ));
+            }
+            if !chunks.iter().any(|c| *c.r#type() == chunk_type) {
+                return Err(Error::ChunkNotFound(chunk_type.to_string()));
+            }
+            if !covered.contains(&chunk_type) {
+                covered.push(chunk_type);
+            }
+        }
+        if covered.len() > usize::from(u8::MAX) {
+            return Err(malformed(
+                Self::CHUNK_TYPE,
+                format!("cannot cover {} chunk types", covered.len()),
+            ));
+        }
+        let signature = key.sign(&digest(chunks, &covered));
+        Ok(Self {
+            public_key: key.verifying_key(),
+            covered,
+            signature,
+        })
+    }
+    /// Checks the signature against `chunks` and returns the chunks it covers, in file order.
+    ///
+    /// Fails with [`Error::VerificationFailed`] if a covered chunk was added, removed, reordered
+    /// or modified, or if a critical or [`IMAGE_CHUNKS`] chunk of a type the signature does not
+    /// cover was added.
+    pub fn verify<'a>(&self, chunks: &'a [Chunk]) -> Result<Vec<&'a Chunk>, Error> {
+        self.public_key
+            .verify(&digest(chunks, &self.covered), &self.signature)?;
+        if chunks
+            .iter()
+            .any(|c| always_covered(c.r#type()) && !self.covered.contains(c.r#type()))
+        {
+            return Err(Error::VerificationFailed);
+        }
+        Ok(chunks
+            .iter()
+            .filter(|c| self.covered.contains(c.r#type()))
+            .collect())
+    }
+    /// The signer's public key.
+    pub const fn public_key(&self) -> &VerifyingKey {
+        &self.public_key
+    }
+    /// The covered chunk types: the critical and [`IMAGE_CHUNKS`] ones in file order, then the
+    /// extra ones.
+    pub fn covered(&self) -> &[ChunkType] {
+        &self.covered
+    }
+    /// The signature over the digest of the covered chunks.
+    pub const fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+impl ChunkCodec for ChunkSignature {
+    const CHUNK_TYPE: ChunkType = ChunkType::new(*b"siGN");
+
+    fn decode(data: &[u8]) -> Result<Self, Error> {
+        let truncated = || malformed(Self::CHUNK_TYPE, "too short".to_owned());
+        let Some((&version, rest)) = data.split_first() else {
+            return Err(truncated());
+        };
+        if version != VERSION {
+            return Err(malformed(
+                Self::CHUNK_TYPE,
+                format!("unsupported version {version}"),
+            ));
+        }
+        let (public_key, rest) = rest.split_first_chunk::<32>().ok_or_else(truncated)?;
+        let (&count, mut rest) = rest.split_first().ok_or_else(truncated)?;
+        if count == 0 {
+            return Err(malformed(Self::CHUNK_TYPE, "covers no chunks".to_owned()));
+        }
+        let mut covered = Vec::with_capacity(count.into());
+        for _ in 0..count {
+            let (chunk_type, tail) = rest.split_first_chunk::<4>().ok_or_else(truncated)?;
+            covered.push(ChunkType::try_from(*chunk_type)?);
+            rest = tail;
+        }
+        let signature: [u8; 64] = rest.try_into().map_err(|_| {
+            malformed(
+                Self::CHUNK_TYPE,
+                format!("expected a 64-byte signature, found {} bytes", rest.len()),
+            )
+        })?;
+        Ok(Self {
+            public_key: VerifyingKey::from_bytes(*public_key),
+            covered,
+            signature: Signature::from_bytes(signature),
+        })
+    }
+    fn encode(&self) -> Vec<u8> {
+        let mut data = vec![VERSION];
+        data.extend_from_slice(self.public_key.as_bytes());
+        data.push(self.covered.len() as u8);
+        for chunk_type in &self.covered {
+            data.extend_from_slice(chunk_type.as_bytes());
+        }
+        data.extend_from_slice(self.signature.as_bytes());
+        data
+    }
+}
+
+impl fmt::Display for ChunkSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ed25519 signature by {} over ", self.public_key)?;
+        for (i, chunk_type) in self.covered.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{chunk_type}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether every chunk of `chunk_type` must be covered by a signature.
+fn always_covered(chunk_type: &ChunkType) -> bool {
+    chunk_type.is_critical() || IMAGE_CHUNKS.contains(chunk_type)
+}
+
+/// The digest a signature over the `covered` types of `chunks` signs.
+fn digest(chunks: &[Chunk], covered: &[ChunkType]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN);
+    hasher.update(&[covered.len() as u8]);
+    for chunk_type in covered {
+        hasher.update(chunk_type.as_bytes());
+    }
+    for chunk in chunks.iter().filter(|c| covered.contains(c.r#type())) {
+        hasher.update(chunk.r#type().as_bytes());
+        hasher.update(&chunk.length().to_be_bytes());
+        hasher.update(chunk.data());
+    }
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::to_chunk;
+
+    fn chunks() -> Vec<Chunk> {
+        let chunk = |t: &[u8; 4], data: &str| Chunk::new(ChunkType::new(*t), data);
+        vec![
+            chunk(b"IHDR", "header"),
+            chunk(b"tEXt", "Comment\0hello"),
+            chunk(b"ruSt", "hidden message"),
+            chunk(b"IDAT", "pixels"),
+            chunk(b"IEND", ""),
+        ]
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::from_bytes([1; 32]);
+        let mut chunks = chunks();
+        let rust = ChunkType::new(*b"ruSt");
+        let signature = ChunkSignature::sign(&key, &chunks, &[rust]).unwrap();
+        assert_eq!(signature.public_key(), &key.verifying_key());
+        assert_eq!(
+            signature
+                .covered()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["IHDR", "IDAT", "IEND", "ruSt"]
+        );
+
+        let decoded = ChunkSignature::decode(&signature.encode()).unwrap();
+        assert_eq!(decoded, signature);
+        chunks.insert(4, to_chunk(&decoded));
+        assert_eq!(signature.verify(&chunks).unwrap().len(), 4);
+
+        // Uncovered chunks can change; covered ones cannot.
+        chunks[1] = Chunk::new(ChunkType::TEXT, "Comment\0edited");
+        assert!(signature.verify(&chunks).is_ok());
+        chunks[2] = Chunk::new(rust, "forged message");
+        assert!(matches!(
+            signature.verify(&chunks),
+            Err(Error::VerificationFailed)
+        ));
+        chunks[2] = Chunk::new(rust, "hidden message");
+        chunks.insert(3, Chunk::new(ChunkType::PLTE, [0; 3]));
+        assert!(signature.verify(&chunks).is_err());
+    }
+
+    #[test]
+    fn test_image_chunks_covered() {
+        let key = SigningKey::from_bytes([1; 32]);
+        let mut chunks = chunks();
+        chunks.insert(1, Chunk::new(ChunkType::TRNS, [0, 0]));
+        let signature = ChunkSignature::sign(&key, &chunks, &[]).unwrap();
+        assert!(signature.covered().contains(&ChunkType::TRNS));
+        chunks[1] = Chunk::new(ChunkType::TRNS, [0, 1]);
+        assert!(signature.verify(&chunks).is_err());
+
+        // Adding transparency the signer never saw also changes the image.
+        let mut chunks = self::chunks();
+        let signature = ChunkSignature::sign(&key, &chunks, &[]).unwrap();
+        chunks.insert(1, Chunk::new(ChunkType::TRNS, [0, 0]));
+        assert!(matches!(
+            signature.verify(&chunks),
+            Err(Error::VerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_sign_errors() {
+        let key = SigningKey::from_bytes([1; 32]);
+        assert!(matches!(
+            ChunkSignature::sign(&key, &chunks(), &[ChunkType::TIME]),
+            Err(Error::ChunkNotFound(_))
+        ));
+        assert!(ChunkSignature::sign(&key, &chunks(), &[ChunkSignature::CHUNK_TYPE]).is_err());
+        assert!(ChunkSignature::decode(&[VERSION]).is_err());
+        assert!(ChunkSignature::decode(&[2; 101]).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+//! The `tEXt`, `zTXt` and `iTXt` textual data chunks.
+
+use crate::{chunk::Chunk, chunk_type::ChunkType, deflate::Compression, zlib, Error};
+
+/// Largest decompressed text accepted from `zTXt` and `iTXt` chunks.
+const MAX_TEXT_LEN: usize = 16 * 1024 * 1024;
+
+/// How a [`TextChunk`] is stored.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TextFormat {
+    /// `tEXt`: uncompressed Latin-1.
+    Plain,
+    /// `zTXt`: compressed Latin-1.
+    Compressed,
+    /// `iTXt`: UTF-8 with an optional language tag and translated keyword.
+    International {
+        compressed: bool,
+        language: String,
+        translated_keyword: String,
+    },
+}
+
+/// A keyword / text pair from a `tEXt`, `zTXt` or `iTXt` chunk.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TextChunk {
+    keyword: String,
+    text: String,
+    format: TextFormat,
+}
+
+impl TryFrom<&Chunk> for TextChunk {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let chunk_type = *chunk.r#type();
+        let malformed = |reason: &str| Error::MalformedChunk {
+            chunk_type,
+            reason: reason.to_owned(),
+        };
+        let data = chunk.data();
+        let (keyword, rest) =
+            split_nul(data).ok_or_else(|| malformed("missing keyword separator"))?;
+        let keyword = latin1(keyword);
+        check_stored_keyword(chunk_type, &keyword)?;
+
+        let (text, format) = match chunk_type {
+            ChunkType::TEXT => (latin1(rest), TextFormat::Plain),
+            ChunkType::ZTXT => {
+                let (&method, compressed) = rest
+                    .split_first()
+                    .ok_or_else(|| malformed("missing compression method"))?;
+                if method != 0 {
+                    return Err(malformed(&format!("invalid compression method {method}")));
+                }
+                (latin1(&inflate(compressed)?), TextFormat::Compressed)
+            }
+            ChunkType::ITXT => {
+                let [flag, method, rest @ ..] = rest else {
+                    return Err(malformed("missing compression flag"));
+                };
+                let (language, rest) =
+                    split_nul(rest).ok_or_else(|| malformed("missing language tag separator"))?;
+                let (translated_keyword, text) = split_nul(rest)
+                    .ok_or_else(|| malformed("missing translated keyword separator"))?;
+                let compressed = match (flag, method) {
+                    (0, _) => false,
+                    (1, 0) => true,
+                    _ => return Err(malformed("invalid compression flag or method")),
+                };
+                let text = match compressed {
+                    true => inflate(text)?,
+                    false => text.to_vec(),
+                };
+                let language = std::str::from_utf8(language)?.to_owned();
+                check_language(&language).map_err(|e| malformed(&e))?;
+                (
+                    String::from_utf8(text).map_err(|e| e.utf8_error())?,
+                    TextFormat::International {
+                        compressed,
+                        language,
+                        translated_keyword: std::str::from_utf8(translated_keyword)?.to_owned(),
+                    },
+                )
+            }
+            _ => return Err(malformed("not a text chunk")),
+        };
+
+        Ok(Self {
+            keyword,
+            text,
+            format,
+        })
+    }
+}
+
+impl std::fmt::Display for TextChunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.keyword, self.text)
+    }
+}
+
+impl TextChunk {
+    /// The chunk types that hold text.
+    pub const CHUNK_TYPES: [ChunkType; 3] = [ChunkType::TEXT, ChunkType::ZTXT, ChunkType::ITXT];
+
+    /// Creates an uncompressed entry: `tEXt` if `text` is Latin-1, `iTXt` otherwise.
+    pub fn new(keyword: impl Into<String>, text: impl Into<String>) -> Result<Self, Error> {
+        let (keyword, text) = (keyword.into(), text.into());
+        check_keyword(&keyword)?;
+        let format = match is_latin1(&text) {
+            true => TextFormat::Plain,
+            false => TextFormat::International {
+                compressed: false,
+                language: String::new(),
+                translated_keyword: String::new(),
+            },
+        };
+
+        Ok(Self {
+            keyword,
+            text,
+            format,
+        })
+    }
+    /// Creates an `iTXt` entry with a language tag such as `en-US` and the keyword translated
+    /// into that language. Either may be empty.
+    pub fn international(
+        keyword: impl Into<String>,
+        text: impl Into<String>,
+        language: impl Into<String>,
+        translated_keyword: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let (keyword, language) = (keyword.into(), language.into());
+        check_keyword(&keyword)?;
+        check_language(&language).map_err(Error::InvalidText)?;
+
+        Ok(Self {
+            keyword,
+            text: text.into(),
+            format: TextFormat::International {
+                compressed: false,
+                language,
+                translated_keyword: translated_keyword.into(),
+            },
+        })
+    }
+    /// Stores the text compressed: `tEXt` becomes `zTXt`, `iTXt` sets its compression flag.
+    pub fn compressed(mut self) -> Self {
+        match &mut self.format {
+            TextFormat::Plain => self.format = TextFormat::Compressed,
+            TextFormat::Compressed => {}
+            TextFormat::International { compressed, .. } => *compressed = true,
+        }
+        self
+    }
+    /// The keyword, such as `Title` or `Comment`.
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+    /// The text, decompressed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// How the entry is stored.
+    pub const fn format(&self) -> &TextFormat {
+        &self.format
+    }
+    /// The chunk type the entry is stored in.
+    pub const fn chunk_type(&self) -> ChunkType {
+        match self.format {
+            TextFormat::Plain => ChunkType::TEXT,
+            TextFormat::Compressed => ChunkType::ZTXT,
+            TextFormat::International { .. } => ChunkType::ITXT,
+        }
+    }
+    /// Serializes the entry as a chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut data = to_latin1(&self.keyword);
+        data.push(0);
+        match &self.format {
+            TextFormat::Plain => data.extend(to_latin1(&self.text)),
+            TextFormat::Compressed => {
+                data.push(0);
+                data.extend(zlib::compress(&to_latin1(&self.text), Compression::Best));
+            }
+            TextFormat::International {
+                compressed,
+                language,
+                translated_keyword,
+            } => {
+                data.extend_from_slice(&[*compressed as u8, 0]);
+                data.extend_from_slice(language.as_bytes());
+                data.push(0);
+                data.extend_from_slice(translated_keyword.as_bytes());
+                data.push(0);
+                match compressed {
+                    true => data.extend(zlib::compress(self.text.as_bytes(), Compression::Best)),
+                    false => data.extend_from_slice(self.text.as_bytes()),
+                }
+            }
+        }
+        Chunk::new(self.chunk_type(), data)
+    }
+}
+
+/// Checks a keyword against the specification: 1 to 79 printable Latin-1 characters, with no
+/// leading, trailing or consecutive spaces.
+pub fn check_keyword(keyword: &str) -> Result<(), Error> {
+    let invalid = |reason: String| Err(Error::InvalidText(reason));
+    let len = keyword.chars().count();
+    if !(1..=79).contains(&len) {
+        return invalid(format!("keyword must be 1 to 79 characters, found {len}"));
+    }
+    if let Some(c) = keyword
+        .chars()
+        .find(|&c| !matches!(c as u32, 32..=126 | 161..=255))
+    {
+        return invalid(format!("invalid keyword character {c:?}"));
+    }
+    if keyword.starts_with(' ') || keyword.ends_with(' ') || keyword.contains("  ") {
+        return invalid("keyword has leading, trailing or consecutive spaces".to_owned());
+    }
+    Ok(())
+}
+
+/// Checks a keyword read from a `chunk_type` chunk, reporting a bad one as a malformed chunk.
+pub(crate) fn check_stored_keyword(chunk_type: ChunkType, keyword: &str) -> Result<(), Error> {
+    check_keyword(keyword).map_err(|e| match e {
+        Error::InvalidText(reason) => Error::MalformedChunk { chunk_type, reason },
+        e => e,
+    })
+}
+
+/// Language tags are hyphen-separated words of ASCII letters and digits.
+fn check_language(language: &str) -> Result<(), String> {
+    if language.is_empty()
+        || language
+            .split('-')
+            .all(|word| !word.is_empty() && word.bytes().all(|b| b.is_ascii_alphanumeric()))
+    {
+        Ok(())
+    } else {
+        Err(format!("invalid language tag {language:?}"))
+    }
+}
+
+pub(crate) fn split_nul(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let i = data.iter().position(|&b| b == 0)?;
+    Some((&data[..i], &data[i + 1..]))
+}
+
+fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    zlib::decompress_limited(data, MAX_TEXT_LEN)
+}
+
+pub(crate) fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+pub(crate) fn is_latin1(text: &str) -> bool {
+    text.chars().all(|c| (c as u32) < 256)
+}
+
+/// Encodes `text` as Latin-1. Callers have checked it with [`is_latin1`].
+pub(crate) fn to_latin1(text: &str) -> Vec<u8> {
+    text.chars().map(|c| c as u32 as u8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_round_trip() {
+        for entry in [
+            TextChunk::new("Title", "Caf\u{e9}").unwrap(),
+            TextChunk::new("Comment", "long text ".repeat(50))
+                .unwrap()
+                .compressed(),
+            TextChunk::new("Title", "\u{732b}").unwrap(),
+            TextChunk::international("Author", "\u{732b}", "ja-JP", "\u{8457}\u{8005}")
+                .unwrap()
+                .compressed(),
+        ] {
+            let chunk = entry.to_chunk();
+            assert_eq!(*chunk.r#type(), entry.chunk_type());
+            assert_eq!(TextChunk::try_from(&chunk).unwrap(), entry);
+        }
+    }
+
+    #[test]
+    fn test_text_formats() {
+        let entry = TextChunk::new("Title", "Caf\u{e9}").unwrap();
+        assert_eq!(entry.chunk_type(), ChunkType::TEXT);
+        assert_eq!(&entry.to_chunk().data()[6..], b"Caf\xe9");
+        assert_eq!(entry.clone().compressed().chunk_type(), ChunkType::ZTXT);
+        assert_eq!(
+            TextChunk::new("Title", "\u{732b}").unwrap().chunk_type(),
+            ChunkType::ITXT
+        );
+        assert_eq!(entry.to_string(), "Title: Caf\u{e9}");
+    }
+
+    #[test]
+    fn test_parse_text_chunks() {
+        let chunk = Chunk::new(ChunkType::TEXT, b"Software\0pngme");
+        let entry = TextChunk::try_from(&chunk).unwrap();
+        assert_eq!((entry.keyword(), entry.text()), ("Software", "pngme"));
+
+        let chunk = Chunk::new(ChunkType::ITXT, b"Title\0\0\0en\0Titel\0hi");
+        let entry = TextChunk::try_from(&chunk).unwrap();
+        assert_eq!(entry.text(), "hi");
+        assert_eq!(
+            *entry.format(),
+            TextFormat::International {
+                compressed: false,
+                language: "en".to_owned(),
+                translated_keyword: "Titel".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_malformed_text_chunks() {
+        for chunk in [
+            Chunk::new(ChunkType::TEXT, b"no separator"),
+            Chunk::new(ChunkType::TEXT, b"\0empty keyword"),
+            Chunk::new(ChunkType::ZTXT, b"Title\0\x01x"),
+            Chunk::new(ChunkType::ZTXT, b"Title\0\0not zlib"),
+            Chunk::new(ChunkType::ITXT, b"Title\0\x02\0\0\0text"),
+            Chunk::new(ChunkType::ITXT, b"Title\0\0\0en\0missing"),
+            Chunk::new(ChunkType::ITXT, b"Title\0\0\0e n\0\0text"),
+            Chunk::new(ChunkType::ITXT, b"Title\0\0\0\0\0\xff"),
+            Chunk::new(ChunkType::IEND, b"Title\0text"),
+        ] {
+            assert!(TextChunk::try_from(&chunk).is_err(), "{chunk}");
+        }
+    }
+
+    #[test]
+    fn test_check_keyword() {
+        assert!(check_keyword("Title").is_ok());
+        assert!(check_keyword("Creation Time").is_ok());
+        assert!(check_keyword(&"k".repeat(79)).is_ok());
+        for keyword in ["", " Title", "Title ", "Two  spaces", "tab\t", "\u{732b}"] {
+            assert!(check_keyword(keyword).is_err(), "{keyword:?}");
+        }
+        assert!(check_keyword(&"k".repeat(80)).is_err());
+        assert!(matches!(
+            TextChunk::new("", "text"),
+            Err(Error::InvalidText(_))
+        ));
+        assert!(TextChunk::international("Title", "text", "en_US", "").is_err());
+    }
+}
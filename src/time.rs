@@ -0,0 +1,262 @@
+//! The `tIME` last-modification time chunk.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    color::{check_type, malformed},
+    Error,
+};
+
+/// A UTC calendar time with one-second precision, as stored in a `tIME` chunk.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl TryFrom<&[u8]> for Timestamp {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        let &[y0, y1, month, day, hour, minute, second] = data else {
+            return Err(malformed(
+                ChunkType::TIME,
+                format!("expected 7 bytes, found {}", data.len()),
+            ));
+        };
+        Self::new(
+            u16::from_be_bytes([y0, y1]),
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        )
+    }
+}
+
+impl TryFrom<&Chunk> for Timestamp {
+    type Error = Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        check_type(chunk, ChunkType::TIME)?;
+        Self::try_from(chunk.data())
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = Error;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()),
+            Err(e) => i64::try_from(e.duration().as_secs()).map(|s| -s),
+        };
+        Self::from_unix(secs.unwrap_or(i64::MAX))
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        let secs = timestamp.to_unix();
+        match u64::try_from(secs) {
+            Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+            Err(_) => UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()),
+        }
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl Timestamp {
+    /// Creates a timestamp, checking each field's range. `second` may be 60 for a leap second.
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, Error> {
+        let invalid = |reason: String| Err(malformed(ChunkType::TIME, reason));
+        if !(1..=12).contains(&month) {
+            return invalid(format!("invalid month {month}"));
+        }
+        if !(1..=days_in_month(year.into(), month)).contains(&day) {
+            return invalid(format!("invalid day {day} for {year:04}-{month:02}"));
+        }
+        if hour > 23 || minute > 59 || second > 60 {
+            return invalid(format!("invalid time {hour:02}:{minute:02}:{second:02}"));
+        }
+
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+    /// The current time.
+    pub fn now() -> Result<Self, Error> {
+        Self::try_from(SystemTime::now())
+    }
+    /// Converts seconds since 1970-01-01 00:00:00 UTC, failing for years outside 0 to 65535.
+    pub fn from_unix(secs: i64) -> Result<Self, Error> {
+        let (days, secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+        let (year, month, day) = civil_from_days(days);
+        let year = u16::try_from(year).map_err(|_| {
+            malformed(
+                ChunkType::TIME,
+                format!("year {year} is outside 0 to 65535"),
+            )
+        })?;
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+        })
+    }
+    /// Seconds since 1970-01-01 00:00:00 UTC. A leap second counts as the first second of the
+    /// next minute.
+    pub fn to_unix(&self) -> i64 {
+        let days = days_from_civil(self.year.into(), self.month, self.day);
+        days * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+    /// The full year, such as 2024.
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+    /// The month, 1 to 12.
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+    /// The day of the month, 1 to 31.
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+    /// The hour, 0 to 23.
+    pub const fn hour(&self) -> u8 {
+        self.hour
+    }
+    /// The minute, 0 to 59.
+    pub const fn minute(&self) -> u8 {
+        self.minute
+    }
+    /// The second, 0 to 60.
+    pub const fn second(&self) -> u8 {
+        self.second
+    }
+    /// Serializes the timestamp as a `tIME` chunk.
+    pub fn to_chunk(&self) -> Chunk {
+        let [y0, y1] = self.year.to_be_bytes();
+        Chunk::new(
+            ChunkType::TIME,
+            [
+                y0,
+                y1,
+                self.month,
+                self.day,
+                self.hour,
+                self.minute,
+                self.second,
+            ],
+        )
+    }
+}
+
+const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_conversion() {
+        for (secs, expected) in [
+            (0, "1970-01-01 00:00:00 UTC"),
+            (951_782_400, "2000-02-29 00:00:00 UTC"),
+            (1_700_000_000, "2023-11-14 22:13:20 UTC"),
+            (-1, "1969-12-31 23:59:59 UTC"),
+            (-62_167_219_200, "0000-01-01 00:00:00 UTC"),
+        ] {
+            let timestamp = Timestamp::from_unix(secs).unwrap();
+            assert_eq!(timestamp.to_string(), expected);
+            assert_eq!(timestamp.to_unix(), secs);
+        }
+        assert!(Timestamp::from_unix(-62_167_219_201).is_err());
+
+        let timestamp = Timestamp::new(2024, 5, 1, 12, 30, 0).unwrap();
+        let time = SystemTime::from(timestamp);
+        assert_eq!(Timestamp::try_from(time).unwrap(), timestamp);
+    }
+
+    #[test]
+    fn test_chunk_round_trip() {
+        let timestamp = Timestamp::new(2024, 2, 29, 23, 59, 60).unwrap();
+        assert_eq!(
+            Timestamp::try_from(&timestamp.to_chunk()).unwrap(),
+            timestamp
+        );
+        assert_eq!(timestamp.to_chunk().data(), [7, 232, 2, 29, 23, 59, 60]);
+
+        assert!(Timestamp::new(2023, 2, 29, 0, 0, 0).is_err());
+        assert!(Timestamp::new(2024, 13, 1, 0, 0, 0).is_err());
+        assert!(Timestamp::new(2024, 1, 1, 24, 0, 0).is_err());
+        assert!(Timestamp::try_from(&Chunk::new(ChunkType::TIME, [0; 6])).is_err());
+    }
+}
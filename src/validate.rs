@@ -0,0 +1,724 @@
+//! Chunk ordering and multiplicity rules from the PNG specification.
+
+use std::fmt;
+
+use crate::{
+    apng::{self, AnimationControl, FrameControl},
+    chunk::Chunk,
+    chunk_type::ChunkType,
+    ihdr::{ColorType, Ihdr},
+    palette::{Background, Histogram, Palette, Transparency},
+    Error,
+};
+
+/// One way a PNG breaks the specification's structural rules.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Violation {
+    index: Option<usize>,
+    kind: ViolationKind,
+}
+
+/// The rule broken by a [`Violation`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// The first chunk is not `IHDR`.
+    IhdrNotFirst,
+    /// The `IHDR` chunk could not be parsed.
+    InvalidIhdr(String),
+    /// A required chunk is absent.
+    Missing(ChunkType),
+    /// A chunk that may appear at most once appears again.
+    Duplicate(ChunkType),
+    /// A chunk follows `IEND`.
+    AfterIend(ChunkType),
+    /// An `IDAT` chunk is separated from the previous `IDAT` by other chunks.
+    NonContiguousIdat,
+    /// A chunk that must precede `PLTE` follows it.
+    AfterPlte(ChunkType),
+    /// A chunk that must precede the image data follows it.
+    AfterIdat(ChunkType),
+    /// A chunk that must follow `PLTE` precedes it.
+    BeforePlte(ChunkType),
+    /// A chunk's data length is not allowed for its type.
+    InvalidLength(ChunkType, u32),
+    /// A chunk is not allowed with the image's color type.
+    ColorTypeMismatch(ChunkType, ColorType),
+    /// A chunk requires another chunk that is absent.
+    Requires(ChunkType, ChunkType),
+    /// A chunk's contents do not match the image header or palette.
+    Mismatch(ChunkType, String),
+    /// An `fcTL` or `fdAT` chunk is out of the animation's sequence: (type, expected, found).
+    SequenceNumber(ChunkType, u32, u32),
+    /// The number of `fcTL` chunks differs from the frame count in `acTL`: (declared, found).
+    FrameCount(u32, usize),
+    /// Two chunks that must not appear together both appear.
+    Exclusive(ChunkType, ChunkType),
+    /// A critical chunk type is not defined by the specification.
+    UnknownCritical(ChunkType),
+    /// A chunk type has its reserved bit set.
+    ReservedBit(ChunkType),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(index) = self.index {
+            write!(f, "chunk {index}: ")?;
+        }
+        self.kind.fmt(f)
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IhdrNotFirst => f.write_str("first chunk is not IHDR"),
+            Self::InvalidIhdr(reason) => write!(f, "invalid IHDR: {reason}"),
+            Self::Missing(t) => write!(f, "missing required {t} chunk"),
+            Self::Duplicate(t) => write!(f, "{t} may appear only once"),
+            Self::AfterIend(t) => write!(f, "{t} follows IEND"),
+            Self::NonContiguousIdat => f.write_str("IDAT chunks are not consecutive"),
+            Self::AfterPlte(t) => write!(f, "{t} must precede PLTE"),
+            Self::AfterIdat(t) => write!(f, "{t} must precede IDAT"),
+            Self::BeforePlte(t) => write!(f, "{t} must follow PLTE"),
+            Self::InvalidLength(t, l) => write!(f, "{t} has invalid length {l}"),
+            Self::ColorTypeMismatch(t, c) => write!(f, "{t} is not allowed for color type {c}"),
+            Self::Requires(t, r) => write!(f, "{t} requires a {r} chunk"),
+            Self::Mismatch(t, reason) => write!(f, "{t} does not match the image: {reason}"),
+            Self::SequenceNumber(t, e, n) => {
+                write!(f, "{t} has sequence number {n}, expected {e}")
+            }
+            Self::FrameCount(d, n) => write!(f, "acTL declares {d} frames, found {n}"),
+            Self::Exclusive(t, o) => write!(f, "{t} must not appear together with {o}"),
+            Self::UnknownCritical(t) => write!(f, "unknown critical chunk {t}"),
+            Self::ReservedBit(t) => write!(f, "{t} has the reserved bit set"),
+        }
+    }
+}
+
+impl Violation {
+    const fn at(index: usize, kind: ViolationKind) -> Self {
+        Self {
+            index: Some(index),
+            kind,
+        }
+    }
+    const fn global(kind: ViolationKind) -> Self {
+        Self { index: None, kind }
+    }
+    /// Index of the offending chunk, or `None` for violations about the file as a whole.
+    pub const fn index(&self) -> Option<usize> {
+        self.index
+    }
+    /// The rule that was broken.
+    pub const fn kind(&self) -> &ViolationKind {
+        &self.kind
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Order {
+    Any,
+    BeforePlte,
+    BeforeIdat,
+    AfterPlte,
+}
+
+/// Rules for every chunk type the specification defines: (type, may repeat, order, fixed data
+/// length).
+#[allow(clippy::type_complexity)]
+const RULES: &[(&[u8; 4], bool, Order, Option<u32>)] = &[
+    (b"IHDR", false, Order::Any, Some(13)),
+    (b"PLTE", false, Order::BeforeIdat, None),
+    (b"IDAT", true, Order::Any, None),
+    (b"IEND", false, Order::Any, Some(0)),
+    (b"cHRM", false, Order::BeforePlte, Some(32)),
+    (b"cICP", false, Order::BeforePlte, Some(4)),
+    (b"gAMA", false, Order::BeforePlte, Some(4)),
+    (b"iCCP", false, Order::BeforePlte, None),
+    (b"mDCV", false, Order::BeforePlte, Some(24)),
+    (b"cLLI", false, Order::BeforePlte, Some(8)),
+    (b"sBIT", false, Order::BeforePlte, None),
+    (b"sRGB", false, Order::BeforePlte, Some(1)),
+    (b"bKGD", false, Order::AfterPlte, None),
+    (b"hIST", false, Order::AfterPlte, None),
+    (b"tRNS", false, Order::AfterPlte, None),
+    (b"eXIf", false, Order::Any, None),
+    (b"pHYs", false, Order::BeforeIdat, Some(9)),
+    (b"sPLT", true, Order::BeforeIdat, None),
+    (b"acTL", false, Order::BeforeIdat, Some(8)),
+    (b"fcTL", true, Order::Any, Some(26)),
+    (b"fdAT", true, Order::Any, None),
+    (b"tIME", false, Order::Any, Some(7)),
+    (b"iTXt", true, Order::Any, None),
+    (b"tEXt", true, Order::Any, None),
+    (b"zTXt", true, Order::Any, None),
+    (b"oFFs", false, Order::BeforeIdat, Some(9)),
+    (b"pCAL", false, Order::BeforeIdat, None),
+    (b"sCAL", false, Order::BeforeIdat, None),
+    (b"sTER", false, Order::BeforeIdat, None),
+];
+
+/// Checks `chunks` against the specification's ordering and multiplicity rules, returning every
+/// violation found.
+pub fn validate(chunks: &[Chunk]) -> Vec<Violation> {
+    let mut violations = vec![];
+    let position = |t: &ChunkType| chunks.iter().position(|c| c.r#type() == t);
+    let first_plte = position(&ChunkType::PLTE);
+    let first_idat = position(&ChunkType::IDAT);
+    let first_iend = position(&ChunkType::IEND);
+
+    let ihdr = match chunks.first() {
+        Some(chunk) if *chunk.r#type() == ChunkType::IHDR => match Ihdr::try_from(chunk) {
+            Ok(ihdr) => Some(ihdr),
+            Err(e) => {
+                violations.push(Violation::at(0, ViolationKind::InvalidIhdr(e.to_string())));
+                None
+            }
+        },
+        Some(_) => {
+            violations.push(Violation::at(0, ViolationKind::IhdrNotFirst));
+            None
+        }
+        None => None,
+    };
+    for required in [ChunkType::IHDR, ChunkType::IDAT, ChunkType::IEND] {
+        if position(&required).is_none() {
+            violations.push(Violation::global(ViolationKind::Missing(required)));
+        }
+    }
+
+    let mut previous_idat = None;
+    for (index, chunk) in chunks.iter().enumerate() {
+        let t = *chunk.r#type();
+        let mut violate = |kind| violations.push(Violation::at(index, kind));
+
+        if !t.is_reserved_bit_valid() {
+            violate(ViolationKind::ReservedBit(t));
+        }
+        if first_iend.is_some_and(|i| index > i) {
+            violate(ViolationKind::AfterIend(t));
+        }
+        if t == ChunkType::IDAT {
+            if previous_idat.is_some_and(|i| i + 1 != index) {
+                violate(ViolationKind::NonContiguousIdat);
+            }
+            previous_idat = Some(index);
+        }
+
+        let Some(&(_, multiple, order, length)) = RULES.iter().find(|(r, ..)| *r == t.as_bytes())
+        else {
+            if t.is_critical() {
+                violate(ViolationKind::UnknownCritical(t));
+            }
+            continue;
+        };
+        if length.is_some_and(|l| l != chunk.length())
+            || (t == ChunkType::PLTE && !(3..=768).contains(&chunk.length()))
+            || (t == ChunkType::PLTE && chunk.length() % 3 != 0)
+        {
+            violate(ViolationKind::InvalidLength(t, chunk.length()));
+        }
+        if !multiple && chunks[..index].iter().any(|c| *c.r#type() == t) {
+            violate(ViolationKind::Duplicate(t));
+        }
+        let after_idat = first_idat.is_some_and(|i| index > i);
+        match order {
+            Order::Any => {}
+            Order::BeforePlte => {
+                if first_plte.is_some_and(|i| index > i) {
+                    violate(ViolationKind::AfterPlte(t));
+                }
+                if after_idat {
+                    violate(ViolationKind::AfterIdat(t));
+                }
+            }
+            Order::BeforeIdat => {
+                if after_idat {
+                    violate(ViolationKind::AfterIdat(t));
+                }
+            }
+            Order::AfterPlte => {
+                if first_plte.is_some_and(|i| index < i) {
+                    violate(ViolationKind::BeforePlte(t));
+                }
+                if after_idat {
+                    violate(ViolationKind::AfterIdat(t));
+                }
+            }
+        }
+    }
+
+    if let Some(ihdr) = ihdr {
+        let color_type = ihdr.color_type();
+        match (color_type, first_plte) {
+            (ColorType::Indexed, None) => {
+                violations.push(Violation::global(ViolationKind::Missing(ChunkType::PLTE)));
+            }
+            (ColorType::Grayscale | ColorType::GrayscaleAlpha, Some(i)) => {
+                violations.push(Violation::at(
+                    i,
+                    ViolationKind::ColorTypeMismatch(ChunkType::PLTE, color_type),
+                ))
+            }
+            _ => {}
+        }
+        if color_type.has_alpha() {
+            if let Some(i) = position(&ChunkType::TRNS) {
+                violations.push(Violation::at(
+                    i,
+                    ViolationKind::ColorTypeMismatch(ChunkType::TRNS, color_type),
+                ));
+            }
+        }
+        violations.extend(palette_mismatches(chunks, &ihdr));
+        violations.extend(animation_violations(chunks, &ihdr));
+    }
+    if first_plte.is_none() {
+        if let Some(i) = position(&ChunkType::HIST) {
+            violations.push(Violation::at(
+                i,
+                ViolationKind::Requires(ChunkType::HIST, ChunkType::PLTE),
+            ));
+        }
+    }
+    if let (Some(_), Some(i)) = (position(&ChunkType::ICCP), position(&ChunkType::SRGB)) {
+        violations.push(Violation::at(
+            i,
+            ViolationKind::Exclusive(ChunkType::SRGB, ChunkType::ICCP),
+        ));
+    }
+
+    violations.sort_by_key(|v| v.index.map_or(0, |i| i + 1));
+    violations
+}
+
+/// Checks `PLTE` and the chunks that depend on it against the image header. Color types that
+/// forbid `PLTE` or `tRNS` outright are reported by [`validate`] itself.
+fn palette_mismatches(chunks: &[Chunk], ihdr: &Ihdr) -> Vec<Violation> {
+    let color_type = ihdr.color_type();
+    let find = |t: ChunkType| chunks.iter().enumerate().find(|(_, c)| *c.r#type() == t);
+    let palette = find(ChunkType::PLTE).and_then(|(_, c)| Palette::try_from(c).ok());
+    let checks = [
+        (
+            ChunkType::PLTE,
+            !matches!(color_type, ColorType::Grayscale | ColorType::GrayscaleAlpha),
+        ),
+        (ChunkType::TRNS, !color_type.has_alpha()),
+        (ChunkType::BKGD, true),
+        (ChunkType::HIST, palette.is_some()),
+    ];
+
+    let mut violations = vec![];
+    for (chunk_type, applies) in checks {
+        let Some((index, chunk)) = find(chunk_type).filter(|_| applies) else {
+            continue;
+        };
+        let result = match chunk_type {
+            // Malformed palettes are reported as invalid lengths.
+            ChunkType::PLTE => palette.as_ref().map_or(Ok(()), |p| p.check(ihdr)),
+            ChunkType::TRNS => Transparency::from_chunk(chunk, color_type)
+                .and_then(|t| t.check(ihdr, palette.as_ref())),
+            ChunkType::BKGD => Background::from_chunk(chunk, color_type)
+                .and_then(|b| b.check(ihdr, palette.as_ref())),
+            _ => Histogram::try_from(chunk)
+                .and_then(|h| h.check(palette.as_ref().expect("hIST is checked with PLTE"))),
+        };
+        if let Err(e) = result {
+            violations.push(Violation::at(
+                index,
+                ViolationKind::Mismatch(chunk_type, reason(e)),
+            ));
+        }
+    }
+    violations
+}
+
+/// Checks the `acTL`, `fcTL` and `fdAT` chunks of an animated PNG: sequence numbers count up
+/// from 0 in file order, the frame count matches `acTL` and every frame fits the canvas and has
+/// image data.
+fn animation_violations(chunks: &[Chunk], ihdr: &Ihdr) -> Vec<Violation> {
+    let mut violations = vec![];
+    let actl = chunks
+        .iter()
+        .find(|c| *c.r#type() == ChunkType::ACTL)
+        .and_then(|c| AnimationControl::try_from(c).ok());
+    let Some(actl) = actl else {
+        if let Some(i) = chunks
+            .iter()
+            .position(|c| matches!(*c.r#type(), ChunkType::FCTL | ChunkType::FDAT))
+        {
+            violations.push(Violation::at(
+                i,
+                ViolationKind::Requires(*chunks[i].r#type(), ChunkType::ACTL),
+            ));
+        }
+        return violations;
+    };
+
+    let mut expected = 0_u32;
+    let mut frames = 0;
+    let mut seen_idat = false;
+    // The `fcTL` whose frame is being read and whether it has data yet.
+    let mut open_frame: Option<(usize, bool)> = None;
+    let mismatch = |violations: &mut Vec<Violation>, index, t, reason| {
+        violations.push(Violation::at(index, ViolationKind::Mismatch(t, reason)));
+    };
+    let close = |violations: &mut Vec<Violation>, frame: Option<(usize, bool)>| {
+        if let Some((index, false)) = frame {
+            mismatch(
+                violations,
+                index,
+                ChunkType::FCTL,
+                "frame has no image data".to_owned(),
+            );
+        }
+    };
+    for (index, chunk) in chunks.iter().enumerate() {
+        let t = *chunk.r#type();
+        if let Some(number) = apng::sequence_number(chunk) {
+            if number != expected {
+                violations.push(Violation::at(
+                    index,
+                    ViolationKind::SequenceNumber(t, expected, number),
+                ));
+            }
+            expected = number.wrapping_add(1);
+        }
+        match t {
+            ChunkType::FCTL => {
+                frames += 1;
+                close(&mut violations, open_frame.take());
+                let Ok(fctl) = FrameControl::try_from(chunk) else {
+                    continue;
+                };
+                if let Err(e) = fctl.check(ihdr) {
+                    mismatch(&mut violations, index, t, reason(e));
+                }
+                let full_canvas = (
+                    fctl.width(),
+                    fctl.height(),
+                    fctl.x_offset(),
+                    fctl.y_offset(),
+                ) == (ihdr.width(), ihdr.height(), 0, 0);
+                if !seen_idat && !full_canvas {
+                    mismatch(
+                        &mut violations,
+                        index,
+                        t,
+                        "the default image frame must cover the canvas".to_owned(),
+                    );
+                }
+                open_frame = Some((index, false));
+            }
+            ChunkType::IDAT if !seen_idat => {
+                // The image data completes the default image's frame, if it is one.
+                seen_idat = true;
+                open_frame = None;
+            }
+            ChunkType::FDAT => match &mut open_frame {
+                Some((_, has_data)) => *has_data = true,
+                _ => violations.push(Violation::at(
+                    index,
+                    ViolationKind::Requires(ChunkType::FDAT, ChunkType::FCTL),
+                )),
+            },
+            _ => {}
+        }
+    }
+    close(&mut violations, open_frame);
+    if frames != actl.num_frames() as usize {
+        violations.push(Violation::global(ViolationKind::FrameCount(
+            actl.num_frames(),
+            frames,
+        )));
+    }
+    violations
+}
+
+/// The reason of a typed chunk parse or check error.
+fn reason(e: Error) -> String {
+    match e {
+        Error::MalformedChunk { reason, .. } => reason,
+        e => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::ihdr::Interlace;
+
+    /// A chunk with data of a valid length for its type.
+    fn chunk(chunk_type: &str) -> Chunk {
+        let chunk_type = ChunkType::from_str(chunk_type).unwrap();
+        let length = match RULES.iter().find(|r| r.0 == chunk_type.as_bytes()) {
+            _ if chunk_type == ChunkType::PLTE => 3,
+            Some((.., Some(length))) => *length as usize,
+            _ => 0,
+        };
+        Chunk::new(chunk_type, vec![0; length])
+    }
+
+    fn ihdr(color_type: ColorType) -> Chunk {
+        Ihdr::new(1, 1, 8, color_type, Interlace::None)
+            .unwrap()
+            .to_chunk()
+    }
+
+    fn kinds(chunks: &[Chunk]) -> Vec<(Option<usize>, String)> {
+        validate(chunks)
+            .into_iter()
+            .map(|v| (v.index(), v.kind().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_png() {
+        let chunks = [
+            ihdr(ColorType::Indexed),
+            chunk("gAMA"),
+            chunk("PLTE"),
+            chunk("tRNS"),
+            chunk("IDAT"),
+            chunk("IDAT"),
+            chunk("tEXt"),
+            chunk("IEND"),
+        ];
+        assert!(validate(&chunks).is_empty());
+    }
+
+    #[test]
+    fn test_missing_chunks() {
+        assert_eq!(
+            kinds(&[]),
+            [
+                (None, "missing required IHDR chunk".to_owned()),
+                (None, "missing required IDAT chunk".to_owned()),
+                (None, "missing required IEND chunk".to_owned()),
+            ]
+        );
+        assert_eq!(
+            kinds(&[ihdr(ColorType::Indexed), chunk("IDAT"), chunk("IEND")]),
+            [(None, "missing required PLTE chunk".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_ordering_violations() {
+        let chunks = [
+            chunk("gAMA"),
+            ihdr(ColorType::Rgb),
+            chunk("tRNS"),
+            chunk("PLTE"),
+            chunk("IDAT"),
+            chunk("pHYs"),
+            chunk("IDAT"),
+            chunk("IEND"),
+            chunk("tEXt"),
+        ];
+        assert_eq!(
+            kinds(&chunks),
+            [
+                (Some(0), "first chunk is not IHDR".to_owned()),
+                (Some(2), "tRNS must follow PLTE".to_owned()),
+                (Some(5), "pHYs must precede IDAT".to_owned()),
+                (Some(6), "IDAT chunks are not consecutive".to_owned()),
+                (Some(8), "tEXt follows IEND".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiplicity_and_color_type_violations() {
+        let chunks = [
+            ihdr(ColorType::GrayscaleAlpha),
+            chunk("IHDR"),
+            chunk("sRGB"),
+            chunk("iCCP"),
+            chunk("PLTE"),
+            chunk("tRNS"),
+            chunk("tRNS"),
+            chunk("IDAT"),
+            chunk("IEND"),
+            chunk("IEND"),
+        ];
+        assert_eq!(
+            kinds(&chunks),
+            [
+                (Some(1), "IHDR may appear only once".to_owned()),
+                (
+                    Some(2),
+                    "sRGB must not appear together with iCCP".to_owned()
+                ),
+                (
+                    Some(4),
+                    "PLTE is not allowed for color type grayscale+alpha".to_owned()
+                ),
+                (
+                    Some(5),
+                    "tRNS is not allowed for color type grayscale+alpha".to_owned()
+                ),
+                (Some(6), "tRNS may appear only once".to_owned()),
+                (Some(9), "IEND follows IEND".to_owned()),
+                (Some(9), "IEND may appear only once".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        let chunks = [
+            ihdr(ColorType::Rgb),
+            Chunk::new(ChunkType::from_str("gAMA").unwrap(), [0; 3]),
+            Chunk::new(ChunkType::from_str("PLTE").unwrap(), [0; 4]),
+            chunk("IDAT"),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), [0]),
+        ];
+        assert_eq!(
+            kinds(&chunks),
+            [
+                (Some(1), "gAMA has invalid length 3".to_owned()),
+                (Some(2), "PLTE has invalid length 4".to_owned()),
+                (Some(4), "IEND has invalid length 1".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_palette_mismatches() {
+        let chunks = [
+            Ihdr::new(1, 1, 1, ColorType::Indexed, Interlace::None)
+                .unwrap()
+                .to_chunk(),
+            Chunk::new(ChunkType::PLTE, [0; 9]),
+            Chunk::new(ChunkType::TRNS, [0; 4]),
+            Chunk::new(ChunkType::BKGD, [3]),
+            Chunk::new(ChunkType::HIST, [0; 4]),
+            chunk("IDAT"),
+            chunk("IEND"),
+        ];
+        assert_eq!(
+            kinds(&chunks),
+            [
+                (
+                    Some(1),
+                    "PLTE does not match the image: 3 entries do not fit in 1 bits".to_owned()
+                ),
+                (
+                    Some(2),
+                    "tRNS does not match the image: 4 alpha values for 3 palette entries"
+                        .to_owned()
+                ),
+                (
+                    Some(3),
+                    "bKGD does not match the image: index 3 is outside the 3-entry palette"
+                        .to_owned()
+                ),
+                (
+                    Some(4),
+                    "hIST does not match the image: 2 entries for 3 palette entries".to_owned()
+                ),
+            ]
+        );
+
+        let chunks = [
+            ihdr(ColorType::Rgb),
+            Chunk::new(ChunkType::TRNS, [0; 2]),
+            chunk("IDAT"),
+            chunk("IEND"),
+        ];
+        assert_eq!(
+            kinds(&chunks),
+            [(
+                Some(1),
+                "tRNS does not match the image: expected 6 bytes, found 2".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_animation_violations() {
+        let fctl = |sequence_number, width| {
+            FrameControl::new(
+                sequence_number,
+                width,
+                1,
+                0,
+                0,
+                1,
+                10,
+                apng::DisposeOp::None,
+                apng::BlendOp::Source,
+            )
+            .to_chunk()
+        };
+        let fdat = |sequence_number| apng::frame_data_chunk(sequence_number, &[0]);
+        let animated = |frames: &[Chunk]| {
+            let mut chunks = vec![
+                ihdr(ColorType::Rgb),
+                AnimationControl::new(2, 0).to_chunk(),
+                fctl(0, 1),
+                chunk("IDAT"),
+            ];
+            chunks.extend_from_slice(frames);
+            chunks.push(chunk("IEND"));
+            chunks
+        };
+        assert!(validate(&animated(&[fctl(1, 1), fdat(2), fdat(3)])).is_empty());
+
+        assert_eq!(
+            kinds(&animated(&[fctl(1, 2), fdat(3), chunk("tEXt"), fdat(3)])),
+            [
+                (
+                    Some(4),
+                    "fcTL does not match the image: 2x1 frame at (0, 0) exceeds the 1x1 canvas"
+                        .to_owned()
+                ),
+                (Some(5), "fdAT has sequence number 3, expected 2".to_owned()),
+                (Some(7), "fdAT has sequence number 3, expected 4".to_owned()),
+            ]
+        );
+        assert_eq!(
+            kinds(&animated(&[fdat(1), fctl(2, 1)])),
+            [
+                (Some(4), "fdAT requires a fcTL chunk".to_owned()),
+                (
+                    Some(5),
+                    "fcTL does not match the image: frame has no image data".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(
+            kinds(&animated(&[])),
+            [(None, "acTL declares 2 frames, found 1".to_owned())]
+        );
+
+        let chunks = [ihdr(ColorType::Rgb), chunk("IDAT"), fdat(0), chunk("IEND")];
+        assert_eq!(
+            kinds(&chunks),
+            [(Some(2), "fdAT requires a acTL chunk".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_unknown_chunks() {
+        let chunks = [
+            ihdr(ColorType::Rgb),
+            chunk("RuSt"),
+            chunk("ruSt"),
+            chunk("rust"),
+            chunk("IDAT"),
+            chunk("IEND"),
+        ];
+        assert_eq!(
+            kinds(&chunks),
+            [
+                (Some(1), "unknown critical chunk RuSt".to_owned()),
+                (Some(3), "rust has the reserved bit set".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Streaming PNG serialization over [`std::io::Write`].
+
+use std::io::{self, Write};
+
+use crate::{
+    chunk::{Chunk, Crc32},
+    chunk_type::ChunkType,
+    png::Png,
+    Error,
+};
+
+/// Writes a PNG signature followed by chunks, without building the file in memory.
+///
+/// ```
+/// # use pngme::{Chunk, ChunkType, Png, writer::PngWriter};
+/// # use std::{io::Write, str::FromStr};
+/// let mut writer = PngWriter::new(Vec::new())?;
+/// writer.write_chunk(&Chunk::new(ChunkType::from_str("ruSt")?, "message"))?;
+///
+/// let mut chunk = writer.start_chunk(ChunkType::from_str("ruSt")?, 7)?;
+/// chunk.write_all(b"mes")?;
+/// chunk.write_all(b"sage")?;
+/// chunk.finish()?;
+///
+/// let png = Png::try_from(writer.finish()?.as_slice())?;
+/// assert_eq!(png.chunks()[0].crc(), png.chunks()[1].crc());
+/// # Ok::<(), pngme::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct PngWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> PngWriter<W> {
+    /// Writes the PNG signature.
+    pub fn new(mut inner: W) -> Result<Self, Error> {
+        inner.write_all(&Png::STANDARD_HEADER)?;
+        Ok(Self { inner })
+    }
+    /// Writes a whole chunk.
+    pub fn write_chunk(&mut self, chunk: &Chunk) -> Result<(), Error> {
+        let mut writer = self.start_chunk(*chunk.r#type(), chunk.length())?;
+        writer.write_all(chunk.data())?;
+        writer.finish()
+    }
+    /// Starts a chunk whose `length` bytes of data are written through the returned
+    /// [`ChunkWriter`].
+    pub fn start_chunk(
+        &mut self,
+        r#type: ChunkType,
+        length: u32,
+    ) -> Result<ChunkWriter<'_, W>, Error> {
+        if length > Chunk::MAX_LENGTH {
+            return Err(Error::InvalidLength { offset: 0, length });
+        }
+        self.inner.write_all(&length.to_be_bytes())?;
+        self.inner.write_all(r#type.as_bytes())?;
+        let mut crc = Crc32::new();
+        crc.update(r#type.as_bytes());
+
+        Ok(ChunkWriter {
+            inner: &mut self.inner,
+            remaining: length,
+            crc,
+        })
+    }
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Writes one chunk's data, computing its CRC as the bytes go by.
+///
+/// Call [`ChunkWriter::finish`] once all data has been written to append the CRC.
+#[derive(Debug)]
+pub struct ChunkWriter<'a, W: Write> {
+    inner: &'a mut W,
+    remaining: u32,
+    crc: Crc32,
+}
+
+impl<W: Write> Write for ChunkWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk data exceeds declared length",
+            ));
+        }
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        self.remaining -= n as u32;
+        Ok(n)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> ChunkWriter<'_, W> {
+    /// Writes the CRC, failing if less data was written than the declared length.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.remaining > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("chunk data is {} bytes short", self.remaining),
+            )
+            .into());
+        }
+        self.inner.write_all(&self.crc.finish().to_be_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_write_chunks() {
+        let chunks = [
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), [0; 13]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), "message"),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), []),
+        ];
+        let mut writer = PngWriter::new(vec![]).unwrap();
+        for chunk in &chunks {
+            writer.write_chunk(chunk).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), Png::from_chunks(chunks).bytes());
+    }
+
+    #[test]
+    fn test_chunk_writer_crc() {
+        let mut writer = PngWriter::new(vec![]).unwrap();
+        let mut chunk = writer
+            .start_chunk(ChunkType::from_str("RuSt").unwrap(), 42)
+            .unwrap();
+        for word in "This is where your secret message will be!".split_inclusive(' ') {
+            chunk.write_all(word.as_bytes()).unwrap();
+        }
+        chunk.finish().unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes[bytes.len() - 4..], 2882656334_u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_chunk_writer_length_mismatch() {
+        let mut writer = PngWriter::new(vec![]).unwrap();
+        let mut chunk = writer
+            .start_chunk(ChunkType::from_str("RuSt").unwrap(), 4)
+            .unwrap();
+        assert!(chunk.write_all(b"too long").is_err());
+        chunk.write_all(b"abc").unwrap();
+        assert!(chunk.finish().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! X25519 Diffie-Hellman key agreement (RFC 7748).
+
+use std::str::FromStr;
+
+use crate::{envelope::random_bytes, field25519::Fe, hex, Error};
+
+/// The u-coordinate of the Curve25519 base point.
+const BASE_POINT: [u8; 32] = {
+    let mut point = [0; 32];
+    point[0] = 9;
+    point
+};
+
+/// An X25519 public key, written as 64 hex digits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct PublicKey([u8; 32]);
+
+impl FromStr for PublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s.trim())
+            .map(Self)
+            .ok_or_else(|| Error::InvalidKey("expected 64 hex digits".to_owned()))
+    }
+}
+
+impl std::fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl PublicKey {
+    /// Wraps the 32-byte little-endian u-coordinate.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+    /// The 32-byte little-endian u-coordinate.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// An X25519 secret key. Its `Debug` output hides the key.
+#[derive(PartialEq, Eq, Clone)]
+pub struct SecretKey([u8; 32]);
+
+impl FromStr for SecretKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s.trim())
+            .map(Self)
+            .ok_or_else(|| Error::InvalidKey("expected 64 hex digits".to_owned()))
+    }
+}
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl SecretKey {
+    /// Generates a key from the operating system's random number generator.
+    pub fn generate() -> Result<Self, Error> {
+        random_bytes().map(Self)
+    }
+    /// Wraps 32 random bytes. Clamping happens when the key is used.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+    /// The secret key as 64 hex digits, for storing in a key file.
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+    /// The matching public key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519(&self.0, &BASE_POINT))
+    }
+    /// The secret shared with the holder of `public_key`. Fails for low-order public keys,
+    /// which would make the result independent of this key.
+    pub fn diffie_hellman(&self, public_key: &PublicKey) -> Result<[u8; 32], Error> {
+        let shared = x25519(&self.0, &public_key.0);
+        if shared == [0; 32] {
+            return Err(Error::InvalidKey("public key has low order".to_owned()));
+        }
+        Ok(shared)
+    }
+}
+
+/// Multiplies the point with u-coordinate `u` by the clamped `scalar` using the Montgomery
+/// ladder, in time independent of the scalar.
+fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+
+    let a24 = Fe::from_u64(121_665);
+    let x1 = Fe::from_bytes(u);
+    let (mut x2, mut z2, mut x3, mut z3) = (Fe::ONE, Fe::ZERO, x1, Fe::ONE);
+    let mut swap = 0;
+    for t in (0..255).rev() {
+        let bit = u64::from(k[t / 8] >> (t % 8) & 1);
+        swap ^= bit;
+        Fe::swap(&mut x2, &mut x3, swap);
+        Fe::swap(&mut z2, &mut z3, swap);
+        swap = bit;
+
+        let a = x2 + z2;
+        let aa = a.square();
+        let b = x2 - z2;
+        let bb = b.square();
+        let e = aa - bb;
+        let da = (x3 - z3) * a;
+        let cb = (x3 + z3) * b;
+        x3 = (da + cb).square();
+        z3 = x1 * (da - cb).square();
+        x2 = aa * bb;
+        z2 = e * (aa + a24 * e);
+    }
+    Fe::swap(&mut x2, &mut x3, swap);
+    Fe::swap(&mut z2, &mut z3, swap);
+    (x2 * z2.invert()).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_x25519() {
+        // RFC 7748 section 5.2.
+        assert_eq!(
+            x25519(
+                &bytes("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &bytes("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"),
+            ),
+            bytes("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+        assert_eq!(
+            x25519(
+                &bytes("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d"),
+                &bytes("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493"),
+            ),
+            bytes("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957")
+        );
+    }
+
+    #[test]
+    fn test_diffie_hellman() {
+        // RFC 7748 section 6.1.
+        let alice: SecretKey = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"
+            .parse()
+            .unwrap();
+        let bob: SecretKey = "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            alice.public_key().to_string(),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        let shared = alice.diffie_hellman(&bob.public_key()).unwrap();
+        assert_eq!(shared, bob.diffie_hellman(&alice.public_key()).unwrap());
+        assert_eq!(
+            hex::encode(&shared),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+
+        assert!(alice
+            .diffie_hellman(&PublicKey::from_bytes([0; 32]))
+            .is_err());
+        assert!("abc".parse::<PublicKey>().is_err());
+        assert_eq!(format!("{alice:?}"), "SecretKey(..)");
+    }
+}
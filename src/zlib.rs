@@ -0,0 +1,175 @@
+//! zlib framing (RFC 1950) around DEFLATE streams, as used by `IDAT`, `zTXt`, `iTXt` and `iCCP`.
+
+use crate::{
+    deflate::{deflate, Compression},
+    inflate::inflate,
+    Error,
+};
+
+/// Compresses `data` into a zlib stream.
+pub fn compress(data: &[u8], level: Compression) -> Vec<u8> {
+    const CMF: u8 = 0x78;
+
+    let flg = level.zlib_level() << 6;
+    let flg = flg + (31 - (u16::from_be_bytes([CMF, flg]) % 31) as u8);
+    let mut out = vec![CMF, flg];
+    out.extend_from_slice(&deflate(data, level));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Decompresses a zlib stream, verifying its header and Adler-32 checksum.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    decompress_limited(data, usize::MAX)
+}
+
+/// Like [`decompress`], but fails once the output would grow beyond `limit` bytes.
+pub fn decompress_limited(data: &[u8], limit: usize) -> Result<Vec<u8>, Error> {
+    let [cmf, flg, ..] = *data else {
+        return Err(Error::Decompress("missing zlib header".to_owned()));
+    };
+    if cmf & 0x0f != 8 || cmf >> 4 > 7 {
+        return Err(Error::Decompress(format!(
+            "unsupported compression method {cmf:#04x}"
+        )));
+    }
+    if u16::from_be_bytes([cmf, flg]) % 31 != 0 {
+        return Err(Error::Decompress("zlib header check failed".to_owned()));
+    }
+    if flg & 0x20 != 0 {
+        return Err(Error::Decompress(
+            "preset dictionaries are not supported".to_owned(),
+        ));
+    }
+
+    let (out, used) = inflate(&data[2..], limit)?;
+    let trailer = data
+        .get(2 + used..2 + used + 4)
+        .ok_or_else(|| Error::Decompress("missing Adler-32 checksum".to_owned()))?;
+    let stored = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let computed = adler32(&out);
+    if stored != computed {
+        return Err(Error::Decompress(format!(
+            "Adler-32 mismatch: stored {stored:#010x}, computed {computed:#010x}"
+        )));
+    }
+    Ok(out)
+}
+
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    // Largest block for which the sums cannot overflow before reduction.
+    const BLOCK: usize = 5552;
+
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for block in data.chunks(BLOCK) {
+        for &byte in block {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORED: [u8; 30] = [
+        120, 1, 1, 19, 0, 236, 255, 104, 101, 108, 108, 111, 44, 32, 115, 116, 111, 114, 101, 100,
+        32, 98, 108, 111, 99, 107, 70, 206, 7, 29,
+    ];
+    const FIXED: [u8; 16] = [
+        120, 156, 203, 72, 205, 201, 201, 87, 200, 64, 39, 1, 104, 3, 8, 177,
+    ];
+
+    /// 300 pseudo-random lowercase letters, compressed by zlib into a dynamic Huffman block.
+    fn dynamic_text() -> Vec<u8> {
+        (0..300_usize)
+            .map(|i| ((i * i * 7 + i / 3) % 251 % 26) as u8 + b'a')
+            .collect()
+    }
+
+    #[rustfmt::skip]
+    const DYNAMIC: [u8; 204] = [
+        120, 218, 29, 206, 137, 149, 5, 33, 8, 0, 193, 88, 65, 197, 99, 68, 69, 188, 163, 223,
+        125, 63, 129, 174, 134, 96, 56, 77, 43, 93, 198, 136, 216, 119, 157, 237, 12, 103, 234,
+        229, 90, 117, 36, 59, 97, 192, 38, 83, 11, 100, 96, 167, 217, 248, 182, 229, 46, 178, 34,
+        69, 252, 96, 92, 69, 243, 71, 149, 207, 254, 102, 42, 69, 151, 241, 178, 229, 45, 114, 34,
+        20, 78, 182, 169, 56, 152, 88, 206, 36, 250, 15, 50, 107, 198, 211, 86, 104, 121, 231, 115,
+        249, 84, 253, 96, 154, 136, 111, 57, 117, 86, 52, 124, 70, 82, 95, 46, 111, 167, 6, 74, 65,
+        55, 111, 63, 144, 232, 30, 85, 65, 32, 191, 158, 184, 75, 48, 4, 63, 31, 188, 166, 11, 178,
+        202, 168, 132, 60, 168, 217, 223, 54, 219, 100, 18, 157, 233, 43, 50, 150, 58, 22, 75, 108,
+        169, 215, 64, 138, 13, 187, 243, 220, 2, 83, 137, 189, 165, 184, 253, 138, 175, 200, 193, 9,
+        211, 190, 20, 173, 85, 59, 194, 171, 10, 244, 60, 230, 56, 76, 147, 159, 18, 254, 0, 77,
+        252, 127, 201,
+    ];
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b"Wikipedia"), 300286872);
+        assert_eq!(adler32(&[]), 1);
+    }
+
+    #[test]
+    fn test_decompress_stored() {
+        assert_eq!(decompress(&STORED).unwrap(), b"hello, stored block");
+    }
+
+    #[test]
+    fn test_decompress_fixed() {
+        assert_eq!(decompress(&FIXED).unwrap(), b"hello hello hello hello");
+    }
+
+    #[test]
+    fn test_decompress_dynamic() {
+        assert_eq!(decompress(&DYNAMIC).unwrap(), dynamic_text());
+    }
+
+    #[test]
+    fn test_decompress_limit() {
+        assert!(decompress_limited(&FIXED, 23).is_ok());
+        assert!(matches!(
+            decompress_limited(&FIXED, 22),
+            Err(Error::Decompress(_))
+        ));
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let data = dynamic_text().repeat(20);
+        for level in [
+            Compression::None,
+            Compression::Fast,
+            Compression::Default,
+            Compression::Best,
+        ] {
+            let compressed = compress(&data, level);
+            assert_eq!(u16::from_be_bytes([compressed[0], compressed[1]]) % 31, 0);
+            assert_eq!(decompress(&compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decompress_corrupt() {
+        let mut bad_checksum = FIXED;
+        bad_checksum[15] ^= 1;
+        let mut bad_header = FIXED;
+        bad_header[1] ^= 1;
+        let mut bad_data = DYNAMIC;
+        bad_data[20] ^= 0xff;
+
+        for data in [
+            &bad_checksum[..],
+            &bad_header,
+            &bad_data,
+            &FIXED[..10],
+            &STORED[..20],
+            &[],
+        ] {
+            assert!(matches!(decompress(data), Err(Error::Decompress(_))));
+        }
+    }
+}